@@ -1,6 +1,45 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Minimal port of the Firefox hash, as used in `rustc-hash`, to compare
+/// [`Family`] with a fast non-cryptographic hasher against the default
+/// SipHash.
+#[derive(Default)]
+struct FxHasher {
+    hash: u64,
+}
+
+impl FxHasher {
+    fn add_to_hash(&mut self, word: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut chunks = bytes.chunks_exact(8);
+        for chunk in &mut chunks {
+            self.add_to_hash(u64::from_le_bytes(chunk.try_into().unwrap()));
+        }
+        for byte in chunks.remainder() {
+            self.add_to_hash(u64::from(*byte));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.add_to_hash(u64::from(i));
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.add_to_hash(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+}
 
 pub fn family(c: &mut Criterion) {
     c.bench_function(
@@ -42,6 +81,26 @@ pub fn family(c: &mut Criterion) {
         })
     });
 
+    c.bench_function(
+        "counter family with Vec<(String, String)> label set and Fx hasher",
+        |b| {
+            let family =
+                Family::<Vec<(String, String)>, Counter, _, _>::with_hasher(BuildHasherDefault::<
+                    FxHasher,
+                >::default(
+                ));
+
+            b.iter(|| {
+                family
+                    .get_or_create(&vec![
+                        ("method".to_owned(), "GET".to_owned()),
+                        ("status".to_owned(), "200".to_owned()),
+                    ])
+                    .inc();
+            })
+        },
+    );
+
     c.bench_function("counter family with custom type label set", |b| {
         #[derive(Clone, Hash, PartialEq, Eq)]
         struct Labels {
@@ -0,0 +1,52 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use prometheus_client::metrics::counter::{Counter, ShardedCounter};
+use std::time::{Duration, Instant};
+
+/// Increment `inc` from `threads` threads concurrently, `iters` times each,
+/// returning the wall-clock time it took.
+fn contended(threads: usize, iters: u64, inc: impl Fn() + Sync) -> Duration {
+    let start = Instant::now();
+    std::thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                for _ in 0..iters {
+                    inc();
+                }
+            });
+        }
+    });
+    start.elapsed()
+}
+
+pub fn sharded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("contended counter");
+
+    for threads in [1, 2, 4, 8, 16] {
+        group.bench_with_input(
+            BenchmarkId::new("Counter", threads),
+            &threads,
+            |b, &threads| {
+                let counter: Counter = Counter::default();
+                b.iter_custom(|iters| {
+                    contended(threads, iters, || {
+                        counter.inc();
+                    })
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("ShardedCounter", threads),
+            &threads,
+            |b, &threads| {
+                let counter = ShardedCounter::default();
+                b.iter_custom(|iters| contended(threads, iters, || counter.inc()))
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, sharded);
+criterion_main!(benches);
@@ -12,55 +12,142 @@ use quote::quote;
 use syn::DeriveInput;
 
 /// Derive `prometheus_client::encoding::EncodeLabelSet`.
+///
+/// Fields annotated with `#[prometheus(flatten)]` are encoded via their own
+/// `EncodeLabelSet` implementation. `#[prometheus(flatten, prefix = "...")]`
+/// additionally prepends the given prefix to each of their label keys.
+///
+/// The label key of a field is its name, unless renamed via
+/// `#[prometheus(rename = "...")]`, e.g. for keys which are not valid Rust
+/// identifiers.
+///
+/// Fields annotated with `#[prometheus(skip_encoding_if = "path")]`, e.g.
+/// `"Option::is_none"`, are not encoded when the function at `path` returns
+/// `true` for a reference to the field.
+///
+/// Structs annotated with `#[prometheus(skip_none)]` skip all `Option` fields
+/// that are `None`, as if each was annotated with
+/// `#[prometheus(skip_encoding_if = "Option::is_none")]`. A `Some` value is
+/// encoded via the inner type's `EncodeLabelValue` implementation. Fields are
+/// detected as `Option` by their type being spelled `Option<...>`, thus not
+/// through type aliases.
+///
+/// Single-field tuple structs delegate to the `EncodeLabelSet` implementation
+/// of their field.
+///
+/// For generic structs, each field type is required to implement
+/// `EncodeLabelValue`, or `EncodeLabelSet` if flattened.
 #[proc_macro_derive(EncodeLabelSet, attributes(prometheus))]
 pub fn derive_encode_label_set(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     let name = &ast.ident;
+    let is_generic = ast.generics.type_params().next().is_some();
+    let mut bounds: Vec<syn::WherePredicate> = Vec::new();
+    let skip_none = match skip_none(&ast.attrs) {
+        Ok(skip_none) => skip_none,
+        Err(e) => return e.to_compile_error().into(),
+    };
 
     let body: TokenStream2 = match ast.clone().data {
         syn::Data::Struct(s) => match s.fields {
-            syn::Fields::Named(syn::FieldsNamed { named, .. }) => named
-                .into_iter()
-                .map(|f| {
-                    let attribute = f
-                        .attrs
-                        .iter()
-                        .find(|a| a.path().is_ident("prometheus"))
-                        .map(|a| a.parse_args::<syn::Ident>().unwrap().to_string());
-                    let flatten = match attribute.as_deref() {
-                        Some("flatten") => true,
-                        Some(other) => {
-                            panic!("Provided attribute '{other}', but only 'flatten' is supported")
-                        }
-                        None => false,
-                    };
-                    let ident = f.ident.unwrap();
-                    if flatten {
-                        quote! {
-                             EncodeLabelSet::encode(&self.#ident, encoder)?;
+            syn::Fields::Named(syn::FieldsNamed { named, .. }) => {
+                let fields = named
+                    .into_iter()
+                    .map(|f| {
+                        let mut attrs = LabelSetFieldAttrs::parse(&f.attrs)?;
+                        let ident = f.ident.unwrap();
+                        let ty = f.ty;
+                        if skip_none
+                            && !attrs.flatten
+                            && attrs.skip_encoding_if.is_none()
+                            && is_option(&ty)
+                        {
+                            attrs.skip_encoding_if =
+                                Some(syn::parse_quote! { std::option::Option::is_none });
                         }
-                    } else {
-                        let ident_string = KEYWORD_IDENTIFIERS
-                            .iter()
-                            .find(|pair| ident == pair.1)
-                            .map(|pair| pair.0.to_string())
-                            .unwrap_or_else(|| ident.to_string());
-
-                        quote! {
-                            let mut label_encoder = encoder.encode_label();
-                            let mut label_key_encoder = label_encoder.encode_label_key()?;
-                            EncodeLabelKey::encode(&#ident_string, &mut label_key_encoder)?;
-
-                            let mut label_value_encoder = label_key_encoder.encode_label_value()?;
-                            EncodeLabelValue::encode(&self.#ident, &mut label_value_encoder)?;
-
-                            label_value_encoder.finish()?;
+                        if is_generic {
+                            bounds.push(if attrs.flatten {
+                                syn::parse_quote! { #ty: prometheus_client::encoding::EncodeLabelSet }
+                            } else {
+                                syn::parse_quote! { #ty: prometheus_client::encoding::EncodeLabelValue }
+                            });
                         }
-                    }
-                })
-                .collect(),
-            syn::Fields::Unnamed(_) => {
-                panic!("Can not derive Encode for struct with unnamed fields.")
+                        let encode = if attrs.flatten {
+                            if let Some(rename) = attrs.rename {
+                                return Err(syn::Error::new_spanned(
+                                    rename,
+                                    "rename is not supported on flattened fields",
+                                ));
+                            }
+                            let encoder = match attrs.prefix {
+                                Some(prefix) => quote! { encoder.by_ref().with_key_prefix(#prefix) },
+                                None => quote! { encoder.by_ref() },
+                            };
+                            quote! {
+                                 EncodeLabelSet::encode(&self.#ident, #encoder)?;
+                            }
+                        } else {
+                            if let Some(prefix) = attrs.prefix {
+                                return Err(syn::Error::new_spanned(
+                                    prefix,
+                                    "prefix is only supported on flattened fields",
+                                ));
+                            }
+
+                            let ident_string = match attrs.rename {
+                                Some(rename) => rename.value(),
+                                None => KEYWORD_IDENTIFIERS
+                                    .iter()
+                                    .find(|pair| ident == pair.1)
+                                    .map(|pair| pair.0.to_string())
+                                    .unwrap_or_else(|| ident.to_string()),
+                            };
+
+                            quote! {
+                                let mut label_encoder = encoder.encode_label();
+                                let mut label_key_encoder = label_encoder.encode_label_key()?;
+                                EncodeLabelKey::encode(&#ident_string, &mut label_key_encoder)?;
+
+                                let mut label_value_encoder = label_key_encoder.encode_label_value()?;
+                                EncodeLabelValue::encode(&self.#ident, &mut label_value_encoder)?;
+
+                                label_value_encoder.finish()?;
+                            }
+                        };
+
+                        Ok(match attrs.skip_encoding_if {
+                            Some(skip_encoding_if) => quote! {
+                                if !#skip_encoding_if(&self.#ident) {
+                                    #encode
+                                }
+                            },
+                            None => encode,
+                        })
+                    })
+                    .collect::<syn::Result<TokenStream2>>();
+                match fields {
+                    Ok(fields) => fields,
+                    Err(e) => return e.to_compile_error().into(),
+                }
+            }
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                if is_generic {
+                    let ty = &fields.unnamed[0].ty;
+                    bounds.push(syn::parse_quote! {
+                        #ty: prometheus_client::encoding::EncodeLabelSet
+                    });
+                }
+                quote! {
+                    prometheus_client::encoding::EncodeLabelSet::encode(&self.0, encoder)?;
+                }
+            }
+            syn::Fields::Unnamed(fields) => {
+                return syn::Error::new_spanned(
+                    fields,
+                    "Can not derive EncodeLabelSet for tuple struct with more than one field.",
+                )
+                .to_compile_error()
+                .into();
             }
             syn::Fields::Unit => panic!("Can not derive Encode for struct with unit field."),
         },
@@ -70,8 +157,12 @@ pub fn derive_encode_label_set(input: TokenStream) -> TokenStream {
         syn::Data::Union(_) => panic!("Can not derive Encode for union."),
     };
 
+    let mut generics = ast.generics.clone();
+    generics.make_where_clause().predicates.extend(bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
     let gen = quote! {
-        impl prometheus_client::encoding::EncodeLabelSet for #name {
+        impl #impl_generics prometheus_client::encoding::EncodeLabelSet for #name #ty_generics #where_clause {
             fn encode(&self, mut encoder: prometheus_client::encoding::LabelSetEncoder) -> std::result::Result<(), std::fmt::Error> {
                 use prometheus_client::encoding::EncodeLabel;
                 use prometheus_client::encoding::EncodeLabelKey;
@@ -87,8 +178,89 @@ pub fn derive_encode_label_set(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// Extract the struct level `#[prometheus(skip_none)]` attribute of a struct
+/// deriving `EncodeLabelSet`.
+fn skip_none(attrs: &[syn::Attribute]) -> syn::Result<bool> {
+    let mut skip_none = false;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("prometheus")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("skip_none") {
+                return Err(meta.error("only 'skip_none' is supported on structs"));
+            }
+            skip_none = true;
+            Ok(())
+        })?;
+    }
+    Ok(skip_none)
+}
+
+/// Whether the given type is spelled `Option<...>`, optionally with a path.
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
+
+/// Attributes of a field of a struct deriving `EncodeLabelSet`.
+struct LabelSetFieldAttrs {
+    /// `#[prometheus(flatten)]`
+    flatten: bool,
+    /// `#[prometheus(prefix = "...")]`
+    prefix: Option<syn::LitStr>,
+    /// `#[prometheus(skip_encoding_if = "...")]`
+    skip_encoding_if: Option<syn::ExprPath>,
+    /// `#[prometheus(rename = "...")]`
+    rename: Option<syn::LitStr>,
+}
+
+impl LabelSetFieldAttrs {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut field_attrs = LabelSetFieldAttrs {
+            flatten: false,
+            prefix: None,
+            skip_encoding_if: None,
+            rename: None,
+        };
+        for attr in attrs.iter().filter(|a| a.path().is_ident("prometheus")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("flatten") {
+                    field_attrs.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("prefix") {
+                    field_attrs.prefix = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("skip_encoding_if") {
+                    let path: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.skip_encoding_if = Some(path.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    let rename: syn::LitStr = meta.value()?.parse()?;
+                    if rename.value().is_empty() {
+                        return Err(syn::Error::new_spanned(rename, "rename must not be empty"));
+                    }
+                    field_attrs.rename = Some(rename);
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "only 'flatten', 'prefix', 'skip_encoding_if' and 'rename' are supported",
+                    ))
+                }
+            })?;
+        }
+        Ok(field_attrs)
+    }
+}
+
 /// Derive `prometheus_client::encoding::EncodeLabelValue`.
-#[proc_macro_derive(EncodeLabelValue)]
+///
+/// Each unit enum variant is encoded as its identifier, unless renamed via
+/// `#[prometheus(rename = "...")]`. Variants with a single unnamed field
+/// delegate to the field's `EncodeLabelValue` implementation.
+#[proc_macro_derive(EncodeLabelValue, attributes(prometheus))]
 pub fn derive_encode_label_value(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).unwrap();
     let name = &ast.ident;
@@ -98,15 +270,40 @@ pub fn derive_encode_label_value(input: TokenStream) -> TokenStream {
             panic!("Can not derive EncodeLabel for struct.")
         }
         syn::Data::Enum(syn::DataEnum { variants, .. }) => {
-            let match_arms: TokenStream2 = variants
+            let match_arms = variants
                 .into_iter()
                 .map(|v| {
                     let ident = v.ident;
-                    quote! {
-                        #name::#ident => encoder.write_str(stringify!(#ident))?,
+                    let rename = rename(&v.attrs)?;
+                    match v.fields {
+                        syn::Fields::Unit => {
+                            let value = rename.unwrap_or_else(|| ident.to_string());
+                            Ok(quote! {
+                                #name::#ident => encoder.write_str(#value)?,
+                            })
+                        }
+                        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                            if rename.is_some() {
+                                return Err(syn::Error::new_spanned(
+                                    ident,
+                                    "rename is only supported on unit variants",
+                                ));
+                            }
+                            Ok(quote! {
+                                #name::#ident(inner) => prometheus_client::encoding::EncodeLabelValue::encode(inner, encoder)?,
+                            })
+                        }
+                        fields => Err(syn::Error::new_spanned(
+                            fields,
+                            "Can not derive EncodeLabelValue for variants with other than a single unnamed field.",
+                        )),
                     }
                 })
-                .collect();
+                .collect::<syn::Result<TokenStream2>>();
+            let match_arms = match match_arms {
+                Ok(match_arms) => match_arms,
+                Err(e) => return e.to_compile_error().into(),
+            };
 
             quote! {
                 match self {
@@ -132,6 +329,124 @@ pub fn derive_encode_label_value(input: TokenStream) -> TokenStream {
     gen.into()
 }
 
+/// Extract the value of a `#[prometheus(rename = "...")]` attribute, if any.
+fn rename(attrs: &[syn::Attribute]) -> syn::Result<Option<String>> {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("prometheus")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("rename") {
+                return Err(meta.error("only 'rename' is supported"));
+            }
+            let value: syn::LitStr = meta.value()?.parse()?;
+            if value.value().is_empty() {
+                return Err(syn::Error::new_spanned(value, "rename must not be empty"));
+            }
+            rename = Some(value.value());
+            Ok(())
+        })?;
+    }
+    Ok(rename)
+}
+
+/// Derive `prometheus_client::collector::Collector`.
+///
+/// Each field of the struct is expected to be a closure returning the current
+/// value of a metric, annotated with either `#[prometheus(counter)]` or
+/// `#[prometheus(gauge)]`. On each scrape the closure is called and its return
+/// value encoded as a `ConstCounter` or `ConstGauge` respectively. The field
+/// name is used as the metric name and the field's doc comment as its help
+/// text.
+///
+/// As required by `Collector`, the struct needs to implement `Debug`, `Send`
+/// and `Sync`.
+#[proc_macro_derive(Collector, attributes(prometheus))]
+pub fn derive_collector(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(input).unwrap();
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let body: TokenStream2 = match ast.clone().data {
+        syn::Data::Struct(s) => match s.fields {
+            syn::Fields::Named(syn::FieldsNamed { named, .. }) => named
+                .into_iter()
+                .map(|f| {
+                    let ident = f.ident.unwrap();
+                    let metric = f
+                        .attrs
+                        .iter()
+                        .find(|a| a.path().is_ident("prometheus"))
+                        .map(|a| a.parse_args::<syn::Ident>().unwrap().to_string());
+                    let metric = match metric.as_deref() {
+                        Some("counter") => quote! { prometheus_client::metrics::counter::ConstCounter },
+                        Some("gauge") => quote! { prometheus_client::metrics::gauge::ConstGauge },
+                        Some(other) => panic!(
+                            "Provided attribute '{other}', but only 'counter' and 'gauge' are supported"
+                        ),
+                        None => panic!(
+                            "Field '{ident}' requires either #[prometheus(counter)] or #[prometheus(gauge)]"
+                        ),
+                    };
+                    let help = f
+                        .attrs
+                        .iter()
+                        .filter(|a| a.path().is_ident("doc"))
+                        .filter_map(|a| match &a.meta {
+                            syn::Meta::NameValue(syn::MetaNameValue {
+                                value:
+                                    syn::Expr::Lit(syn::ExprLit {
+                                        lit: syn::Lit::Str(s),
+                                        ..
+                                    }),
+                                ..
+                            }) => Some(s.value().trim().to_string()),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let ident_string = KEYWORD_IDENTIFIERS
+                        .iter()
+                        .find(|pair| ident == pair.1)
+                        .map(|pair| pair.0.to_string())
+                        .unwrap_or_else(|| ident.to_string());
+
+                    quote! {
+                        {
+                            let metric = #metric::new((self.#ident)());
+                            let metric_encoder = encoder.encode_descriptor(
+                                #ident_string,
+                                #help,
+                                None,
+                                metric.metric_type(),
+                            )?;
+                            metric.encode(metric_encoder)?;
+                        }
+                    }
+                })
+                .collect(),
+            syn::Fields::Unnamed(_) => {
+                panic!("Can not derive Collector for struct with unnamed fields.")
+            }
+            syn::Fields::Unit => panic!("Can not derive Collector for struct with unit field."),
+        },
+        syn::Data::Enum(_) => panic!("Can not derive Collector for enum."),
+        syn::Data::Union(_) => panic!("Can not derive Collector for union."),
+    };
+
+    let gen = quote! {
+        impl #impl_generics prometheus_client::collector::Collector for #name #ty_generics #where_clause {
+            fn encode(&self, mut encoder: prometheus_client::encoding::DescriptorEncoder) -> std::result::Result<(), std::fmt::Error> {
+                use prometheus_client::encoding::EncodeMetric;
+
+                #body
+
+                Ok(())
+            }
+        }
+    };
+
+    gen.into()
+}
+
 // Copied from https://github.com/djc/askama (MIT and APACHE licensed) and
 // modified.
 static KEYWORD_IDENTIFIERS: [(&str, &str); 48] = [
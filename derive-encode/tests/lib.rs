@@ -205,3 +205,299 @@ fn flatten() {
         + "# EOF\n";
     assert_eq!(expected, buffer);
 }
+
+#[test]
+fn flatten_with_prefix() {
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Database {
+        id: u64,
+    }
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Labels {
+        #[prometheus(flatten, prefix = "primary_")]
+        primary: Database,
+        #[prometheus(flatten, prefix = "replica_")]
+        replica: Database,
+        id: u64,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            primary: Database { id: 1 },
+            replica: Database { id: 2 },
+            id: 3,
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{primary_id=\"1\",replica_id=\"2\",id=\"3\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn skip_none() {
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    #[prometheus(skip_none)]
+    struct Labels {
+        method: Option<String>,
+        status: std::option::Option<u16>,
+        #[prometheus(skip_encoding_if = "String::is_empty")]
+        path: String,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            method: None,
+            status: Some(200),
+            path: "/".to_string(),
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{status=\"200\",path=\"/\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn skip_encoding_if() {
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Context {
+        tenant: String,
+    }
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Labels {
+        #[prometheus(skip_encoding_if = "Option::is_none")]
+        method: Option<String>,
+        #[prometheus(flatten, skip_encoding_if = "Option::is_none")]
+        context: Option<Context>,
+        path: String,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            method: None,
+            context: None,
+            path: "/".to_string(),
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{path=\"/\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+
+    family.clear();
+    family
+        .get_or_create(&Labels {
+            method: Some("GET".to_string()),
+            context: Some(Context {
+                tenant: "a".to_string(),
+            }),
+            path: "/".to_string(),
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{method=\"GET\",tenant=\"a\",path=\"/\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn generic_label_set() {
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Labels<T, C> {
+        id: T,
+        #[prometheus(flatten)]
+        common: C,
+    }
+
+    #[derive(EncodeLabelSet, Hash, Clone, Eq, PartialEq, Debug)]
+    struct Common {
+        region: &'static str,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels<u64, Common>, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            id: 42,
+            common: Common { region: "eu" },
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{id=\"42\",region=\"eu\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn rename_label_key() {
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
+    struct Labels {
+        #[prometheus(rename = "http.method")]
+        method: String,
+        path: String,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            method: "GET".to_string(),
+            path: "/metrics".to_string(),
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{http.method=\"GET\",path=\"/metrics\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn rename_label_value() {
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelValue, Debug)]
+    enum Version {
+        #[prometheus(rename = "http/1.1")]
+        Http11,
+        #[allow(dead_code)]
+        Http2,
+    }
+
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
+    struct Labels {
+        version: Version,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            version: Version::Http11,
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{version=\"http/1.1\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
+
+#[test]
+fn newtype_variant_label_value() {
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelValue, Debug)]
+    enum ErrorKind {
+        Timeout,
+    }
+
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelValue, Debug)]
+    enum Outcome {
+        Ok,
+        Err(ErrorKind),
+    }
+
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
+    struct Labels {
+        outcome: Outcome,
+    }
+
+    let mut registry = Registry::default();
+    let family = Family::<Labels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&Labels {
+            outcome: Outcome::Ok,
+        })
+        .inc();
+    family
+        .get_or_create(&Labels {
+            outcome: Outcome::Err(ErrorKind::Timeout),
+        })
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    assert!(buffer.contains("my_counter_total{outcome=\"Ok\"} 1\n"));
+    assert!(buffer.contains("my_counter_total{outcome=\"Timeout\"} 1\n"));
+}
+
+#[test]
+fn tuple_struct() {
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
+    struct CommonLabels {
+        a: u64,
+        b: u64,
+    }
+
+    #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
+    struct RequestLabels(CommonLabels);
+
+    let mut registry = Registry::default();
+    let family = Family::<RequestLabels, Counter>::default();
+    registry.register("my_counter", "This is my counter", family.clone());
+
+    family
+        .get_or_create(&RequestLabels(CommonLabels { a: 42, b: 8 }))
+        .inc();
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP my_counter This is my counter.\n".to_owned()
+        + "# TYPE my_counter counter\n"
+        + "my_counter_total{a=\"42\",b=\"8\"} 1\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
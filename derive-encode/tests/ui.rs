@@ -0,0 +1,23 @@
+#[test]
+fn collector() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/collector-pass.rs");
+    t.compile_fail("tests/ui/collector-missing-attribute.rs");
+    t.compile_fail("tests/ui/collector-unknown-attribute.rs");
+}
+
+#[test]
+fn encode_label_value() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/label-value-empty-rename.rs");
+    t.compile_fail("tests/ui/label-value-multi-field-variant.rs");
+}
+
+#[test]
+fn encode_label_set() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/label-set-multi-field-tuple-struct.rs");
+    t.compile_fail("tests/ui/label-set-prefix-without-flatten.rs");
+    t.compile_fail("tests/ui/label-set-rename-flatten.rs");
+    t.compile_fail("tests/ui/label-set-unknown-struct-attribute.rs");
+}
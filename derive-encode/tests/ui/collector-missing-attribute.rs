@@ -0,0 +1,9 @@
+use prometheus_client::collector::Collector;
+
+#[derive(Collector, Debug)]
+struct MyCollector {
+    /// Number of open file descriptors.
+    open_fds: fn() -> i64,
+}
+
+fn main() {}
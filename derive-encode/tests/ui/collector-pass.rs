@@ -0,0 +1,48 @@
+use prometheus_client::collector::Collector;
+use prometheus_client::encoding::text::encode;
+use prometheus_client::registry::Registry;
+
+#[derive(Collector)]
+struct MyCollector {
+    /// Number of open file descriptors.
+    #[prometheus(gauge)]
+    open_fds: Box<dyn Fn() -> i64 + Send + Sync>,
+    /// Total number of context switches
+    /// since process start.
+    #[prometheus(counter)]
+    context_switches: fn() -> u64,
+    /// Raw identifier as metric name.
+    #[prometheus(gauge)]
+    r#type: fn() -> f64,
+}
+
+impl std::fmt::Debug for MyCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MyCollector").finish_non_exhaustive()
+    }
+}
+
+fn main() {
+    let fds = 7;
+    let mut registry = Registry::default();
+    registry.register_collector(Box::new(MyCollector {
+        open_fds: Box::new(move || fds),
+        context_switches: || 42,
+        r#type: || 1.5,
+    }));
+
+    let mut buffer = String::new();
+    encode(&mut buffer, &registry).unwrap();
+
+    let expected = "# HELP open_fds Number of open file descriptors.\n".to_owned()
+        + "# TYPE open_fds gauge\n"
+        + "open_fds 7\n"
+        + "# HELP context_switches Total number of context switches since process start.\n"
+        + "# TYPE context_switches counter\n"
+        + "context_switches_total 42\n"
+        + "# HELP type Raw identifier as metric name.\n"
+        + "# TYPE type gauge\n"
+        + "type 1.5\n"
+        + "# EOF\n";
+    assert_eq!(expected, buffer);
+}
@@ -0,0 +1,10 @@
+use prometheus_client::collector::Collector;
+
+#[derive(Collector, Debug)]
+struct MyCollector {
+    /// Latency of requests.
+    #[prometheus(histogram)]
+    latency: fn() -> f64,
+}
+
+fn main() {}
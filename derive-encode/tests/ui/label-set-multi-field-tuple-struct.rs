@@ -0,0 +1,6 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(EncodeLabelSet)]
+struct Labels(Vec<(String, String)>, Vec<(String, String)>);
+
+fn main() {}
@@ -0,0 +1,9 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(EncodeLabelSet)]
+struct Labels {
+    #[prometheus(prefix = "db_")]
+    id: u64,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(EncodeLabelSet)]
+struct Database {
+    id: u64,
+}
+
+#[derive(EncodeLabelSet)]
+struct Labels {
+    #[prometheus(flatten, rename = "db")]
+    database: Database,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use prometheus_client::encoding::EncodeLabelSet;
+
+#[derive(EncodeLabelSet)]
+#[prometheus(skip_empty)]
+struct Labels {
+    method: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use prometheus_client::encoding::EncodeLabelValue;
+
+#[derive(EncodeLabelValue)]
+enum Method {
+    #[prometheus(rename = "")]
+    Get,
+}
+
+fn main() {}
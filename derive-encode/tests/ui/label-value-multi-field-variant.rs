@@ -0,0 +1,9 @@
+use prometheus_client::encoding::EncodeLabelValue;
+
+#[derive(EncodeLabelValue)]
+enum Outcome {
+    Ok,
+    Err(String, u16),
+}
+
+fn main() {}
@@ -2,7 +2,7 @@ use std::sync::Mutex;
 
 use actix_web::middleware::Compress;
 use actix_web::{web, App, HttpResponse, HttpServer, Responder, Result};
-use prometheus_client::encoding::text::encode;
+use prometheus_client::encoding::text::{encode, CONTENT_TYPE};
 use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
@@ -37,9 +37,7 @@ pub async fn metrics_handler(state: web::Data<Mutex<AppState>>) -> Result<HttpRe
     let state = state.lock().unwrap();
     let mut body = String::new();
     encode(&mut body, &state.registry).unwrap();
-    Ok(HttpResponse::Ok()
-        .content_type("application/openmetrics-text; version=1.0.0; charset=utf-8")
-        .body(body))
+    Ok(HttpResponse::Ok().content_type(CONTENT_TYPE).body(body))
 }
 
 pub async fn some_handler(metrics: web::Data<Metrics>) -> impl Responder {
@@ -1,11 +1,8 @@
-use axum::body::Body;
 use axum::extract::State;
-use axum::http::header::CONTENT_TYPE;
-use axum::http::StatusCode;
-use axum::response::{IntoResponse, Response};
+use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::Router;
-use prometheus_client::encoding::text::encode;
+use prometheus_client::axum::metrics_handler;
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
 use prometheus_client::registry::Registry;
@@ -35,26 +32,6 @@ impl Metrics {
     }
 }
 
-#[derive(Debug)]
-pub struct AppState {
-    pub registry: Registry,
-}
-
-pub async fn metrics_handler(State(state): State<Arc<Mutex<AppState>>>) -> impl IntoResponse {
-    let state = state.lock().await;
-    let mut buffer = String::new();
-    encode(&mut buffer, &state.registry).unwrap();
-
-    Response::builder()
-        .status(StatusCode::OK)
-        .header(
-            CONTENT_TYPE,
-            "application/openmetrics-text; version=1.0.0; charset=utf-8",
-        )
-        .body(Body::from(buffer))
-        .unwrap()
-}
-
 pub async fn some_handler(State(metrics): State<Arc<Mutex<Metrics>>>) -> impl IntoResponse {
     metrics.lock().await.inc_requests(Method::Get);
     "okay".to_string()
@@ -65,18 +42,12 @@ async fn main() {
     let metrics = Metrics {
         requests: Family::default(),
     };
-    let mut state = AppState {
-        registry: Registry::default(),
-    };
-    state
-        .registry
-        .register("requests", "Count of requests", metrics.requests.clone());
+    let mut registry = Registry::default();
+    registry.register("requests", "Count of requests", metrics.requests.clone());
     let metrics = Arc::new(Mutex::new(metrics));
-    let state = Arc::new(Mutex::new(state));
 
     let router = Router::new()
-        .route("/metrics", get(metrics_handler))
-        .with_state(state)
+        .route("/metrics", metrics_handler(Arc::new(registry)))
         .route("/handler", get(some_handler))
         .with_state(metrics);
     let port = 8080;
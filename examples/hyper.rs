@@ -7,7 +7,11 @@ use hyper::{
     Request, Response,
 };
 use hyper_util::rt::TokioIo;
-use prometheus_client::{encoding::text::encode, metrics::counter::Counter, registry::Registry};
+use prometheus_client::{
+    encoding::text::{self, encode},
+    metrics::counter::Counter,
+    registry::Registry,
+};
 use std::{
     io,
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -76,14 +80,11 @@ pub fn make_handler(
         Box::pin(async move {
             let mut buf = String::new();
             encode(&mut buf, &reg.clone())
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+                .map_err(std::io::Error::other)
                 .map(|_| {
                     let body = full(Bytes::from(buf));
                     Response::builder()
-                        .header(
-                            hyper::header::CONTENT_TYPE,
-                            "application/openmetrics-text; version=1.0.0; charset=utf-8",
-                        )
+                        .header(hyper::header::CONTENT_TYPE, text::CONTENT_TYPE)
                         .body(body)
                         .unwrap()
                 })
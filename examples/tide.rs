@@ -1,5 +1,6 @@
+use prometheus_client::encoding::text::{encode, CONTENT_TYPE};
+use prometheus_client::encoding::EncodeLabelSet;
 use prometheus_client::encoding::EncodeLabelValue;
-use prometheus_client::encoding::{text::encode, EncodeLabelSet};
 use prometheus_client::metrics::counter::Counter;
 use prometheus_client::metrics::family::Family;
 use prometheus_client::registry::Registry;
@@ -35,7 +36,7 @@ async fn main() -> std::result::Result<(), std::io::Error> {
             encode(&mut encoded, &req.state().registry).unwrap();
             let response = tide::Response::builder(200)
                 .body(encoded)
-                .content_type("application/openmetrics-text; version=1.0.0; charset=utf-8")
+                .content_type(CONTENT_TYPE)
                 .build();
             Ok(response)
         });
@@ -0,0 +1,319 @@
+//! [axum](https://docs.rs/axum) integration serving the metrics of a
+//! [`Registry`].
+//!
+//! [`metrics_handler`] returns a ready-to-mount route exposing the metrics of a
+//! shared [`Registry`]. The exposition format is negotiated via the request's
+//! `Accept` header:
+//!
+//! - `application/openmetrics-text` selects the OpenMetrics
+//!   [`text`](crate::encoding::text) format.
+//! - `application/openmetrics-protobuf` selects the OpenMetrics
+//!   [`protobuf`](crate::encoding::protobuf) format, given the `protobuf`
+//!   feature is enabled.
+//! - `text/plain` selects the Prometheus
+//!   [text format 0.0.4](crate::encoding::prometheus_text).
+//!
+//! The acceptable format with the highest quality value wins. Without an
+//! `Accept` header or an acceptable format, the OpenMetrics text format is
+//! served.
+//!
+//! Encoding runs on a blocking thread of the tokio runtime, thus a large
+//! registry or a contended lock does not stall the executor. With the `async`
+//! feature enabled, the
+//! [`AsyncCollector`](crate::collector::AsyncCollector)s of the registry are
+//! awaited on that thread before encoding, and their metrics are served in
+//! all formats.
+//!
+//! ```no_run
+//! use axum::Router;
+//! use prometheus_client::axum::metrics_handler;
+//! use prometheus_client::metrics::counter::Counter;
+//! use prometheus_client::registry::Registry;
+//! use std::sync::{Arc, RwLock};
+//!
+//! # async fn run() {
+//! let mut registry = Registry::default();
+//! let requests: Counter = Counter::default();
+//! registry.register("requests", "Count of requests", requests.clone());
+//!
+//! // Keep write access to the registry, e.g. to register metrics at runtime.
+//! let registry = Arc::new(RwLock::new(registry));
+//!
+//! let router: Router = Router::new().route("/metrics", metrics_handler(registry.clone()));
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:8080").await.unwrap();
+//! axum::serve(listener, router).await.unwrap();
+//! # }
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use ::axum::http::header::{ACCEPT, CONTENT_TYPE};
+use ::axum::http::{HeaderMap, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use ::axum::routing::{get, MethodRouter};
+
+use crate::collector::Collector;
+use crate::encoding;
+use crate::registry::{Registry, SharedRegistry};
+
+/// Route serving the metrics of the given [`Registry`] on `GET` requests, see
+/// [module level documentation](self) for details.
+///
+/// The registry is read-locked for the duration of the encoding only,
+/// including awaiting its
+/// [`AsyncCollector`](crate::collector::AsyncCollector)s.
+pub fn metrics_handler<R, S>(registry: R) -> MethodRouter<S>
+where
+    R: ReadRegistry,
+    S: Clone + Send + Sync + 'static,
+{
+    get(move |headers: HeaderMap| respond(registry.clone(), headers))
+}
+
+/// Shared access to a [`Registry`] for [`metrics_handler`].
+///
+/// Implemented for [`SharedRegistry`], `Arc<Registry>`,
+/// `Arc<Mutex<Registry>>` and `Arc<RwLock<Registry>>`. A poisoned lock is
+/// ignored, as encoding only reads the registry.
+pub trait ReadRegistry: Clone + Send + Sync + 'static {
+    /// Call `f` with shared access to the [`Registry`].
+    fn with_registry<T>(&self, f: impl FnOnce(&Registry) -> T) -> T;
+}
+
+impl ReadRegistry for Arc<Registry> {
+    fn with_registry<T>(&self, f: impl FnOnce(&Registry) -> T) -> T {
+        f(self)
+    }
+}
+
+impl ReadRegistry for Arc<Mutex<Registry>> {
+    fn with_registry<T>(&self, f: impl FnOnce(&Registry) -> T) -> T {
+        f(&self.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl ReadRegistry for Arc<RwLock<Registry>> {
+    fn with_registry<T>(&self, f: impl FnOnce(&Registry) -> T) -> T {
+        f(&self.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl ReadRegistry for SharedRegistry {
+    fn with_registry<T>(&self, f: impl FnOnce(&Registry) -> T) -> T {
+        f(&self.read())
+    }
+}
+
+async fn respond(registry: impl ReadRegistry, headers: HeaderMap) -> Response {
+    let format = Format::negotiate(headers.get(ACCEPT).and_then(|v| v.to_str().ok()));
+    #[cfg(feature = "async")]
+    let runtime = tokio::runtime::Handle::current();
+
+    let encoded = tokio::task::spawn_blocking(move || {
+        registry.with_registry(|registry| {
+            // Awaited on the blocking thread, as the guard of a locked registry
+            // can not be held across an `.await` of the handler.
+            #[cfg(feature = "async")]
+            let collected = runtime.block_on(registry.collect_async());
+            #[cfg(not(feature = "async"))]
+            let collected = Vec::<Box<dyn Collector>>::new();
+
+            format.encode(registry, &mut collected.into_iter())
+        })
+    })
+    .await;
+
+    match encoded {
+        Ok(Ok(body)) => ([(CONTENT_TYPE, format.content_type())], body).into_response(),
+        Ok(Err(_)) | Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// Exposition formats served by [`metrics_handler`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    OpenMetricsText,
+    #[cfg(feature = "protobuf")]
+    OpenMetricsProtobuf,
+    PrometheusText,
+}
+
+impl Format {
+    /// Select the format with the highest quality value in the given `Accept`
+    /// header, falling back to OpenMetrics text.
+    fn negotiate(accept: Option<&str>) -> Self {
+        let mut selected: Option<(Format, f32)> = None;
+        for media_range in accept.unwrap_or_default().split(',') {
+            let mut params = media_range.split(';');
+            let media_type = params.next().unwrap_or_default().trim();
+            let quality = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+
+            let format = match media_type.to_ascii_lowercase().as_str() {
+                "application/openmetrics-text" | "application/*" | "*/*" => Format::OpenMetricsText,
+                #[cfg(feature = "protobuf")]
+                "application/openmetrics-protobuf" => Format::OpenMetricsProtobuf,
+                "text/plain" | "text/*" => Format::PrometheusText,
+                _ => continue,
+            };
+            // Media ranges with quality value `0` are not acceptable.
+            if quality > selected.map(|(_, q)| q).unwrap_or(0.0) {
+                selected = Some((format, quality));
+            }
+        }
+        selected.map_or(Format::OpenMetricsText, |(format, _)| format)
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::OpenMetricsText => encoding::text::CONTENT_TYPE,
+            #[cfg(feature = "protobuf")]
+            Format::OpenMetricsProtobuf => encoding::protobuf::CONTENT_TYPE,
+            Format::PrometheusText => encoding::prometheus_text::CONTENT_TYPE,
+        }
+    }
+
+    /// Encode the given [`Registry`], taking the [`Collector`] of each
+    /// [`AsyncCollector`](crate::collector::AsyncCollector) from `collected`.
+    fn encode(
+        self,
+        registry: &Registry,
+        collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+    ) -> Result<Vec<u8>, std::fmt::Error> {
+        match self {
+            Format::OpenMetricsText => {
+                let mut buffer = String::new();
+                encoding::text::encode_collected(&mut buffer, registry, collected)?;
+                Ok(buffer.into_bytes())
+            }
+            #[cfg(feature = "protobuf")]
+            Format::OpenMetricsProtobuf => Ok(prost::Message::encode_to_vec(
+                &encoding::protobuf::encode_collected(registry, collected)?,
+            )),
+            Format::PrometheusText => {
+                let mut buffer = String::new();
+                encoding::prometheus_text::encode_collected(&mut buffer, registry, collected)?;
+                Ok(buffer.into_bytes())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::counter::Counter;
+    use ::axum::http::HeaderValue;
+    use http_body_util::BodyExt;
+
+    #[test]
+    fn negotiate() {
+        assert_eq!(Format::OpenMetricsText, Format::negotiate(None));
+        assert_eq!(Format::OpenMetricsText, Format::negotiate(Some("")));
+        assert_eq!(
+            Format::OpenMetricsText,
+            Format::negotiate(Some("application/json"))
+        );
+        assert_eq!(
+            Format::PrometheusText,
+            Format::negotiate(Some("text/plain; version=0.0.4"))
+        );
+        // Default `Accept` header of Prometheus.
+        assert_eq!(
+            Format::OpenMetricsText,
+            Format::negotiate(Some(
+                "application/openmetrics-text;version=1.0.0;q=0.5,\
+                 application/openmetrics-text;version=0.0.1;q=0.4,\
+                 text/plain;version=0.0.4;q=0.3,*/*;q=0.2"
+            ))
+        );
+        assert_eq!(
+            Format::PrometheusText,
+            Format::negotiate(Some("application/openmetrics-text;q=0,text/plain;q=0.1"))
+        );
+        #[cfg(feature = "protobuf")]
+        assert_eq!(
+            Format::OpenMetricsProtobuf,
+            Format::negotiate(Some(
+                "application/openmetrics-protobuf;version=1.0.0,\
+                 application/openmetrics-text;version=1.0.0;q=0.5"
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn respond_negotiates_format() {
+        let mut registry = Registry::default();
+        let counter: Counter = Counter::default();
+        registry.register("requests", "Requests", counter.clone());
+        counter.inc();
+        let registry = Arc::new(RwLock::new(registry));
+
+        let response = respond(registry.clone(), HeaderMap::new()).await;
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(
+            encoding::text::CONTENT_TYPE,
+            response.headers()[CONTENT_TYPE]
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let mut expected = String::new();
+        encoding::text::encode(&mut expected, &registry.read().unwrap()).unwrap();
+        assert_eq!(expected.as_bytes(), body);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("text/plain"));
+        let response = respond(registry.clone(), headers).await;
+        assert_eq!(
+            encoding::prometheus_text::CONTENT_TYPE,
+            response.headers()[CONTENT_TYPE]
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.starts_with(b"# HELP requests_total Requests."));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn respond_awaits_async_collectors() {
+        use crate::collector::{from_fn, AsyncCollector};
+        use crate::encoding::EncodeMetric;
+        use crate::metrics::gauge::ConstGauge;
+        use std::future::Future;
+        use std::pin::Pin;
+
+        #[derive(Debug)]
+        struct Connections;
+
+        impl AsyncCollector for Connections {
+            fn collect(&self) -> Pin<Box<dyn Future<Output = Box<dyn Collector>> + Send + '_>> {
+                Box::pin(async {
+                    Box::new(from_fn(|mut encoder| {
+                        let gauge = ConstGauge::new(42);
+                        let metric_encoder = encoder.encode_descriptor(
+                            "connections",
+                            "Connections",
+                            None,
+                            gauge.metric_type(),
+                        )?;
+                        gauge.encode(metric_encoder)
+                    })) as Box<dyn Collector>
+                })
+            }
+        }
+
+        let mut registry = Registry::default();
+        registry.register_async_collector(Box::new(Connections));
+        let registry = SharedRegistry::new(registry);
+
+        for accept in ["application/openmetrics-text", "text/plain"] {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_static(accept));
+            let response = respond(registry.clone(), headers).await;
+            assert_eq!(StatusCode::OK, response.status());
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body.contains("connections 42\n"), "{body}");
+        }
+    }
+}
@@ -3,6 +3,41 @@
 //! See [`Collector`] for details.
 
 use crate::encoding::DescriptorEncoder;
+use crate::registry::Descriptor;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+
+/// Derive [`Collector`] for a struct of closures sampling metric values.
+///
+/// Each field is annotated with either `#[prometheus(counter)]` or
+/// `#[prometheus(gauge)]` and called on each scrape. Its return value is
+/// exposed as a [`ConstCounter`](crate::metrics::counter::ConstCounter) or
+/// [`ConstGauge`](crate::metrics::gauge::ConstGauge) named after the field, with
+/// the field's doc comment as help text.
+///
+/// ```
+/// # use prometheus_client::collector::Collector;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// #[derive(Collector, Debug)]
+/// struct MyCollector {
+///     /// Number of open file descriptors.
+///     #[prometheus(gauge)]
+///     open_fds: fn() -> i64,
+///     /// Total number of context switches.
+///     #[prometheus(counter)]
+///     context_switches: fn() -> u64,
+/// }
+///
+/// let mut registry = Registry::default();
+/// registry.register_collector(Box::new(MyCollector {
+///     open_fds: || 42,
+///     context_switches: || 1,
+/// }));
+/// ```
+pub use prometheus_client_derive_encode::Collector;
 
 /// The [`Collector`] abstraction allows users to provide additional metrics and
 /// their description on each scrape.
@@ -38,4 +73,158 @@ use crate::encoding::DescriptorEncoder;
 pub trait Collector: std::fmt::Debug + Send + Sync + 'static {
     /// Once the [`Collector`] is registered, this method is called on each scrape.
     fn encode(&self, encoder: DescriptorEncoder) -> Result<(), std::fmt::Error>;
+
+    /// Advertise the metrics this [`Collector`] encodes, without encoding
+    /// them.
+    ///
+    /// The [`Registry`](crate::registry::Registry) includes these in
+    /// [`Registry::metric_names`](crate::registry::Registry::metric_names) and
+    /// rejects metrics registered via
+    /// [`Registry::try_register`](crate::registry::Registry::try_register)
+    /// under the same name. Defaults to none.
+    ///
+    /// ```
+    /// # use prometheus_client::collector::Collector;
+    /// # use prometheus_client::encoding::DescriptorEncoder;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::{Descriptor, Registry};
+    /// #
+    /// #[derive(Debug)]
+    /// struct MyCollector {}
+    ///
+    /// impl Collector for MyCollector {
+    ///     fn encode(&self, mut encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+    ///         // ...
+    /// #       Ok(())
+    ///     }
+    ///
+    ///     fn descriptors(&self) -> Vec<Descriptor> {
+    ///         vec![Descriptor::new("open_fds", "Open file descriptors", None)]
+    ///     }
+    /// }
+    ///
+    /// let mut registry = Registry::default();
+    /// registry.register_collector(Box::new(MyCollector {}));
+    ///
+    /// assert_eq!(vec!["open_fds"], registry.metric_names().collect::<Vec<_>>());
+    /// assert!(registry
+    ///     .try_register("open_fds", "Duplicate", Counter::<u64>::default())
+    ///     .is_err());
+    /// ```
+    fn descriptors(&self) -> Vec<Descriptor> {
+        Vec::new()
+    }
+}
+
+/// Create a [`Collector`] from a closure called on each scrape.
+///
+/// ```
+/// # use prometheus_client::collector::from_fn;
+/// # use prometheus_client::encoding::text::encode;
+/// # use prometheus_client::encoding::EncodeMetric;
+/// # use prometheus_client::metrics::gauge::ConstGauge;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// let mut registry = Registry::default();
+/// registry.register_collector(Box::new(from_fn(|mut encoder| {
+///     let uptime = ConstGauge::new(42.0);
+///     let metric_encoder = encoder.encode_descriptor(
+///         "uptime_seconds",
+///         "Process uptime",
+///         None,
+///         uptime.metric_type(),
+///     )?;
+///     uptime.encode(metric_encoder)
+/// })));
+///
+/// let mut buffer = String::new();
+/// encode(&mut buffer, &registry).unwrap();
+///
+/// let expected = "# HELP uptime_seconds Process uptime\n".to_owned() +
+///                "# TYPE uptime_seconds gauge\n" +
+///                "uptime_seconds 42.0\n" +
+///                "# EOF\n";
+/// assert_eq!(expected, buffer);
+/// ```
+pub fn from_fn<F>(f: F) -> FromFn<F>
+where
+    F: Fn(DescriptorEncoder) -> Result<(), std::fmt::Error> + Send + Sync + 'static,
+{
+    FromFn(f)
+}
+
+/// A [`Collector`] backed by a closure, see [`from_fn`].
+pub struct FromFn<F>(F);
+
+impl<F> std::fmt::Debug for FromFn<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FromFn").finish()
+    }
+}
+
+impl<F> Collector for FromFn<F>
+where
+    F: Fn(DescriptorEncoder) -> Result<(), std::fmt::Error> + Send + Sync + 'static,
+{
+    fn encode(&self, encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+        (self.0)(encoder)
+    }
+}
+
+/// Asynchronous counterpart to [`Collector`] for collectors that need to await
+/// I/O, e.g. a database query, to produce their metrics.
+///
+/// On each scrape, [`AsyncCollector::collect`] gathers the current state and
+/// returns a [`Collector`] encoding it. All [`AsyncCollector`]s are awaited
+/// before encoding starts, thus the future returned by
+/// [`encode_async`](crate::encoding::text::encode_async) is [`Send`] as long as
+/// the writer is, e.g. to encode within `tokio::spawn` or an axum handler.
+///
+/// Register an [`AsyncCollector`] with a [`Registry`](crate::registry::Registry)
+/// via
+/// [`Registry::register_async_collector`](crate::registry::Registry::register_async_collector)
+/// and encode the registry with
+/// [`encode_async`](crate::encoding::text::encode_async).
+///
+/// ```
+/// # use prometheus_client::collector::{from_fn, AsyncCollector, Collector};
+/// # use prometheus_client::encoding::EncodeMetric;
+/// # use prometheus_client::metrics::gauge::ConstGauge;
+/// # use std::future::Future;
+/// # use std::pin::Pin;
+/// #
+/// #[derive(Debug)]
+/// struct MyCollector {}
+///
+/// impl MyCollector {
+///     async fn query_connections(&self) -> i64 {
+///         42
+///     }
+/// }
+///
+/// impl AsyncCollector for MyCollector {
+///     fn collect(&self) -> Pin<Box<dyn Future<Output = Box<dyn Collector>> + Send + '_>> {
+///         Box::pin(async move {
+///             let connections = self.query_connections().await;
+///             Box::new(from_fn(move |mut encoder| {
+///                 let gauge = ConstGauge::new(connections);
+///                 let metric_encoder = encoder.encode_descriptor(
+///                     "db_connections",
+///                     "Open database connections",
+///                     None,
+///                     gauge.metric_type(),
+///                 )?;
+///                 gauge.encode(metric_encoder)
+///             })) as Box<dyn Collector>
+///         })
+///     }
+/// }
+/// ```
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub trait AsyncCollector: std::fmt::Debug + Send + Sync + 'static {
+    /// Once the [`AsyncCollector`] is registered, this method is called on
+    /// each scrape via [`encode_async`](crate::encoding::text::encode_async),
+    /// returning a [`Collector`] encoding the gathered metrics.
+    fn collect(&self) -> Pin<Box<dyn Future<Output = Box<dyn Collector>> + Send + '_>>;
 }
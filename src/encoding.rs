@@ -6,16 +6,33 @@ use crate::metrics::exemplar::Exemplar;
 use crate::metrics::MetricType;
 use crate::registry::{Prefix, Unit};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Wrapping,
+};
 use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::atomic::{
+    AtomicI16, AtomicI32, AtomicI8, AtomicIsize, AtomicU16, AtomicU32, AtomicU8, AtomicUsize,
+    Ordering,
+};
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicI64, AtomicU64};
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
+#[cfg(feature = "opentelemetry")]
+#[cfg_attr(docsrs, doc(cfg(feature = "opentelemetry")))]
+pub mod opentelemetry;
+pub mod prometheus_text;
 #[cfg(feature = "protobuf")]
 #[cfg_attr(docsrs, doc(cfg(feature = "protobuf")))]
 pub mod protobuf;
 pub mod text;
+pub mod visitor;
 
 macro_rules! for_both_mut {
     ($self:expr, $inner:ident, $pattern:pat, $fn:expr) => {
@@ -23,6 +40,7 @@ macro_rules! for_both_mut {
             $inner::Text($pattern) => $fn,
             #[cfg(feature = "protobuf")]
             $inner::Protobuf($pattern) => $fn,
+            $inner::Visitor($pattern) => $fn,
         }
     };
 }
@@ -33,6 +51,7 @@ macro_rules! for_both {
             $inner::Text($pattern) => $fn,
             #[cfg(feature = "protobuf")]
             $inner::Protobuf($pattern) => $fn,
+            $inner::Visitor($pattern) => $fn,
         }
     };
 }
@@ -49,6 +68,24 @@ pub trait EncodeMetric {
     // One can not use [`TypedMetric`] directly, as associated constants are not
     // object safe and thus can not be used with dynamic dispatching.
     fn metric_type(&self) -> MetricType;
+
+    /// The number of series, i.e. distinct label sets, of the instance.
+    ///
+    /// Defaults to `1`. Overridden by
+    /// [`Family`](crate::metrics::family::Family).
+    fn series(&self) -> usize {
+        1
+    }
+
+    /// The instance as [`ReadGauge`](crate::metrics::gauge::ReadGauge), e.g.
+    /// to read the value of a [`Gauge`](crate::metrics::gauge::Gauge) behind a
+    /// `Box<dyn Metric>`.
+    ///
+    /// Defaults to `None`. Overridden by
+    /// [`Gauge`](crate::metrics::gauge::Gauge).
+    fn as_read_gauge(&self) -> Option<&dyn crate::metrics::gauge::ReadGauge> {
+        None
+    }
 }
 
 impl EncodeMetric for Box<dyn EncodeMetric> {
@@ -59,6 +96,14 @@ impl EncodeMetric for Box<dyn EncodeMetric> {
     fn metric_type(&self) -> MetricType {
         self.deref().metric_type()
     }
+
+    fn series(&self) -> usize {
+        self.deref().series()
+    }
+
+    fn as_read_gauge(&self) -> Option<&dyn crate::metrics::gauge::ReadGauge> {
+        self.deref().as_read_gauge()
+    }
 }
 
 /// Encoder for a Metric Descriptor.
@@ -71,6 +116,7 @@ enum DescriptorEncoderInner<'a> {
 
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::DescriptorEncoder<'a>),
+    Visitor(visitor::DescriptorEncoder<'a>),
 }
 
 impl<'a> From<text::DescriptorEncoder<'a>> for DescriptorEncoder<'a> {
@@ -86,7 +132,13 @@ impl<'a> From<protobuf::DescriptorEncoder<'a>> for DescriptorEncoder<'a> {
     }
 }
 
-impl DescriptorEncoder<'_> {
+impl<'a> From<visitor::DescriptorEncoder<'a>> for DescriptorEncoder<'a> {
+    fn from(e: visitor::DescriptorEncoder<'a>) -> Self {
+        Self(DescriptorEncoderInner::Visitor(e))
+    }
+}
+
+impl<'a> DescriptorEncoder<'a> {
     pub(crate) fn with_prefix_and_labels<'s>(
         &'s mut self,
         prefix: Option<&'s Prefix>,
@@ -101,6 +153,16 @@ impl DescriptorEncoder<'_> {
         )
     }
 
+    /// The predicate on the metric family name set via
+    /// [`Registry::encode_filtered`](crate::registry::Registry::encode_filtered),
+    /// if any.
+    pub(crate) fn filter(&self) -> Option<&'a dyn Fn(&str) -> bool> {
+        match &self.0 {
+            DescriptorEncoderInner::Text(e) => e.filter(),
+            _ => None,
+        }
+    }
+
     /// Encode a descriptor.
     pub fn encode_descriptor<'s>(
         &'s mut self,
@@ -128,6 +190,7 @@ enum MetricEncoderInner<'a> {
 
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::MetricEncoder<'a>),
+    Visitor(visitor::MetricEncoder<'a>),
 }
 
 impl<'a> From<text::MetricEncoder<'a>> for MetricEncoder<'a> {
@@ -143,6 +206,12 @@ impl<'a> From<protobuf::MetricEncoder<'a>> for MetricEncoder<'a> {
     }
 }
 
+impl<'a> From<visitor::MetricEncoder<'a>> for MetricEncoder<'a> {
+    fn from(e: visitor::MetricEncoder<'a>) -> Self {
+        Self(MetricEncoderInner::Visitor(e))
+    }
+}
+
 impl MetricEncoder<'_> {
     /// Encode a counter.
     pub fn encode_counter<
@@ -154,7 +223,26 @@ impl MetricEncoder<'_> {
         v: &CounterValue,
         exemplar: Option<&Exemplar<S, ExemplarValue>>,
     ) -> Result<(), std::fmt::Error> {
-        for_both_mut!(self, MetricEncoderInner, e, e.encode_counter(v, exemplar))
+        self.encode_counter_with_created(v, exemplar, None)
+    }
+
+    /// Encode a counter along with its creation time.
+    pub(crate) fn encode_counter_with_created<
+        S: EncodeLabelSet,
+        CounterValue: EncodeCounterValue,
+        ExemplarValue: EncodeExemplarValue,
+    >(
+        &mut self,
+        v: &CounterValue,
+        exemplar: Option<&Exemplar<S, ExemplarValue>>,
+        created: Option<SystemTime>,
+    ) -> Result<(), std::fmt::Error> {
+        for_both_mut!(
+            self,
+            MetricEncoderInner,
+            e,
+            e.encode_counter(v, exemplar, created)
+        )
     }
 
     /// Encode a gauge.
@@ -165,11 +253,47 @@ impl MetricEncoder<'_> {
         for_both_mut!(self, MetricEncoderInner, e, e.encode_gauge(v))
     }
 
+    /// Encode a gauge along with an exemplar.
+    pub(crate) fn encode_gauge_with_exemplar<
+        S: EncodeLabelSet,
+        GaugeValue: EncodeGaugeValue,
+        ExemplarValue: EncodeExemplarValue,
+    >(
+        &mut self,
+        v: &GaugeValue,
+        exemplar: Option<&Exemplar<S, ExemplarValue>>,
+    ) -> Result<(), std::fmt::Error> {
+        match &mut self.0 {
+            MetricEncoderInner::Text(e) => e.encode_gauge_with_exemplar(v, exemplar),
+            // The protobuf format has no notion of gauge exemplars.
+            #[cfg(feature = "protobuf")]
+            MetricEncoderInner::Protobuf(e) => e.encode_gauge(v),
+            MetricEncoderInner::Visitor(e) => e.encode_gauge(v),
+        }
+    }
+
+    /// Encode a metric of unknown type.
+    pub fn encode_unknown<UnknownValue: EncodeGaugeValue>(
+        &mut self,
+        v: &UnknownValue,
+    ) -> Result<(), std::fmt::Error> {
+        for_both_mut!(self, MetricEncoderInner, e, e.encode_unknown(v))
+    }
+
     /// Encode an info.
     pub fn encode_info(&mut self, label_set: &impl EncodeLabelSet) -> Result<(), std::fmt::Error> {
         for_both_mut!(self, MetricEncoderInner, e, e.encode_info(label_set))
     }
 
+    /// Encode a state set, given the name of each state and whether it is
+    /// enabled.
+    pub fn encode_stateset<'s>(
+        &mut self,
+        states: impl IntoIterator<Item = (&'s str, bool)>,
+    ) -> Result<(), std::fmt::Error> {
+        for_both_mut!(self, MetricEncoderInner, e, e.encode_stateset(states))
+    }
+
     /// Encode a histogram.
     pub fn encode_histogram<S: EncodeLabelSet>(
         &mut self,
@@ -177,15 +301,106 @@ impl MetricEncoder<'_> {
         count: u64,
         buckets: &[(f64, u64)],
         exemplars: Option<&HashMap<usize, Exemplar<S, f64>>>,
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_histogram_buckets(sum, count, buckets.iter().copied(), exemplars, None)
+    }
+
+    /// Encode a histogram given its buckets as an iterator of upper bound and
+    /// count pairs, along with its creation time.
+    pub(crate) fn encode_histogram_buckets<S: EncodeLabelSet, V: EncodeExemplarValue>(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+        exemplars: Option<&HashMap<usize, Exemplar<S, V>>>,
+        created: Option<SystemTime>,
     ) -> Result<(), std::fmt::Error> {
         for_both_mut!(
             self,
             MetricEncoderInner,
             e,
-            e.encode_histogram(sum, count, buckets, exemplars)
+            e.encode_histogram(sum, count, buckets, exemplars, created)
         )
     }
 
+    /// Encode a gauge histogram.
+    pub fn encode_gauge_histogram(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: &[(f64, u64)],
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_gauge_histogram_buckets(sum, count, buckets.iter().copied())
+    }
+
+    /// Encode a gauge histogram given its buckets as an iterator of upper
+    /// bound and count pairs.
+    pub(crate) fn encode_gauge_histogram_buckets(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+    ) -> Result<(), std::fmt::Error> {
+        for_both_mut!(
+            self,
+            MetricEncoderInner,
+            e,
+            e.encode_gauge_histogram(sum, count, buckets)
+        )
+    }
+
+    /// Borrow the encoder, e.g. to encode multiple metrics with different
+    /// label sets, like
+    /// [`ConstGauge::with_labels`](crate::metrics::gauge::ConstGauge::with_labels),
+    /// into the same metric family.
+    pub fn by_ref(&mut self) -> MetricEncoder<'_> {
+        for_both_mut!(self, MetricEncoderInner, e, e.by_ref().into())
+    }
+
+    /// Reborrow the [`MetricEncoder`], stamping each metric point encoded with
+    /// the returned encoder with the given timestamp, e.g. to re-expose values
+    /// collected at a known time from a
+    /// [`Collector`](crate::collector::Collector).
+    ///
+    /// Only the protobuf format exposes the timestamp. The text formats and
+    /// [`MetricVisitor`](visitor::MetricVisitor)s ignore it.
+    ///
+    /// ```
+    /// # use prometheus_client::collector::Collector;
+    /// # use prometheus_client::encoding::{DescriptorEncoder, EncodeMetric};
+    /// # use prometheus_client::metrics::gauge::ConstGauge;
+    /// # use prometheus_client::metrics::TypedMetric;
+    /// # use std::time::SystemTime;
+    /// #
+    /// #[derive(Debug)]
+    /// struct ProxyCollector {
+    ///     scraped_at: SystemTime,
+    ///     temperature: f64,
+    /// }
+    ///
+    /// impl Collector for ProxyCollector {
+    ///     fn encode(&self, mut encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+    ///         let mut metric_encoder = encoder.encode_descriptor(
+    ///             "temperature",
+    ///             "Temperature",
+    ///             None,
+    ///             ConstGauge::<f64>::TYPE,
+    ///         )?;
+    ///         ConstGauge::new(self.temperature)
+    ///             .encode(metric_encoder.with_timestamp(self.scraped_at))
+    ///     }
+    /// }
+    /// ```
+    #[cfg_attr(not(feature = "protobuf"), allow(unused_variables))]
+    pub fn with_timestamp(&mut self, timestamp: SystemTime) -> MetricEncoder<'_> {
+        match &mut self.0 {
+            MetricEncoderInner::Text(e) => e.by_ref().into(),
+            #[cfg(feature = "protobuf")]
+            MetricEncoderInner::Protobuf(e) => e.by_ref().with_timestamp(timestamp).into(),
+            MetricEncoderInner::Visitor(e) => e.by_ref().into(),
+        }
+    }
+
     /// Encode a metric family.
     pub fn encode_family<'s, S: EncodeLabelSet>(
         &'s mut self,
@@ -198,6 +413,30 @@ impl MetricEncoder<'_> {
             e.encode_family(label_set).map(Into::into)
         )
     }
+
+    /// Encode the given label sets and their metrics, e.g. the series of a
+    /// [`Family`](crate::metrics::family::Family), via
+    /// [`MetricEncoder::encode_family`].
+    ///
+    /// Series are ordered by their label set if requested by the encoder, see
+    /// [`text::encode_sorted`].
+    pub(crate) fn encode_series<'s, S, M>(
+        &mut self,
+        series: impl Iterator<Item = (&'s S, &'s M)>,
+    ) -> Result<(), std::fmt::Error>
+    where
+        S: EncodeLabelSet + 's,
+        M: EncodeMetric + 's,
+    {
+        if let MetricEncoderInner::Text(e) = &mut self.0 {
+            return e.encode_series(series);
+        }
+
+        for (label_set, metric) in series {
+            metric.encode(self.encode_family(label_set)?)?;
+        }
+        Ok(())
+    }
 }
 
 /// An encodable label set.
@@ -206,34 +445,103 @@ pub trait EncodeLabelSet {
     fn encode(&self, encoder: LabelSetEncoder) -> Result<(), std::fmt::Error>;
 }
 
+/// Collect the labels of the given label set as name and value pairs.
+pub(crate) fn collect_labels(
+    label_set: &impl EncodeLabelSet,
+) -> Result<Vec<(String, String)>, std::fmt::Error> {
+    let mut labels = vec![];
+    label_set.encode(visitor::LabelSetEncoder::new(&mut labels).into())?;
+    Ok(labels)
+}
+
 /// Encoder for a label set.
+///
+/// The second field is the prefix prepended to each label key, see
+/// [`LabelSetEncoder::with_key_prefix`].
 #[derive(Debug)]
-pub struct LabelSetEncoder<'a>(LabelSetEncoderInner<'a>);
+pub struct LabelSetEncoder<'a>(LabelSetEncoderInner<'a>, Option<Cow<'a, str>>);
 
 #[derive(Debug)]
 enum LabelSetEncoderInner<'a> {
     Text(text::LabelSetEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::LabelSetEncoder<'a>),
+    Visitor(visitor::LabelSetEncoder<'a>),
 }
 
 impl<'a> From<text::LabelSetEncoder<'a>> for LabelSetEncoder<'a> {
     fn from(e: text::LabelSetEncoder<'a>) -> Self {
-        Self(LabelSetEncoderInner::Text(e))
+        Self(LabelSetEncoderInner::Text(e), None)
     }
 }
 
 #[cfg(feature = "protobuf")]
 impl<'a> From<protobuf::LabelSetEncoder<'a>> for LabelSetEncoder<'a> {
     fn from(e: protobuf::LabelSetEncoder<'a>) -> Self {
-        Self(LabelSetEncoderInner::Protobuf(e))
+        Self(LabelSetEncoderInner::Protobuf(e), None)
+    }
+}
+
+impl<'a> From<visitor::LabelSetEncoder<'a>> for LabelSetEncoder<'a> {
+    fn from(e: visitor::LabelSetEncoder<'a>) -> Self {
+        Self(LabelSetEncoderInner::Visitor(e), None)
     }
 }
 
-impl LabelSetEncoder<'_> {
+impl<'a> LabelSetEncoder<'a> {
     /// Encode the given label.
-    pub fn encode_label(&mut self) -> LabelEncoder {
-        for_both_mut!(self, LabelSetEncoderInner, e, e.encode_label().into())
+    pub fn encode_label(&mut self) -> LabelEncoder<'_> {
+        let key_prefix = self.1.as_deref();
+        let mut encoder: LabelEncoder =
+            for_both_mut!(self, LabelSetEncoderInner, e, e.encode_label().into());
+        encoder.1 = key_prefix;
+        encoder
+    }
+
+    /// Reborrow the [`LabelSetEncoder`], e.g. to encode multiple nested label
+    /// sets into it.
+    pub fn by_ref(&mut self) -> LabelSetEncoder<'_> {
+        let key_prefix = self.1.as_deref().map(Cow::Borrowed);
+        let mut encoder: LabelSetEncoder =
+            for_both_mut!(self, LabelSetEncoderInner, e, e.by_ref().into());
+        encoder.1 = key_prefix;
+        encoder
+    }
+
+    /// Prepend `prefix` to the key of each label encoded with the returned
+    /// [`LabelSetEncoder`], appending to any existing prefix.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::{EncodeLabelSet, LabelSetEncoder};
+    /// struct Database {
+    ///     id: u64,
+    /// }
+    ///
+    /// impl EncodeLabelSet for Database {
+    ///     fn encode(&self, encoder: LabelSetEncoder) -> Result<(), std::fmt::Error> {
+    ///         [("id", self.id)].encode(encoder)
+    ///     }
+    /// }
+    ///
+    /// struct Labels {
+    ///     primary: Database,
+    ///     replica: Database,
+    /// }
+    ///
+    /// impl EncodeLabelSet for Labels {
+    ///     fn encode(&self, mut encoder: LabelSetEncoder) -> Result<(), std::fmt::Error> {
+    ///         // Encodes as `primary_id="1",replica_id="2"`.
+    ///         self.primary.encode(encoder.by_ref().with_key_prefix("primary_"))?;
+    ///         self.replica.encode(encoder.by_ref().with_key_prefix("replica_"))
+    ///     }
+    /// }
+    /// ```
+    pub fn with_key_prefix(mut self, prefix: &'a str) -> Self {
+        self.1 = Some(match self.1.take() {
+            Some(outer) => Cow::Owned(format!("{outer}{prefix}")),
+            None => Cow::Borrowed(prefix),
+        });
+        self
     }
 }
 
@@ -263,6 +571,42 @@ impl<T: EncodeLabel> EncodeLabelSet for Vec<T> {
     }
 }
 
+/// Encodes the labels in the order of the keys.
+impl<K: EncodeLabelKey, V: EncodeLabelValue> EncodeLabelSet for BTreeMap<K, V> {
+    fn encode(&self, mut encoder: LabelSetEncoder) -> Result<(), std::fmt::Error> {
+        for (key, value) in self.iter() {
+            encode_label(key, value, encoder.encode_label())?
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes the labels in the iteration order of the map, which is arbitrary
+/// and thus may change between encodings. Use a [`BTreeMap`] for a stable text
+/// output. Note that a [`HashMap`] does not implement [`Hash`](std::hash::Hash)
+/// and can thus not be used as the label set of a
+/// [`Family`](crate::metrics::family::Family).
+impl<K: EncodeLabelKey, V: EncodeLabelValue, S> EncodeLabelSet for HashMap<K, V, S> {
+    fn encode(&self, mut encoder: LabelSetEncoder) -> Result<(), std::fmt::Error> {
+        for (key, value) in self.iter() {
+            encode_label(key, value, encoder.encode_label())?
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes nothing for `None`.
+impl<T: EncodeLabelSet> EncodeLabelSet for Option<T> {
+    fn encode(&self, encoder: LabelSetEncoder) -> Result<(), std::fmt::Error> {
+        match self {
+            Some(label_set) => label_set.encode(encoder),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Uninhabited type to represent the lack of a label set for a metric
 #[derive(Debug)]
 pub enum NoLabelSet {}
@@ -280,54 +624,75 @@ pub trait EncodeLabel {
 }
 
 /// Encoder for a label.
+///
+/// The second field is the prefix prepended to the label key.
 #[derive(Debug)]
-pub struct LabelEncoder<'a>(LabelEncoderInner<'a>);
+pub struct LabelEncoder<'a>(LabelEncoderInner<'a>, Option<&'a str>);
 
 #[derive(Debug)]
 enum LabelEncoderInner<'a> {
     Text(text::LabelEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::LabelEncoder<'a>),
+    Visitor(visitor::LabelEncoder<'a>),
 }
 
 impl<'a> From<text::LabelEncoder<'a>> for LabelEncoder<'a> {
     fn from(e: text::LabelEncoder<'a>) -> Self {
-        Self(LabelEncoderInner::Text(e))
+        Self(LabelEncoderInner::Text(e), None)
     }
 }
 
 #[cfg(feature = "protobuf")]
 impl<'a> From<protobuf::LabelEncoder<'a>> for LabelEncoder<'a> {
     fn from(e: protobuf::LabelEncoder<'a>) -> Self {
-        Self(LabelEncoderInner::Protobuf(e))
+        Self(LabelEncoderInner::Protobuf(e), None)
+    }
+}
+
+impl<'a> From<visitor::LabelEncoder<'a>> for LabelEncoder<'a> {
+    fn from(e: visitor::LabelEncoder<'a>) -> Self {
+        Self(LabelEncoderInner::Visitor(e), None)
     }
 }
 
 impl LabelEncoder<'_> {
     /// Encode a label.
-    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder, std::fmt::Error> {
-        for_both_mut!(
+    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder<'_>, std::fmt::Error> {
+        let key_prefix = self.1;
+        let mut encoder: LabelKeyEncoder = for_both_mut!(
             self,
             LabelEncoderInner,
             e,
             e.encode_label_key().map(Into::into)
-        )
+        )?;
+        if let Some(key_prefix) = key_prefix {
+            encoder.write_str(key_prefix)?;
+        }
+        Ok(encoder)
     }
 }
 
 impl<K: EncodeLabelKey, V: EncodeLabelValue> EncodeLabel for (K, V) {
-    fn encode(&self, mut encoder: LabelEncoder) -> Result<(), std::fmt::Error> {
+    fn encode(&self, encoder: LabelEncoder) -> Result<(), std::fmt::Error> {
         let (key, value) = self;
+        encode_label(key, value, encoder)
+    }
+}
 
-        let mut label_key_encoder = encoder.encode_label_key()?;
-        key.encode(&mut label_key_encoder)?;
+fn encode_label<K: EncodeLabelKey, V: EncodeLabelValue>(
+    key: &K,
+    value: &V,
+    mut encoder: LabelEncoder,
+) -> Result<(), std::fmt::Error> {
+    let mut label_key_encoder = encoder.encode_label_key()?;
+    key.encode(&mut label_key_encoder)?;
 
-        let mut label_value_encoder = label_key_encoder.encode_label_value()?;
-        value.encode(&mut label_value_encoder)?;
-        label_value_encoder.finish()?;
+    let mut label_value_encoder = label_key_encoder.encode_label_value()?;
+    value.encode(&mut label_value_encoder)?;
+    label_value_encoder.finish()?;
 
-        Ok(())
-    }
+    Ok(())
 }
 
 /// An encodable label key.
@@ -345,6 +710,7 @@ enum LabelKeyEncoderInner<'a> {
     Text(text::LabelKeyEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::LabelKeyEncoder<'a>),
+    Visitor(visitor::LabelKeyEncoder<'a>),
 }
 
 impl<'a> From<text::LabelKeyEncoder<'a>> for LabelKeyEncoder<'a> {
@@ -360,6 +726,12 @@ impl<'a> From<protobuf::LabelKeyEncoder<'a>> for LabelKeyEncoder<'a> {
     }
 }
 
+impl<'a> From<visitor::LabelKeyEncoder<'a>> for LabelKeyEncoder<'a> {
+    fn from(e: visitor::LabelKeyEncoder<'a>) -> Self {
+        Self(LabelKeyEncoderInner::Visitor(e))
+    }
+}
+
 impl std::fmt::Write for LabelKeyEncoder<'_> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
         for_both_mut!(self, LabelKeyEncoderInner, e, e.write_str(s))
@@ -439,6 +811,7 @@ enum LabelValueEncoderInner<'a> {
     Text(text::LabelValueEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::LabelValueEncoder<'a>),
+    Visitor(visitor::LabelValueEncoder<'a>),
 }
 
 impl<'a> From<text::LabelValueEncoder<'a>> for LabelValueEncoder<'a> {
@@ -454,6 +827,12 @@ impl<'a> From<protobuf::LabelValueEncoder<'a>> for LabelValueEncoder<'a> {
     }
 }
 
+impl<'a> From<visitor::LabelValueEncoder<'a>> for LabelValueEncoder<'a> {
+    fn from(e: visitor::LabelValueEncoder<'a>) -> Self {
+        LabelValueEncoder(LabelValueEncoderInner::Visitor(e))
+    }
+}
+
 impl std::fmt::Write for LabelValueEncoder<'_> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
         for_both_mut!(self, LabelValueEncoderInner, e, e.write_str(s))
@@ -537,6 +916,45 @@ where
     }
 }
 
+impl EncodeLabelValue for char {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        encoder.write_char(*self)
+    }
+}
+
+/// Encodes the bytes as lowercase hex, e.g. `[0xde, 0xad]` as `dead`.
+#[cfg(feature = "hex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+impl EncodeLabelValue for &[u8] {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        for byte in self.iter() {
+            write!(encoder, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes the bytes as lowercase hex, e.g. `[0xde, 0xad]` as `dead`.
+#[cfg(feature = "hex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hex")))]
+impl<const N: usize> EncodeLabelValue for [u8; N] {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        EncodeLabelValue::encode(&self.as_slice(), encoder)
+    }
+}
+
+/// Encodes the UUID in its lowercase hyphenated form, without allocating.
+#[cfg(feature = "uuid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "uuid")))]
+impl EncodeLabelValue for uuid::Uuid {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        encoder.write_str(
+            self.hyphenated()
+                .encode_lower(&mut uuid::Uuid::encode_buffer()),
+        )
+    }
+}
+
 impl EncodeLabelValue for bool {
     fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
         encoder.write_str(if *self { "true" } else { "false" })
@@ -557,6 +975,173 @@ impl_encode_label_value_for_integer!(
     u128, i128, u64, i64, u32, i32, u16, i16, u8, i8, usize, isize
 );
 
+macro_rules! impl_encode_label_value_for_nonzero {
+    ($($t:ident),*) => {$(
+        impl EncodeLabelValue for $t {
+            fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+                EncodeLabelValue::encode(&self.get(), encoder)
+            }
+        }
+    )*};
+}
+
+impl_encode_label_value_for_nonzero!(
+    NonZeroU128,
+    NonZeroI128,
+    NonZeroU64,
+    NonZeroI64,
+    NonZeroU32,
+    NonZeroI32,
+    NonZeroU16,
+    NonZeroI16,
+    NonZeroU8,
+    NonZeroI8,
+    NonZeroUsize,
+    NonZeroIsize
+);
+
+macro_rules! impl_encode_label_value_for_display {
+    ($($t:ident),*) => {$(
+        impl EncodeLabelValue for $t {
+            fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+                write!(encoder, "{self}")
+            }
+        }
+    )*};
+}
+
+impl_encode_label_value_for_display!(
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    SocketAddr,
+    SocketAddrV4,
+    SocketAddrV6
+);
+
+/// Encodes the duration in seconds as a decimal, e.g. `1.5`.
+impl EncodeLabelValue for Duration {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        EncodeLabelValue::encode(&self.as_secs_f64(), encoder)
+    }
+}
+
+impl<T: EncodeLabelValue> EncodeLabelValue for Wrapping<T> {
+    fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+        self.0.encode(encoder)
+    }
+}
+
+macro_rules! radix_label_value {
+    ($(#[$doc:meta])* $name:ident, $trait:ident, $fmt:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name<T>(pub T);
+
+        impl<T: std::fmt::$trait> EncodeLabelValue for $name<T> {
+            fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+                write!(encoder, $fmt, self.0)
+            }
+        }
+    };
+}
+
+radix_label_value!(
+    /// Label value encoding the wrapped integer in lowercase hexadecimal,
+    /// without prefix, e.g. `Hex(0xdead_u32)` as `dead`.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::Hex;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let errors = Family::<[(&str, Hex<u32>); 1], Counter>::default();
+    /// errors.get_or_create(&[("flags", Hex(0xDEAD))]).inc();
+    /// ```
+    Hex,
+    LowerHex,
+    "{:x}"
+);
+
+radix_label_value!(
+    /// Label value encoding the wrapped integer in octal, without prefix, e.g.
+    /// `Octal(0o644_u32)` as `644`.
+    Octal,
+    Octal,
+    "{:o}"
+);
+
+radix_label_value!(
+    /// Label value encoding the wrapped integer in binary, without prefix, e.g.
+    /// `Binary(0b101_u8)` as `101`.
+    Binary,
+    Binary,
+    "{:b}"
+);
+
+macro_rules! impl_encode_label_value_for_atomic {
+    ($($(#[$attr:meta])* $t:ident),*) => {$(
+        $(#[$attr])*
+        /// Encodes a snapshot of the atomic's value at the time of encoding.
+        ///
+        /// The value is read with [`Ordering::Relaxed`], i.e. it is not
+        /// synchronized with any other memory access. Concurrent modifications
+        /// may or may not be reflected, and label values read from different
+        /// atomics do not necessarily stem from the same point in time. Thus
+        /// this is best suited for informational labels, e.g. for debugging,
+        /// not for identifying a series.
+        impl EncodeLabelValue for &$t {
+            fn encode(&self, encoder: &mut LabelValueEncoder) -> Result<(), std::fmt::Error> {
+                EncodeLabelValue::encode(&self.load(Ordering::Relaxed), encoder)
+            }
+        }
+    )*};
+}
+
+impl_encode_label_value_for_atomic!(
+    #[cfg(target_has_atomic = "64")]
+    AtomicU64,
+    #[cfg(target_has_atomic = "64")]
+    AtomicI64,
+    AtomicU32,
+    AtomicI32,
+    AtomicU16,
+    AtomicI16,
+    AtomicU8,
+    AtomicI8,
+    AtomicUsize,
+    AtomicIsize
+);
+
+/// A set of mutually exclusive states, encodable as an OpenMetrics state set.
+///
+/// Usually implemented on a fieldless enum. See
+/// [`StateSet`](crate::metrics::stateset::StateSet).
+///
+/// ```
+/// # use prometheus_client::encoding::EncodeStateSet;
+/// #[derive(Clone, Copy)]
+/// enum Mode {
+///     Primary,
+///     Standby,
+/// }
+///
+/// impl EncodeStateSet for Mode {
+///     const STATES: &'static [&'static str] = &["primary", "standby"];
+///
+///     fn index(&self) -> usize {
+///         *self as usize
+///     }
+/// }
+/// ```
+pub trait EncodeStateSet {
+    /// The names of all possible states, in the order they are encoded.
+    const STATES: &'static [&'static str];
+
+    /// The position of `self` within [`EncodeStateSet::STATES`].
+    fn index(&self) -> usize;
+}
+
 /// An encodable gauge value.
 pub trait EncodeGaugeValue {
     /// Encode the given instance in the OpenMetrics text encoding.
@@ -575,16 +1160,15 @@ impl EncodeGaugeValue for i64 {
     }
 }
 
+/// Values up to [`i64::MAX`] are encoded as integers. Larger values are
+/// encoded as `f64`, given that the protobuf format only supports signed
+/// integer gauges, thus losing precision in the lower bits.
 impl EncodeGaugeValue for u64 {
     fn encode(&self, encoder: &mut GaugeValueEncoder) -> Result<(), std::fmt::Error> {
-        // Between forcing end users to do endless as i64 for things that are
-        // clearly u64 and having one error case for rarely used protobuf when
-        // a gauge is set to u64::MAX, the latter seems like the right choice.
-        if *self == u64::MAX {
-            return Err(std::fmt::Error);
+        match i64::try_from(*self) {
+            Ok(v) => encoder.encode_i64(v),
+            Err(_) => encoder.encode_f64(*self as f64),
         }
-
-        encoder.encode_i64(*self as i64)
     }
 }
 
@@ -615,6 +1199,7 @@ enum GaugeValueEncoderInner<'a> {
     Text(text::GaugeValueEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::GaugeValueEncoder<'a>),
+    Visitor(visitor::GaugeValueEncoder<'a>),
 }
 
 impl GaugeValueEncoder<'_> {
@@ -644,6 +1229,12 @@ impl<'a> From<protobuf::GaugeValueEncoder<'a>> for GaugeValueEncoder<'a> {
     }
 }
 
+impl<'a> From<visitor::GaugeValueEncoder<'a>> for GaugeValueEncoder<'a> {
+    fn from(e: visitor::GaugeValueEncoder<'a>) -> Self {
+        GaugeValueEncoder(GaugeValueEncoderInner::Visitor(e))
+    }
+}
+
 /// An encodable counter value.
 pub trait EncodeCounterValue {
     /// Encode the given instance in the OpenMetrics text encoding.
@@ -683,6 +1274,7 @@ enum CounterValueEncoderInner<'a> {
     Text(text::CounterValueEncoder<'a>),
     #[cfg(feature = "protobuf")]
     Protobuf(protobuf::CounterValueEncoder<'a>),
+    Visitor(visitor::CounterValueEncoder<'a>),
 }
 
 impl<'a> From<text::CounterValueEncoder<'a>> for CounterValueEncoder<'a> {
@@ -698,6 +1290,12 @@ impl<'a> From<protobuf::CounterValueEncoder<'a>> for CounterValueEncoder<'a> {
     }
 }
 
+impl<'a> From<visitor::CounterValueEncoder<'a>> for CounterValueEncoder<'a> {
+    fn from(e: visitor::CounterValueEncoder<'a>) -> Self {
+        CounterValueEncoder(CounterValueEncoderInner::Visitor(e))
+    }
+}
+
 impl CounterValueEncoder<'_> {
     fn encode_f64(&mut self, v: f64) -> Result<(), std::fmt::Error> {
         for_both_mut!(self, CounterValueEncoderInner, e, e.encode_f64(v))
@@ -738,6 +1336,18 @@ impl EncodeExemplarValue for u32 {
     }
 }
 
+impl EncodeExemplarValue for i64 {
+    fn encode(&self, mut encoder: ExemplarValueEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode(*self as f64)
+    }
+}
+
+impl EncodeExemplarValue for i32 {
+    fn encode(&self, mut encoder: ExemplarValueEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode(*self as f64)
+    }
+}
+
 /// Encoder for an exemplar value.
 #[derive(Debug)]
 pub struct ExemplarValueEncoder<'a>(ExemplarValueEncoderInner<'a>);
@@ -764,6 +1374,12 @@ impl<'a> From<protobuf::ExemplarValueEncoder<'a>> for ExemplarValueEncoder<'a> {
 
 impl ExemplarValueEncoder<'_> {
     fn encode(&mut self, v: f64) -> Result<(), std::fmt::Error> {
-        for_both_mut!(self, ExemplarValueEncoderInner, e, e.encode(v))
+        // Not using `for_both_mut!`, as exemplars are never visited, see
+        // `visitor`.
+        match &mut self.0 {
+            ExemplarValueEncoderInner::Text(e) => e.encode(v),
+            #[cfg(feature = "protobuf")]
+            ExemplarValueEncoderInner::Protobuf(e) => e.encode(v),
+        }
     }
 }
@@ -0,0 +1,397 @@
+//! Conversion of a [`Registry`] into OpenTelemetry protocol (OTLP) metrics,
+//! e.g. to ship metrics instrumented with this crate through an OTLP pipeline
+//! instead of scraping them.
+//!
+//! Metrics are mapped to their closest OTLP equivalent:
+//!
+//! - Counters to monotonic, cumulative sums.
+//! - Gauges, as well as metrics of unknown type, to gauges.
+//! - Histograms to cumulative, explicit bucket histograms.
+//! - Info metrics to gauges with value `1`, the info labels being added to the
+//!   attributes.
+//! - State sets to gauges with a data point per state, the state being added to
+//!   the attributes under the name of the metric, with value `1` for the
+//!   enabled and `0` for all other states.
+//!
+//! Gauge histograms have no OTLP equivalent and are omitted, as are exemplars.
+//! Metric names, including prefix and unit suffix, are kept as is. The start
+//! time of counters and histograms is their creation time, see e.g.
+//! [`Counter::created`](crate::metrics::counter::Counter::created).
+//!
+//! The OTLP messages are those of the [`opentelemetry_proto`] crate, re-exported
+//! for convenience, e.g. to be sent to an OTLP/HTTP endpoint.
+//!
+//! ```
+//! # use prometheus_client::encoding::opentelemetry::encode;
+//! # use prometheus_client::metrics::counter::Counter;
+//! # use prometheus_client::registry::Registry;
+//! #
+//! let mut registry = Registry::default();
+//! let counter: Counter = Counter::default();
+//! registry.register("my_counter", "This is my counter", counter.clone());
+//! counter.inc();
+//!
+//! let resource_metrics = encode(&registry).unwrap();
+//!
+//! let metric = &resource_metrics.scope_metrics[0].metrics[0];
+//! assert_eq!("my_counter", metric.name);
+//! assert_eq!("This is my counter.", metric.description);
+//! ```
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub use opentelemetry_proto;
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
+use opentelemetry_proto::tonic::metrics::v1::{
+    metric, number_data_point, AggregationTemporality, Gauge, Histogram, HistogramDataPoint,
+    Metric, NumberDataPoint, ResourceMetrics, ScopeMetrics, Sum,
+};
+
+use crate::metrics::MetricType;
+use crate::registry::{Registry, Unit};
+
+use super::visitor::{CounterValue, GaugeValue, MetricVisitor};
+
+/// Convert the metrics registered with the provided [`Registry`] into OTLP
+/// [`ResourceMetrics`], see [module level documentation](self) for details.
+///
+/// The metrics are contained in a single [`ScopeMetrics`] with the
+/// instrumentation scope of this crate. The resource is left unset, to be
+/// filled in by the caller.
+pub fn encode(registry: &Registry) -> Result<ResourceMetrics, std::fmt::Error> {
+    let mut visitor = OtlpVisitor {
+        time_unix_nano: unix_nano(SystemTime::now()),
+        metrics: vec![],
+    };
+    registry.visit(&mut visitor)?;
+
+    Ok(ResourceMetrics {
+        resource: None,
+        scope_metrics: vec![ScopeMetrics {
+            scope: Some(InstrumentationScope {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                ..Default::default()
+            }),
+            metrics: visitor
+                .metrics
+                .into_iter()
+                .filter(|metric| metric.data.is_some())
+                .collect(),
+            schema_url: String::new(),
+        }],
+        schema_url: String::new(),
+    })
+}
+
+/// [`MetricVisitor`] collecting OTLP metrics.
+#[derive(Debug)]
+struct OtlpVisitor {
+    /// Time of the conversion, used as time of each data point.
+    time_unix_nano: u64,
+    metrics: Vec<Metric>,
+}
+
+impl OtlpVisitor {
+    fn number_data_point(
+        &self,
+        labels: &[(String, String)],
+        value: number_data_point::Value,
+    ) -> NumberDataPoint {
+        NumberDataPoint {
+            attributes: attributes(labels),
+            time_unix_nano: self.time_unix_nano,
+            value: Some(value),
+            ..Default::default()
+        }
+    }
+
+    /// Add the given gauge data point to the current metric, if it is a gauge.
+    fn push_gauge(&mut self, data_point: NumberDataPoint) {
+        if let Some(metric::Data::Gauge(gauge)) = self.data() {
+            gauge.data_points.push(data_point);
+        }
+    }
+
+    /// The data of the metric of the last visited descriptor.
+    fn data(&mut self) -> Option<&mut metric::Data> {
+        self.metrics.last_mut()?.data.as_mut()
+    }
+}
+
+impl MetricVisitor for OtlpVisitor {
+    fn visit_descriptor(
+        &mut self,
+        name: &str,
+        help: &str,
+        unit: Option<&Unit>,
+        metric_type: MetricType,
+    ) {
+        let data = match metric_type {
+            MetricType::Counter => Some(metric::Data::Sum(Sum {
+                data_points: vec![],
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                is_monotonic: true,
+            })),
+            MetricType::Gauge | MetricType::Info | MetricType::StateSet | MetricType::Unknown => {
+                Some(metric::Data::Gauge(Gauge::default()))
+            }
+            MetricType::Histogram => Some(metric::Data::Histogram(Histogram {
+                data_points: vec![],
+                aggregation_temporality: AggregationTemporality::Cumulative as i32,
+            })),
+            MetricType::GaugeHistogram | MetricType::Summary => None,
+        };
+
+        self.metrics.push(Metric {
+            name: name.to_string(),
+            description: help.to_string(),
+            unit: unit.map(ucum).unwrap_or_default().to_string(),
+            data,
+            ..Default::default()
+        });
+    }
+
+    fn visit_counter(&mut self, _name: &str, labels: &[(String, String)], value: CounterValue) {
+        let value = match value {
+            CounterValue::Int(v) => match i64::try_from(v) {
+                Ok(v) => number_data_point::Value::AsInt(v),
+                Err(_) => number_data_point::Value::AsDouble(v as f64),
+            },
+            CounterValue::Double(v) => number_data_point::Value::AsDouble(v),
+        };
+        let data_point = self.number_data_point(labels, value);
+        if let Some(metric::Data::Sum(sum)) = self.data() {
+            sum.data_points.push(data_point);
+        }
+    }
+
+    fn visit_gauge(&mut self, _name: &str, labels: &[(String, String)], value: GaugeValue) {
+        let value = match value {
+            GaugeValue::Int(v) => number_data_point::Value::AsInt(v),
+            GaugeValue::Double(v) => number_data_point::Value::AsDouble(v),
+        };
+        let data_point = self.number_data_point(labels, value);
+        self.push_gauge(data_point);
+    }
+
+    fn visit_histogram(
+        &mut self,
+        _name: &str,
+        labels: &[(String, String)],
+        sum: f64,
+        count: u64,
+        buckets: &[(f64, u64)],
+    ) {
+        // OTLP expects per-bucket counts and omits the upper bound of the
+        // final `+Inf` bucket.
+        let mut previous = 0;
+        let bucket_counts = buckets
+            .iter()
+            .map(|(_, cumulative)| {
+                let count = cumulative.saturating_sub(previous);
+                previous = *cumulative;
+                count
+            })
+            .collect();
+        let explicit_bounds = buckets
+            .iter()
+            .map(|(upper_bound, _)| *upper_bound)
+            .filter(|upper_bound| *upper_bound != f64::MAX)
+            .collect();
+
+        let data_point = HistogramDataPoint {
+            attributes: attributes(labels),
+            time_unix_nano: self.time_unix_nano,
+            count,
+            sum: Some(sum),
+            bucket_counts,
+            explicit_bounds,
+            ..Default::default()
+        };
+        if let Some(metric::Data::Histogram(histogram)) = self.data() {
+            histogram.data_points.push(data_point);
+        }
+    }
+
+    fn visit_info(&mut self, _name: &str, labels: &[(String, String)], info: &[(String, String)]) {
+        let labels = labels.iter().chain(info).cloned().collect::<Vec<_>>();
+        let data_point = self.number_data_point(&labels, number_data_point::Value::AsInt(1));
+        self.push_gauge(data_point);
+    }
+
+    fn visit_stateset(&mut self, name: &str, labels: &[(String, String)], states: &[(&str, bool)]) {
+        for (state, enabled) in states {
+            let mut labels = labels.to_vec();
+            labels.push((name.to_string(), state.to_string()));
+            let data_point =
+                self.number_data_point(&labels, number_data_point::Value::AsInt(*enabled as i64));
+            self.push_gauge(data_point);
+        }
+    }
+
+    fn visit_created(&mut self, _name: &str, _labels: &[(String, String)], created: SystemTime) {
+        let start_time_unix_nano = unix_nano(created);
+        match self.data() {
+            Some(metric::Data::Sum(sum)) => {
+                if let Some(data_point) = sum.data_points.last_mut() {
+                    data_point.start_time_unix_nano = start_time_unix_nano;
+                }
+            }
+            Some(metric::Data::Histogram(histogram)) => {
+                if let Some(data_point) = histogram.data_points.last_mut() {
+                    data_point.start_time_unix_nano = start_time_unix_nano;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Nanoseconds since the UNIX epoch, `0` for times before it.
+fn unix_nano(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn attributes(labels: &[(String, String)]) -> Vec<KeyValue> {
+    labels
+        .iter()
+        .map(|(key, value)| KeyValue {
+            key: key.clone(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.clone())),
+            }),
+        })
+        .collect()
+}
+
+/// The UCUM unit code used by OpenTelemetry for the given unit.
+fn ucum(unit: &Unit) -> &str {
+    match unit {
+        Unit::Amperes => "A",
+        Unit::Bytes => "By",
+        Unit::Celsius => "Cel",
+        Unit::Grams => "g",
+        Unit::Joules => "J",
+        Unit::Meters => "m",
+        Unit::Ratios => "1",
+        Unit::Seconds => "s",
+        Unit::Volts => "V",
+        Unit::Other(other) => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::counter::Counter;
+    use crate::metrics::family::Family;
+    use crate::metrics::gauge::Gauge;
+    use crate::metrics::histogram::Histogram;
+    use crate::metrics::info::Info;
+
+    fn metrics(registry: &Registry) -> Vec<Metric> {
+        let resource_metrics = encode(registry).unwrap();
+        assert_eq!(1, resource_metrics.scope_metrics.len());
+        resource_metrics.scope_metrics[0].metrics.clone()
+    }
+
+    fn attribute(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue {
+                value: Some(any_value::Value::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    #[test]
+    fn encode_counter_family() {
+        let mut registry = Registry::default();
+        let requests = Family::<Vec<(String, String)>, Counter>::default();
+        registry.register("requests", "Requests", requests.clone());
+        requests
+            .get_or_create(&vec![("method".to_string(), "GET".to_string())])
+            .inc_by(3);
+
+        let metrics = metrics(&registry);
+        assert_eq!(1, metrics.len());
+        match metrics[0].data.as_ref().unwrap() {
+            metric::Data::Sum(sum) => {
+                assert!(sum.is_monotonic);
+                assert_eq!(
+                    AggregationTemporality::Cumulative as i32,
+                    sum.aggregation_temporality
+                );
+                assert_eq!(1, sum.data_points.len());
+                let data_point = &sum.data_points[0];
+                assert_eq!(vec![attribute("method", "GET")], data_point.attributes);
+                assert_eq!(Some(number_data_point::Value::AsInt(3)), data_point.value);
+                assert!(data_point.time_unix_nano > 0);
+                assert!(data_point.start_time_unix_nano > 0);
+                assert!(data_point.start_time_unix_nano <= data_point.time_unix_nano);
+            }
+            data => panic!("unexpected data {data:?}"),
+        }
+    }
+
+    #[test]
+    fn encode_gauge_and_histogram() {
+        let mut registry = Registry::default();
+        let gauge: Gauge = Gauge::default();
+        registry.register("temperature", "Temperature", gauge.clone());
+        gauge.set(-3);
+        let histogram = Histogram::new([1.0, 10.0]);
+        registry.register_with_unit("latency", "Latency", Unit::Seconds, histogram.clone());
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+        let histogram_created = histogram.created().unwrap();
+
+        let metrics = metrics(&registry);
+        assert_eq!(2, metrics.len());
+
+        match metrics[0].data.as_ref().unwrap() {
+            metric::Data::Gauge(gauge) => assert_eq!(
+                Some(number_data_point::Value::AsInt(-3)),
+                gauge.data_points[0].value
+            ),
+            data => panic!("unexpected data {data:?}"),
+        }
+
+        assert_eq!("latency_seconds", metrics[1].name);
+        assert_eq!("s", metrics[1].unit);
+        match metrics[1].data.as_ref().unwrap() {
+            metric::Data::Histogram(histogram) => {
+                let data_point = &histogram.data_points[0];
+                assert_eq!(3, data_point.count);
+                assert_eq!(Some(55.5), data_point.sum);
+                assert_eq!(
+                    unix_nano(histogram_created),
+                    data_point.start_time_unix_nano
+                );
+                assert_eq!(vec![1.0, 10.0], data_point.explicit_bounds);
+                assert_eq!(vec![1, 1, 1], data_point.bucket_counts);
+            }
+            data => panic!("unexpected data {data:?}"),
+        }
+    }
+
+    #[test]
+    fn encode_info() {
+        let mut registry = Registry::default();
+        registry.register("build", "Build", Info::new(vec![("version", "1.0")]));
+
+        let metrics = metrics(&registry);
+        match metrics[0].data.as_ref().unwrap() {
+            metric::Data::Gauge(gauge) => {
+                let data_point = &gauge.data_points[0];
+                assert_eq!(vec![attribute("version", "1.0")], data_point.attributes);
+                assert_eq!(Some(number_data_point::Value::AsInt(1)), data_point.value);
+            }
+            data => panic!("unexpected data {data:?}"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Prometheus text format 0.0.4 implementation.
+//!
+//! The classic `text/plain; version=0.0.4` exposition format predates
+//! OpenMetrics and is still the only format understood by some scrapers. Prefer
+//! the OpenMetrics [`text`](super::text) format where possible.
+//!
+//! Compared to OpenMetrics, this format has no `# UNIT` lines, no `# EOF`
+//! marker, no exemplars and no `_created` samples. The `# HELP` and `# TYPE`
+//! lines of counters and info metrics refer to the `_total` and `_info` samples
+//! respectively. Metric types without a counterpart in this format are exposed
+//! as their closest equivalent, e.g. a state set as a gauge and a gauge
+//! histogram as a histogram.
+//!
+//! ```
+//! # use prometheus_client::encoding::prometheus_text::encode;
+//! # use prometheus_client::metrics::counter::Counter;
+//! # use prometheus_client::registry::Registry;
+//! #
+//! let mut registry = Registry::default();
+//! let counter: Counter = Counter::default();
+//! registry.register("my_counter", "This is my counter", counter.clone());
+//! counter.inc();
+//!
+//! let mut buffer = String::new();
+//! encode(&mut buffer, &registry).unwrap();
+//!
+//! let expected = "# HELP my_counter_total This is my counter.\n".to_owned() +
+//!                "# TYPE my_counter_total counter\n" +
+//!                "my_counter_total 1\n";
+//! assert_eq!(expected, buffer);
+//! ```
+
+use crate::collector::Collector;
+use crate::registry::Registry;
+
+use std::fmt::Write;
+
+/// The HTTP `Content-Type` of the Prometheus text format 0.0.4.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Encode the metrics registered with the provided [`Registry`] into the
+/// provided [`Write`]r using the Prometheus text format 0.0.4.
+pub fn encode<W>(writer: &mut W, registry: &Registry) -> Result<(), std::fmt::Error>
+where
+    W: Write,
+{
+    encode_collected(writer, registry, &mut std::iter::empty())
+}
+
+/// Like [`encode`], taking the [`Collector`] of each
+/// [`AsyncCollector`](crate::collector::AsyncCollector) from `collected`, see
+/// [`Registry::encode_collected`].
+pub(crate) fn encode_collected<W>(
+    writer: &mut W,
+    registry: &Registry,
+    collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+) -> Result<(), std::fmt::Error>
+where
+    W: Write,
+{
+    registry.encode_collected(
+        &mut super::text::DescriptorEncoder::new(writer)
+            .with_emit_created(registry.emit_created())
+            .with_exemplar_overflow(registry.exemplar_overflow())
+            .with_legacy_format()
+            .into(),
+        collected,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::counter::Counter;
+    use crate::metrics::exemplar::CounterWithExemplar;
+    use crate::metrics::gauge::Gauge;
+    use crate::metrics::gauge_histogram::GaugeHistogram;
+    use crate::metrics::histogram::Histogram;
+    use crate::metrics::info::Info;
+    use crate::registry::Unit;
+
+    #[test]
+    fn encode_legacy_format() {
+        let mut registry = Registry::default();
+
+        let counter: CounterWithExemplar<Vec<(String, String)>> = Default::default();
+        registry.register("requests", "Requests.\nIn total", counter.clone());
+        counter.inc_by(1, Some(vec![("trace_id".to_string(), "42".to_string())]));
+
+        let gauge: Gauge = Gauge::default();
+        registry.register_with_unit("temperature", "C:\\ temp", Unit::Celsius, gauge.clone());
+        gauge.set(21);
+
+        let info = Info::new(vec![("os", "linux")]);
+        registry.register("build", "Build info", info);
+
+        let histogram = Histogram::new([1.0]);
+        registry.register("latency", "Latency", histogram.clone());
+        histogram.observe(0.5);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP requests_total Requests.\\nIn total.\n".to_owned()
+            + "# TYPE requests_total counter\n"
+            + "requests_total 1\n"
+            + "# HELP temperature_celsius C:\\\\ temp.\n"
+            + "# TYPE temperature_celsius gauge\n"
+            + "temperature_celsius 21\n"
+            + "# HELP build_info Build info.\n"
+            + "# TYPE build_info gauge\n"
+            + "build_info{os=\"linux\"} 1\n"
+            + "# HELP latency Latency.\n"
+            + "# TYPE latency histogram\n"
+            + "latency_sum 0.5\n"
+            + "latency_count 1\n"
+            + "latency_bucket{le=\"1.0\"} 1\n"
+            + "latency_bucket{le=\"+Inf\"} 1\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_gauge_histogram() {
+        let mut registry = Registry::default();
+        let histogram = GaugeHistogram::new([1.0]);
+        registry.register("queue_size", "Queue size", histogram.clone());
+        histogram.observe(0.5);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP queue_size Queue size.\n".to_owned()
+            + "# TYPE queue_size histogram\n"
+            + "queue_size_sum 0.5\n"
+            + "queue_size_count 1\n"
+            + "queue_size_bucket{le=\"1.0\"} 1\n"
+            + "queue_size_bucket{le=\"+Inf\"} 1\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn omit_created() {
+        let mut registry = Registry::default();
+        registry.set_emit_created(true);
+        registry.register("requests", "Requests", Counter::<u64>::default());
+        registry.register("latency", "Latency", Histogram::new([1.0]));
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        assert!(!encoded.contains("_created"));
+    }
+}
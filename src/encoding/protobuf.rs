@@ -30,20 +30,44 @@ pub mod openmetrics_data_model {
     include!(concat!(env!("OUT_DIR"), "/openmetrics.rs"));
 }
 
-use std::{borrow::Cow, collections::HashMap};
+use std::{borrow::Cow, collections::HashMap, time::SystemTime};
 
+use crate::collector::Collector;
+use crate::metrics::exemplar::{Exemplar, ExemplarLabelOverflow};
 use crate::metrics::MetricType;
+use crate::registry::Prefix;
 use crate::registry::{Registry, Unit};
-use crate::{metrics::exemplar::Exemplar, registry::Prefix};
 
-use super::{EncodeCounterValue, EncodeExemplarValue, EncodeGaugeValue, EncodeLabelSet};
+use super::{
+    EncodeCounterValue, EncodeExemplarValue, EncodeGaugeValue, EncodeLabelSet, NoLabelSet,
+};
+
+/// The HTTP `Content-Type` of the OpenMetrics protobuf format, including the
+/// format version.
+///
+/// Serve expositions encoded via [`encode`] with this `Content-Type`, as
+/// Prometheus selects the parser based on it.
+pub const CONTENT_TYPE: &str = "application/openmetrics-protobuf; version=1.0.0";
 
 /// Encode the metrics registered with the provided [`Registry`] into MetricSet
 /// using the OpenMetrics protobuf format.
 pub fn encode(registry: &Registry) -> Result<openmetrics_data_model::MetricSet, std::fmt::Error> {
+    encode_collected(registry, &mut std::iter::empty())
+}
+
+/// Like [`encode`], taking the [`Collector`] of each
+/// [`AsyncCollector`](crate::collector::AsyncCollector) from `collected`, see
+/// [`Registry::encode_collected`].
+pub(crate) fn encode_collected(
+    registry: &Registry,
+    collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+) -> Result<openmetrics_data_model::MetricSet, std::fmt::Error> {
     let mut metric_set = openmetrics_data_model::MetricSet::default();
-    let mut descriptor_encoder = DescriptorEncoder::new(&mut metric_set.metric_families).into();
-    registry.encode(&mut descriptor_encoder)?;
+    let mut descriptor_encoder = DescriptorEncoder::new(&mut metric_set.metric_families)
+        .with_emit_created(registry.emit_created())
+        .with_exemplar_overflow(registry.exemplar_overflow())
+        .into();
+    registry.encode_collected(&mut descriptor_encoder, collected)?;
     Ok(metric_set)
 }
 
@@ -52,8 +76,11 @@ impl From<MetricType> for openmetrics_data_model::MetricType {
         match m {
             MetricType::Counter => openmetrics_data_model::MetricType::Counter,
             MetricType::Gauge => openmetrics_data_model::MetricType::Gauge,
+            MetricType::GaugeHistogram => openmetrics_data_model::MetricType::GaugeHistogram,
             MetricType::Histogram => openmetrics_data_model::MetricType::Histogram,
             MetricType::Info => openmetrics_data_model::MetricType::Info,
+            MetricType::StateSet => openmetrics_data_model::MetricType::StateSet,
+            MetricType::Summary => openmetrics_data_model::MetricType::Summary,
             MetricType::Unknown => openmetrics_data_model::MetricType::Unknown,
         }
     }
@@ -67,19 +94,38 @@ pub(crate) struct DescriptorEncoder<'a> {
     metric_families: &'a mut Vec<openmetrics_data_model::MetricFamily>,
     prefix: Option<&'a Prefix>,
     labels: &'a [(Cow<'static, str>, Cow<'static, str>)],
+    /// Whether to set the `created` field of counters and histograms.
+    emit_created: bool,
+    /// Handling of exemplars exceeding the label set length limit.
+    exemplar_overflow: ExemplarLabelOverflow,
 }
 
 impl DescriptorEncoder<'_> {
     pub(crate) fn new(
         metric_families: &mut Vec<openmetrics_data_model::MetricFamily>,
-    ) -> DescriptorEncoder {
+    ) -> DescriptorEncoder<'_> {
         DescriptorEncoder {
             metric_families,
             prefix: Default::default(),
             labels: Default::default(),
+            emit_created: false,
+            exemplar_overflow: Default::default(),
         }
     }
 
+    pub(crate) fn with_emit_created(mut self, emit_created: bool) -> Self {
+        self.emit_created = emit_created;
+        self
+    }
+
+    pub(crate) fn with_exemplar_overflow(
+        mut self,
+        exemplar_overflow: ExemplarLabelOverflow,
+    ) -> Self {
+        self.exemplar_overflow = exemplar_overflow;
+        self
+    }
+
     pub(crate) fn with_prefix_and_labels<'s>(
         &'s mut self,
         prefix: Option<&'s Prefix>,
@@ -89,6 +135,8 @@ impl DescriptorEncoder<'_> {
             prefix,
             labels,
             metric_families: self.metric_families,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
         }
     }
 
@@ -102,7 +150,7 @@ impl DescriptorEncoder<'_> {
         let family = openmetrics_data_model::MetricFamily {
             name: {
                 match self.prefix {
-                    Some(prefix) => prefix.as_str().to_string() + "_" + name,
+                    Some(prefix) => format!("{}{}{name}", prefix.as_str(), prefix.separator()),
                     None => name.to_string(),
                 }
             },
@@ -135,6 +183,9 @@ impl DescriptorEncoder<'_> {
                 .metrics,
             metric_type,
             labels,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            timestamp: None,
         })
     }
 }
@@ -150,6 +201,12 @@ pub(crate) struct MetricEncoder<'f> {
     family: &'f mut Vec<openmetrics_data_model::Metric>,
     /// Labels to be added to each metric.
     labels: Vec<openmetrics_data_model::Label>,
+    /// Whether to set the `created` field of counters and histograms.
+    emit_created: bool,
+    /// Handling of exemplars exceeding the label set length limit.
+    exemplar_overflow: ExemplarLabelOverflow,
+    /// Timestamp of each metric point, if any.
+    timestamp: Option<SystemTime>,
 }
 
 impl MetricEncoder<'_> {
@@ -161,6 +218,7 @@ impl MetricEncoder<'_> {
         &mut self,
         v: &CounterValue,
         exemplar: Option<&Exemplar<S, ExemplarValue>>,
+        created: Option<SystemTime>,
     ) -> Result<(), std::fmt::Error> {
         let mut value = openmetrics_data_model::counter_value::Total::IntValue(0);
         let mut e = CounterValueEncoder { value: &mut value }.into();
@@ -172,11 +230,11 @@ impl MetricEncoder<'_> {
                 value: Some(openmetrics_data_model::metric_point::Value::CounterValue(
                     openmetrics_data_model::CounterValue {
                         total: Some(value),
-                        exemplar: exemplar.map(|e| e.try_into()).transpose()?,
-                        ..Default::default()
+                        exemplar: exemplar.map(|e| self.exemplar(e)).transpose()?.flatten(),
+                        created: self.created(created),
                     },
                 )),
-                ..Default::default()
+                timestamp: self.timestamp.map(Into::into),
             }],
         });
 
@@ -197,7 +255,36 @@ impl MetricEncoder<'_> {
                 value: Some(openmetrics_data_model::metric_point::Value::GaugeValue(
                     openmetrics_data_model::GaugeValue { value: Some(value) },
                 )),
-                ..Default::default()
+                timestamp: self.timestamp.map(Into::into),
+            }],
+        });
+
+        Ok(())
+    }
+
+    pub fn encode_unknown<GaugeValue: EncodeGaugeValue>(
+        &mut self,
+        v: &GaugeValue,
+    ) -> Result<(), std::fmt::Error> {
+        let mut value = openmetrics_data_model::gauge_value::Value::IntValue(0);
+        let mut e = GaugeValueEncoder { value: &mut value }.into();
+        v.encode(&mut e)?;
+        let value = match value {
+            openmetrics_data_model::gauge_value::Value::IntValue(v) => {
+                openmetrics_data_model::unknown_value::Value::IntValue(v)
+            }
+            openmetrics_data_model::gauge_value::Value::DoubleValue(v) => {
+                openmetrics_data_model::unknown_value::Value::DoubleValue(v)
+            }
+        };
+
+        self.family.push(openmetrics_data_model::Metric {
+            labels: self.labels.clone(),
+            metric_points: vec![openmetrics_data_model::MetricPoint {
+                value: Some(openmetrics_data_model::metric_point::Value::UnknownValue(
+                    openmetrics_data_model::UnknownValue { value: Some(value) },
+                )),
+                timestamp: self.timestamp.map(Into::into),
             }],
         });
 
@@ -222,17 +309,60 @@ impl MetricEncoder<'_> {
                 value: Some(openmetrics_data_model::metric_point::Value::InfoValue(
                     openmetrics_data_model::InfoValue { info: info_labels },
                 )),
-                ..Default::default()
+                timestamp: self.timestamp.map(Into::into),
             }],
         });
 
         Ok(())
     }
 
+    pub fn encode_stateset<'s>(
+        &mut self,
+        states: impl IntoIterator<Item = (&'s str, bool)>,
+    ) -> Result<(), std::fmt::Error> {
+        let states = states
+            .into_iter()
+            .map(
+                |(name, enabled)| openmetrics_data_model::state_set_value::State {
+                    enabled,
+                    name: name.to_string(),
+                },
+            )
+            .collect();
+
+        self.family.push(openmetrics_data_model::Metric {
+            labels: self.labels.clone(),
+            metric_points: vec![openmetrics_data_model::MetricPoint {
+                value: Some(openmetrics_data_model::metric_point::Value::StateSetValue(
+                    openmetrics_data_model::StateSetValue { states },
+                )),
+                timestamp: self.timestamp.map(Into::into),
+            }],
+        });
+
+        Ok(())
+    }
+
+    pub fn by_ref(&mut self) -> MetricEncoder<'_> {
+        MetricEncoder {
+            metric_type: self.metric_type,
+            family: self.family,
+            labels: self.labels.clone(),
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            timestamp: self.timestamp,
+        }
+    }
+
+    pub(crate) fn with_timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
     pub fn encode_family<S: EncodeLabelSet>(
         &mut self,
         label_set: &S,
-    ) -> Result<MetricEncoder, std::fmt::Error> {
+    ) -> Result<MetricEncoder<'_>, std::fmt::Error> {
         let mut labels = self.labels.clone();
         label_set.encode(
             LabelSetEncoder {
@@ -245,26 +375,31 @@ impl MetricEncoder<'_> {
             metric_type: self.metric_type,
             family: self.family,
             labels,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            timestamp: self.timestamp,
         })
     }
 
-    pub fn encode_histogram<S: EncodeLabelSet>(
+    pub fn encode_histogram<S: EncodeLabelSet, V: EncodeExemplarValue>(
         &mut self,
         sum: f64,
         count: u64,
-        buckets: &[(f64, u64)],
-        exemplars: Option<&HashMap<usize, Exemplar<S, f64>>>,
+        buckets: impl Iterator<Item = (f64, u64)>,
+        exemplars: Option<&HashMap<usize, Exemplar<S, V>>>,
+        created: Option<SystemTime>,
     ) -> Result<(), std::fmt::Error> {
         let buckets = buckets
-            .iter()
             .enumerate()
             .map(|(i, (upper_bound, count))| {
                 Ok(openmetrics_data_model::histogram_value::Bucket {
-                    upper_bound: *upper_bound,
-                    count: *count,
+                    upper_bound,
+                    count,
                     exemplar: exemplars
-                        .and_then(|exemplars| exemplars.get(&i).map(|exemplar| exemplar.try_into()))
-                        .transpose()?,
+                        .and_then(|exemplars| exemplars.get(&i))
+                        .map(|exemplar| self.exemplar(exemplar))
+                        .transpose()?
+                        .flatten(),
                 })
             })
             .collect::<Result<Vec<_>, std::fmt::Error>>()?;
@@ -275,19 +410,53 @@ impl MetricEncoder<'_> {
                 value: Some(openmetrics_data_model::metric_point::Value::HistogramValue(
                     openmetrics_data_model::HistogramValue {
                         count,
-                        created: None,
+                        created: self.created(created),
                         buckets,
                         sum: Some(openmetrics_data_model::histogram_value::Sum::DoubleValue(
                             sum,
                         )),
                     },
                 )),
-                ..Default::default()
+                timestamp: self.timestamp.map(Into::into),
             }],
         });
 
         Ok(())
     }
+
+    pub fn encode_gauge_histogram(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+    ) -> Result<(), std::fmt::Error> {
+        // Gauge histograms share the protobuf representation of histograms,
+        // differing only in their metric type.
+        self.encode_histogram::<NoLabelSet, f64>(sum, count, buckets, None, None)
+    }
+
+    /// The `created` field for the given creation time, if enabled.
+    fn created(&self, created: Option<SystemTime>) -> Option<prost_types::Timestamp> {
+        created.filter(|_| self.emit_created).map(Into::into)
+    }
+
+    /// The given exemplar, unless omitted for exceeding the label set length
+    /// limit.
+    fn exemplar<S: EncodeLabelSet, V: EncodeExemplarValue>(
+        &self,
+        exemplar: &Exemplar<S, V>,
+    ) -> Result<Option<openmetrics_data_model::Exemplar>, std::fmt::Error> {
+        let exemplar: openmetrics_data_model::Exemplar = exemplar.try_into()?;
+        let label_set_length = exemplar
+            .label
+            .iter()
+            .map(|l| l.name.chars().count() + l.value.chars().count())
+            .sum();
+        Ok(self
+            .exemplar_overflow
+            .check(label_set_length)?
+            .then_some(exemplar))
+    }
 }
 
 impl<S: EncodeLabelSet, V: EncodeExemplarValue> TryFrom<&Exemplar<S, V>>
@@ -311,7 +480,7 @@ impl<S: EncodeLabelSet, V: EncodeExemplarValue> TryFrom<&Exemplar<S, V>>
 
         Ok(openmetrics_data_model::Exemplar {
             value,
-            timestamp: Default::default(),
+            timestamp: exemplar.timestamp.map(Into::into),
             label: labels,
         })
     }
@@ -382,11 +551,17 @@ pub(crate) struct LabelSetEncoder<'a> {
 }
 
 impl LabelSetEncoder<'_> {
-    pub fn encode_label(&mut self) -> LabelEncoder {
+    pub fn encode_label(&mut self) -> LabelEncoder<'_> {
         LabelEncoder {
             labels: self.labels,
         }
     }
+
+    pub fn by_ref(&mut self) -> LabelSetEncoder<'_> {
+        LabelSetEncoder {
+            labels: self.labels,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -395,7 +570,7 @@ pub(crate) struct LabelEncoder<'a> {
 }
 
 impl LabelEncoder<'_> {
-    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder, std::fmt::Error> {
+    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder<'_>, std::fmt::Error> {
         self.labels.push(openmetrics_data_model::Label::default());
 
         Ok(LabelKeyEncoder {
@@ -447,8 +622,10 @@ mod tests {
     use crate::metrics::exemplar::{CounterWithExemplar, HistogramWithExemplars};
     use crate::metrics::family::Family;
     use crate::metrics::gauge::Gauge;
+    use crate::metrics::gauge_histogram::GaugeHistogram;
     use crate::metrics::histogram::{exponential_buckets, Histogram};
     use crate::metrics::info::Info;
+    use crate::metrics::stateset::StateSet;
     use crate::registry::Unit;
     use std::borrow::Cow;
     use std::collections::HashSet;
@@ -484,6 +661,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encode_counter_created() {
+        let mut registry = Registry::default();
+        registry.set_emit_created(true);
+        registry.register("my_counter", "My counter", Counter::<u64>::default());
+
+        let metric_set = encode(&registry).unwrap();
+
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::CounterValue(value) => {
+                assert!(value.created.is_some());
+            }
+            _ => panic!("wrong value type"),
+        }
+    }
+
+    #[test]
+    fn encode_exemplar_timestamp() {
+        let mut registry = Registry::default();
+        let counter: CounterWithExemplar<Vec<(String, String)>> = Default::default();
+        registry.register("my_counter", "My counter", counter.clone());
+        counter.inc_by_with_timestamp(
+            1,
+            Some(vec![("trace_id".to_string(), "42".to_string())]),
+            Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(42)),
+        );
+
+        let metric_set = encode(&registry).unwrap();
+
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::CounterValue(value) => {
+                let exemplar = value.exemplar.as_ref().unwrap();
+                assert_eq!(
+                    Some(prost_types::Timestamp {
+                        seconds: 42,
+                        nanos: 0
+                    }),
+                    exemplar.timestamp
+                );
+            }
+            _ => panic!("wrong value type"),
+        }
+    }
+
+    #[test]
+    fn encode_exemplar_label_set_length_limit() {
+        use crate::metrics::exemplar::ExemplarLabelOverflow;
+
+        let mut registry = Registry::default();
+        let histogram = HistogramWithExemplars::new([1.0].into_iter());
+        registry.register("my_histogram", "My histogram", histogram.clone());
+        histogram.observe(0.5, Some(vec![("trace_id".to_string(), "a".repeat(121))]));
+
+        let metric_set = encode(&registry).unwrap();
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::HistogramValue(value) => {
+                assert!(value.buckets.iter().all(|b| b.exemplar.is_none()));
+            }
+            _ => panic!("wrong value type"),
+        }
+
+        registry.set_exemplar_overflow(ExemplarLabelOverflow::Error);
+        assert!(encode(&registry).is_err());
+    }
+
+    #[test]
+    fn encode_collector_with_registry_labels() {
+        use crate::encoding::EncodeMetric;
+        use crate::metrics::counter::ConstCounter;
+
+        let mut registry =
+            Registry::with_labels([(Cow::Borrowed("host"), Cow::Borrowed("a"))].into_iter());
+        registry.register_collector(Box::new(crate::collector::from_fn(|mut encoder| {
+            let counter = ConstCounter::new(1u64);
+            let metric_encoder =
+                encoder.encode_descriptor("events", "Events", None, counter.metric_type())?;
+            counter.encode(metric_encoder)
+        })));
+
+        let metric_set = encode(&registry).unwrap();
+
+        let metric = &metric_set.metric_families[0].metrics[0];
+        assert_eq!(
+            vec![openmetrics_data_model::Label {
+                name: "host".to_string(),
+                value: "a".to_string(),
+            }],
+            metric.labels
+        );
+    }
+
     #[test]
     fn encode_counter_double() {
         // Using `f64`
@@ -575,6 +843,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn encode_metric_point_timestamp() {
+        use crate::collector::Collector;
+        use crate::encoding::{DescriptorEncoder, EncodeMetric};
+        use crate::metrics::gauge::ConstGauge;
+        use crate::metrics::TypedMetric;
+        use std::time::{Duration, UNIX_EPOCH};
+
+        #[derive(Debug)]
+        struct TimestampedCollector {
+            timestamp: SystemTime,
+        }
+
+        impl Collector for TimestampedCollector {
+            fn encode(&self, mut encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+                let mut metric_encoder = encoder.encode_descriptor(
+                    "my_gauge",
+                    "My gauge",
+                    None,
+                    ConstGauge::<i64>::TYPE,
+                )?;
+                ConstGauge::new(1).encode(metric_encoder.with_timestamp(self.timestamp))?;
+                ConstGauge::with_labels(2, [("kind", "untimed")]).encode(metric_encoder.by_ref())
+            }
+        }
+
+        let mut registry = Registry::default();
+        registry.register_collector(Box::new(TimestampedCollector {
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_500),
+        }));
+
+        let metric_set = encode(&registry).unwrap();
+        let metrics = &metric_set.metric_families[0].metrics;
+        assert_eq!(
+            Some(prost_types::Timestamp {
+                seconds: 1,
+                nanos: 500_000_000,
+            }),
+            metrics[0].metric_points[0].timestamp
+        );
+        assert_eq!(None, metrics[1].metric_points[0].timestamp);
+    }
+
+    #[test]
+    fn encode_unknown() {
+        use crate::metrics::unknown::Unknown;
+
+        let mut registry = Registry::default();
+        let unknown: Unknown = Unknown::default();
+        registry.register("my_unknown", "My unknown", unknown.clone());
+        unknown.set(1.5);
+
+        let metric_set = encode(&registry).unwrap();
+        assert_eq!(
+            openmetrics_data_model::MetricType::Unknown as i32,
+            extract_metric_type(&metric_set)
+        );
+
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::UnknownValue(value) => {
+                let expected = openmetrics_data_model::unknown_value::Value::DoubleValue(1.5);
+                assert_eq!(Some(expected), value.value);
+            }
+            _ => panic!("wrong value type"),
+        }
+    }
+
     #[test]
     fn encode_gauge() {
         let mut registry = Registry::default();
@@ -634,8 +969,17 @@ mod tests {
         registry.register("my_gauge", "My gauge", gauge.clone());
         gauge.set(u64::MAX);
 
-        // This expected to fail as protobuf uses i64 and u64::MAX does not fit into it.
-        assert!(encode(&registry).is_err());
+        let metric_set = encode(&registry).unwrap();
+
+        // Protobuf gauges use i64, thus values exceeding it are encoded as f64.
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::GaugeValue(value) => {
+                let expected =
+                    openmetrics_data_model::gauge_value::Value::DoubleValue(u64::MAX as f64);
+                assert_eq!(Some(expected), value.value);
+            }
+            _ => panic!("wrong value type"),
+        }
     }
 
     #[test]
@@ -884,6 +1228,79 @@ mod tests {
         assert_eq!("my_family_histogram", metric_set.metric_families[1].name);
     }
 
+    #[test]
+    fn encode_gauge_histogram() {
+        let mut registry = Registry::default();
+        let histogram = GaugeHistogram::new([1.0]);
+        registry.register("queue_items", "Items in queue", histogram.clone());
+        histogram.observe(0.5);
+
+        let metric_set = encode(&registry).unwrap();
+
+        assert_eq!(
+            openmetrics_data_model::MetricType::GaugeHistogram as i32,
+            extract_metric_type(&metric_set)
+        );
+
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::HistogramValue(value) => {
+                assert_eq!(
+                    Some(openmetrics_data_model::histogram_value::Sum::DoubleValue(
+                        0.5
+                    )),
+                    value.sum
+                );
+                assert_eq!(1, value.count);
+                assert_eq!(2, value.buckets.len());
+            }
+            _ => panic!("wrong value type"),
+        }
+    }
+
+    #[test]
+    fn encode_stateset() {
+        #[derive(Clone, Copy, Debug)]
+        enum Mode {
+            Primary,
+            Standby,
+        }
+
+        impl crate::encoding::EncodeStateSet for Mode {
+            const STATES: &'static [&'static str] = &["primary", "standby"];
+
+            fn index(&self) -> usize {
+                *self as usize
+            }
+        }
+
+        let mut registry = Registry::default();
+        let mode = StateSet::new(Mode::Primary);
+        registry.register("mode", "Replication mode", mode.clone());
+        mode.set(Mode::Standby);
+
+        let metric_set = encode(&registry).unwrap();
+
+        let family = metric_set.metric_families.first().unwrap();
+        assert_eq!("mode", family.name);
+
+        assert_eq!(
+            openmetrics_data_model::MetricType::StateSet as i32,
+            extract_metric_type(&metric_set)
+        );
+
+        match extract_metric_point_value(&metric_set) {
+            openmetrics_data_model::metric_point::Value::StateSetValue(value) => {
+                let states = value
+                    .states
+                    .iter()
+                    .map(|s| (s.name.as_str(), s.enabled))
+                    .collect::<Vec<_>>();
+                assert_eq!(vec![("primary", false), ("standby", true)], states);
+            }
+            _ => panic!("wrong value type"),
+        }
+    }
+
     #[test]
     fn encode_info() {
         let mut registry = Registry::default();
@@ -37,14 +37,23 @@
 //! assert_eq!(expected_msg, buffer);
 //! ```
 
+use crate::collector::Collector;
 use crate::encoding::{EncodeExemplarValue, EncodeLabelSet, NoLabelSet};
-use crate::metrics::exemplar::Exemplar;
+use crate::metrics::exemplar::{Exemplar, ExemplarLabelOverflow};
 use crate::metrics::MetricType;
 use crate::registry::{Prefix, Registry, Unit};
 
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The HTTP `Content-Type` of the OpenMetrics text format, including the
+/// format version and charset.
+///
+/// Serve expositions encoded via [`encode`] with this `Content-Type`, as
+/// Prometheus selects the parser based on it.
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
 
 /// Encode both the metrics registered with the provided [`Registry`] and the
 /// EOF marker into the provided [`Write`]r using the OpenMetrics text format.
@@ -88,8 +97,66 @@ pub fn encode<W>(writer: &mut W, registry: &Registry) -> Result<(), std::fmt::Er
 where
     W: Write,
 {
-    encode_registry(writer, registry)?;
-    encode_eof(writer)
+    encode_collected(writer, registry, &mut std::iter::empty())
+}
+
+/// Encode both the metrics registered with the provided [`Registry`] and the
+/// EOF marker into a new [`String`] using the OpenMetrics text format.
+///
+/// See [`encode`] to encode into a custom [`Write`]r.
+///
+/// ```
+/// # use prometheus_client::encoding::text::encode_to_string;
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// let mut registry = Registry::default();
+/// registry.register("my_counter", "This is my counter", Counter::<u64>::default());
+///
+/// let body = encode_to_string(&registry)?;
+/// assert!(body.ends_with("# EOF\n"));
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub fn encode_to_string(registry: &Registry) -> Result<String, std::fmt::Error> {
+    let mut buffer = String::new();
+    encode(&mut buffer, registry)?;
+    Ok(buffer)
+}
+
+/// Encode both the metrics registered with the provided [`Registry`] and the
+/// EOF marker into a new UTF-8 encoded [`Vec<u8>`] using the OpenMetrics text
+/// format, e.g. to be used as an HTTP response body.
+///
+/// The exposition is written into the returned buffer directly, without an
+/// intermediate [`String`].
+///
+/// ```
+/// # use prometheus_client::encoding::text::encode_to_vec;
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// let mut registry = Registry::default();
+/// registry.register("my_counter", "This is my counter", Counter::<u64>::default());
+///
+/// let body: Vec<u8> = encode_to_vec(&registry)?;
+/// assert!(body.ends_with(b"# EOF\n"));
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub fn encode_to_vec(registry: &Registry) -> Result<Vec<u8>, std::fmt::Error> {
+    let mut buffer = Vec::new();
+    encode(&mut VecWriter(&mut buffer), registry)?;
+    Ok(buffer)
+}
+
+/// Adapter writing [`Write`] output into a [`Vec<u8>`] as UTF-8, see
+/// [`encode_to_vec`].
+struct VecWriter<'a>(&'a mut Vec<u8>);
+
+impl Write for VecWriter<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.extend_from_slice(s.as_bytes());
+        Ok(())
+    }
 }
 
 /// Encode the metrics registered with the provided [`Registry`] into the
@@ -142,7 +209,119 @@ pub fn encode_registry<W>(writer: &mut W, registry: &Registry) -> Result<(), std
 where
     W: Write,
 {
-    registry.encode(&mut DescriptorEncoder::new(writer).into())
+    registry.encode(
+        &mut DescriptorEncoder::new(writer)
+            .with_emit_created(registry.emit_created())
+            .with_exemplar_overflow(registry.exemplar_overflow())
+            .into(),
+    )
+}
+
+/// Encode both the metrics registered with the provided [`Registry`] and the
+/// EOF marker into the provided [`Write`]r using the OpenMetrics text format,
+/// with a stable order of series and labels.
+///
+/// In contrast to [`encode`], the series of each
+/// [`Family`](crate::metrics::family::Family) are ordered by their label set,
+/// and the labels of each series and exemplar are ordered by label name.
+/// Thus the output does not depend on the iteration order of a [`HashMap`],
+/// e.g. for snapshot tests. Metric families are encoded in registration order
+/// either way. Sorting buffers the series of each family, prefer [`encode`]
+/// where the order does not matter.
+///
+/// ```
+/// # use prometheus_client::encoding::text::encode_sorted;
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::metrics::family::Family;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// let mut registry = Registry::default();
+/// let requests = Family::<Vec<(&str, &str)>, Counter>::default();
+/// registry.register("requests", "Requests", requests.clone());
+///
+/// requests.get_or_create(&vec![("path", "/"), ("method", "PUT")]).inc();
+/// requests.get_or_create(&vec![("path", "/"), ("method", "GET")]).inc();
+///
+/// let mut buffer = String::new();
+/// encode_sorted(&mut buffer, &registry)?;
+///
+/// let expected = "# HELP requests Requests.\n".to_owned()
+///     + "# TYPE requests counter\n"
+///     + "requests_total{method=\"GET\",path=\"/\"} 1\n"
+///     + "requests_total{method=\"PUT\",path=\"/\"} 1\n"
+///     + "# EOF\n";
+/// assert_eq!(expected, buffer);
+/// # Ok::<(), std::fmt::Error>(())
+/// ```
+pub fn encode_sorted<W>(writer: &mut W, registry: &Registry) -> Result<(), std::fmt::Error>
+where
+    W: Write,
+{
+    registry.encode(
+        &mut DescriptorEncoder::new(writer)
+            .with_emit_created(registry.emit_created())
+            .with_exemplar_overflow(registry.exemplar_overflow())
+            .with_sorted()
+            .into(),
+    )?;
+    encode_eof(writer)
+}
+
+/// Encode the metrics registered with the provided [`Registry`], including
+/// those of any [`AsyncCollector`], into the provided [`Write`]r using the
+/// OpenMetrics text format.
+///
+/// All [`AsyncCollector`]s are awaited in turn before encoding starts, after
+/// which metrics and collectors are encoded just like [`encode`] does. The
+/// returned future is [`Send`] if `W` is, e.g. to encode within
+/// `tokio::spawn`.
+///
+/// # Examples
+///
+/// ```
+/// # use prometheus_client::encoding::text::encode_async;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// # futures::executor::block_on(async {
+/// let registry = Registry::default();
+///
+/// let mut buffer = String::new();
+/// encode_async(&mut buffer, &registry).await?;
+/// # Ok::<(), std::fmt::Error>(())
+/// # }).unwrap();
+/// ```
+///
+/// [`AsyncCollector`]: crate::collector::AsyncCollector
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub async fn encode_async<W>(writer: &mut W, registry: &Registry) -> Result<(), std::fmt::Error>
+where
+    W: Write,
+{
+    let collected = registry.collect_async().await;
+    encode_collected(writer, registry, &mut collected.into_iter())
+}
+
+/// Like [`encode`], taking the [`Collector`] of each [`AsyncCollector`] from
+/// `collected`, see [`Registry::encode_collected`].
+///
+/// [`AsyncCollector`]: crate::collector::AsyncCollector
+pub(crate) fn encode_collected<W>(
+    writer: &mut W,
+    registry: &Registry,
+    collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+) -> Result<(), std::fmt::Error>
+where
+    W: Write,
+{
+    registry.encode_collected(
+        &mut DescriptorEncoder::new(writer)
+            .with_emit_created(registry.emit_created())
+            .with_exemplar_overflow(registry.exemplar_overflow())
+            .into(),
+        collected,
+    )?;
+    encode_eof(writer)
 }
 
 /// Encode the EOF marker into the provided [`Write`]r using the OpenMetrics
@@ -182,10 +361,129 @@ where
     writer.write_str("# EOF\n")
 }
 
+/// Encode both the metrics registered with the provided [`Registry`] and the
+/// EOF marker into the provided [`std::io::Write`]r using the OpenMetrics text
+/// format.
+///
+/// In contrast to [`encode`], the exposition is not assembled in memory first.
+/// Instead it is streamed through a fixed size buffer into `writer`, e.g. an
+/// HTTP response body, keeping memory usage bounded regardless of the number
+/// of series in the [`Registry`]. `writer` is flushed once the exposition is
+/// complete.
+///
+/// # Examples
+///
+/// ```
+/// # use prometheus_client::encoding::text::encode_to_io;
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// let mut registry = Registry::default();
+/// let counter: Counter = Counter::default();
+/// registry.register("my_counter", "This is my counter", counter.clone());
+///
+/// let mut body = Vec::new();
+/// encode_to_io(&mut body, &registry)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn encode_to_io<W>(writer: W, registry: &Registry) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    let mut writer = IoWriter {
+        writer: std::io::BufWriter::new(writer),
+        error: None,
+    };
+
+    encode(&mut writer, registry).map_err(|e| {
+        writer
+            .error
+            .take()
+            .unwrap_or_else(|| std::io::Error::other(e))
+    })?;
+
+    std::io::Write::flush(&mut writer.writer)
+}
+
+/// Adapter writing [`Write`] output into a [`std::io::Write`], retaining the
+/// underlying I/O error which [`std::fmt::Error`] can not carry.
+struct IoWriter<W: std::io::Write> {
+    writer: std::io::BufWriter<W>,
+    error: Option<std::io::Error>,
+}
+
+impl<W: std::io::Write> Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        std::io::Write::write_all(&mut self.writer, s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            std::fmt::Error
+        })
+    }
+}
+
 pub(crate) struct DescriptorEncoder<'a> {
     writer: &'a mut dyn Write,
     prefix: Option<&'a Prefix>,
     labels: &'a [(Cow<'static, str>, Cow<'static, str>)],
+    /// Predicate on the metric family name. The [`Registry`] skips metrics
+    /// not passing it, families of [`Collector`](crate::collector::Collector)s
+    /// not passing it are encoded into [`Sink`] instead of `writer`.
+    filter: Option<&'a dyn Fn(&str) -> bool>,
+    sink: Sink,
+    /// Whether to encode `_created` samples.
+    emit_created: bool,
+    /// Handling of exemplars exceeding the label set length limit.
+    exemplar_overflow: ExemplarLabelOverflow,
+    /// Whether to encode the Prometheus text format 0.0.4 instead of
+    /// OpenMetrics. See [`prometheus_text`](super::prometheus_text).
+    legacy: bool,
+    /// Whether to order series and labels, see [`encode_sorted`].
+    sorted: bool,
+}
+
+/// Write the given `# HELP` text, escaping backslashes and line feeds, as well
+/// as double quotes unless `legacy`. Carriage returns have no escape sequence in
+/// either format and are dropped.
+fn write_help(writer: &mut dyn Write, help: &str, legacy: bool) -> Result<(), std::fmt::Error> {
+    for c in help.chars() {
+        match c {
+            '\\' => writer.write_str("\\\\")?,
+            '\n' => writer.write_str("\\n")?,
+            '"' if !legacy => writer.write_str("\\\"")?,
+            '\r' => {}
+            c => writer.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+/// The labels of the given label sets, comma-separated and ordered by label
+/// name, as encoded by [`encode_sorted`].
+pub(crate) fn sorted_labels(
+    label_sets: &[&dyn super::EncodeLabelSet],
+) -> Result<String, std::fmt::Error> {
+    let mut labels = Vec::new();
+    for label_set in label_sets {
+        label_set.encode(
+            LabelSetEncoder::new(&mut Sink, &mut true)
+                .with_sorted(&mut labels)
+                .into(),
+        )?;
+    }
+    // Label names can not contain `=`.
+    labels.sort_by(|a, b| a.split('=').next().cmp(&b.split('=').next()));
+    Ok(labels.join(","))
+}
+
+/// [`Write`]r discarding all output. Used for metric families of collectors
+/// excluded by a filter, see [`Registry::encode_filtered`].
+#[derive(Debug, Default)]
+struct Sink;
+
+impl Write for Sink {
+    fn write_str(&mut self, _s: &str) -> std::fmt::Result {
+        Ok(())
+    }
 }
 
 impl std::fmt::Debug for DescriptorEncoder<'_> {
@@ -194,12 +492,64 @@ impl std::fmt::Debug for DescriptorEncoder<'_> {
     }
 }
 
-impl DescriptorEncoder<'_> {
-    pub(crate) fn new(writer: &mut dyn Write) -> DescriptorEncoder {
+impl<'a> DescriptorEncoder<'a> {
+    pub(crate) fn new(writer: &mut dyn Write) -> DescriptorEncoder<'_> {
         DescriptorEncoder {
             writer,
             prefix: Default::default(),
             labels: Default::default(),
+            filter: None,
+            sink: Sink,
+            emit_created: false,
+            exemplar_overflow: Default::default(),
+            legacy: false,
+            sorted: false,
+        }
+    }
+
+    pub(crate) fn with_emit_created(mut self, emit_created: bool) -> Self {
+        self.emit_created = emit_created;
+        self
+    }
+
+    pub(crate) fn with_exemplar_overflow(
+        mut self,
+        exemplar_overflow: ExemplarLabelOverflow,
+    ) -> Self {
+        self.exemplar_overflow = exemplar_overflow;
+        self
+    }
+
+    /// Encode the Prometheus text format 0.0.4 instead of OpenMetrics.
+    pub(crate) fn with_legacy_format(mut self) -> Self {
+        self.legacy = true;
+        self
+    }
+
+    /// Order series and labels, see [`encode_sorted`].
+    pub(crate) fn with_sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    pub(crate) fn filter(&self) -> Option<&'a dyn Fn(&str) -> bool> {
+        self.filter
+    }
+
+    pub(crate) fn with_filter<'s>(
+        &'s mut self,
+        filter: &'s dyn Fn(&str) -> bool,
+    ) -> DescriptorEncoder<'s> {
+        DescriptorEncoder {
+            prefix: self.prefix,
+            labels: self.labels,
+            filter: Some(filter),
+            writer: self.writer,
+            sink: Sink,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
         }
     }
 
@@ -211,7 +561,13 @@ impl DescriptorEncoder<'_> {
         DescriptorEncoder {
             prefix,
             labels,
+            filter: self.filter,
             writer: self.writer,
+            sink: Sink,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
         }
     }
 
@@ -222,10 +578,42 @@ impl DescriptorEncoder<'_> {
         unit: Option<&'s Unit>,
         metric_type: MetricType,
     ) -> Result<MetricEncoder<'s>, std::fmt::Error> {
+        if let Some(filter) = self.filter {
+            let mut family_name = String::new();
+            if let Some(prefix) = self.prefix {
+                family_name.push_str(prefix.as_str());
+                family_name.push(prefix.separator());
+            }
+            family_name.push_str(name);
+            if let Some(unit) = unit {
+                family_name.push('_');
+                family_name.push_str(unit.as_str());
+            }
+
+            if !filter(&family_name) {
+                return Ok(MetricEncoder {
+                    writer: &mut self.sink,
+                    prefix: self.prefix,
+                    name,
+                    unit,
+                    const_labels: self.labels,
+                    family_labels: None,
+                    emit_created: self.emit_created,
+                    exemplar_overflow: self.exemplar_overflow,
+                    legacy: self.legacy,
+                    sorted: self.sorted,
+                });
+            }
+        }
+
+        if self.legacy {
+            return self.encode_legacy_descriptor(name, help, unit, metric_type);
+        }
+
         self.writer.write_str("# HELP ")?;
         if let Some(prefix) = self.prefix {
             self.writer.write_str(prefix.as_str())?;
-            self.writer.write_str("_")?;
+            self.writer.write_char(prefix.separator())?;
         }
         self.writer.write_str(name)?;
         if let Some(unit) = unit {
@@ -233,13 +621,13 @@ impl DescriptorEncoder<'_> {
             self.writer.write_str(unit.as_str())?;
         }
         self.writer.write_str(" ")?;
-        self.writer.write_str(help)?;
+        write_help(self.writer, help, false)?;
         self.writer.write_str("\n")?;
 
         self.writer.write_str("# TYPE ")?;
         if let Some(prefix) = self.prefix {
             self.writer.write_str(prefix.as_str())?;
-            self.writer.write_str("_")?;
+            self.writer.write_char(prefix.separator())?;
         }
         self.writer.write_str(name)?;
         if let Some(unit) = unit {
@@ -254,7 +642,7 @@ impl DescriptorEncoder<'_> {
             self.writer.write_str("# UNIT ")?;
             if let Some(prefix) = self.prefix {
                 self.writer.write_str(prefix.as_str())?;
-                self.writer.write_str("_")?;
+                self.writer.write_char(prefix.separator())?;
             }
             self.writer.write_str(name)?;
             self.writer.write_str("_")?;
@@ -271,6 +659,72 @@ impl DescriptorEncoder<'_> {
             unit,
             const_labels: self.labels,
             family_labels: None,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
+        })
+    }
+
+    /// Encode the `# HELP` and `# TYPE` lines of the Prometheus text format
+    /// 0.0.4.
+    ///
+    /// In contrast to OpenMetrics, the family name of counters and info
+    /// metrics includes the sample suffix, there is no `# UNIT` line, and metric
+    /// types unknown to the format are mapped to their closest equivalent.
+    fn encode_legacy_descriptor<'s>(
+        &'s mut self,
+        name: &'s str,
+        help: &str,
+        unit: Option<&'s Unit>,
+        metric_type: MetricType,
+    ) -> Result<MetricEncoder<'s>, std::fmt::Error> {
+        let (suffix, type_str) = match metric_type {
+            MetricType::Counter => ("_total", "counter"),
+            MetricType::Gauge => ("", "gauge"),
+            MetricType::GaugeHistogram => ("", "histogram"),
+            MetricType::Histogram => ("", "histogram"),
+            MetricType::Info => ("_info", "gauge"),
+            MetricType::StateSet => ("", "gauge"),
+            MetricType::Summary => ("", "summary"),
+            MetricType::Unknown => ("", "untyped"),
+        };
+
+        let mut family_name = String::new();
+        if let Some(prefix) = self.prefix {
+            family_name.push_str(prefix.as_str());
+            family_name.push(prefix.separator());
+        }
+        family_name.push_str(name);
+        if let Some(unit) = unit {
+            family_name.push('_');
+            family_name.push_str(unit.as_str());
+        }
+        family_name.push_str(suffix);
+
+        self.writer.write_str("# HELP ")?;
+        self.writer.write_str(&family_name)?;
+        self.writer.write_str(" ")?;
+        write_help(self.writer, help, true)?;
+        self.writer.write_str("\n")?;
+
+        self.writer.write_str("# TYPE ")?;
+        self.writer.write_str(&family_name)?;
+        self.writer.write_str(" ")?;
+        self.writer.write_str(type_str)?;
+        self.writer.write_str("\n")?;
+
+        Ok(MetricEncoder {
+            writer: self.writer,
+            prefix: self.prefix,
+            name,
+            unit,
+            const_labels: self.labels,
+            family_labels: None,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
         })
     }
 }
@@ -291,13 +745,21 @@ pub(crate) struct MetricEncoder<'a> {
     unit: Option<&'a Unit>,
     const_labels: &'a [(Cow<'static, str>, Cow<'static, str>)],
     family_labels: Option<&'a dyn super::EncodeLabelSet>,
+    emit_created: bool,
+    /// Handling of exemplars exceeding the label set length limit.
+    exemplar_overflow: ExemplarLabelOverflow,
+    /// Whether to encode the Prometheus text format 0.0.4, which does not
+    /// support exemplars.
+    legacy: bool,
+    /// Whether to order series and labels, see [`encode_sorted`].
+    sorted: bool,
 }
 
 impl std::fmt::Debug for MetricEncoder<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut labels = String::new();
         if let Some(l) = self.family_labels {
-            l.encode(LabelSetEncoder::new(&mut labels).into())?;
+            l.encode(LabelSetEncoder::new(&mut labels, &mut true).into())?;
         }
 
         f.debug_struct("Encoder")
@@ -319,6 +781,7 @@ impl MetricEncoder<'_> {
         &mut self,
         v: &CounterValue,
         exemplar: Option<&Exemplar<S, ExemplarValue>>,
+        created: Option<SystemTime>,
     ) -> Result<(), std::fmt::Error> {
         self.write_prefix_name_unit()?;
 
@@ -333,18 +796,38 @@ impl MetricEncoder<'_> {
             .into(),
         )?;
 
-        if let Some(exemplar) = exemplar {
+        if let Some(exemplar) = exemplar.filter(|_| !self.legacy) {
             self.encode_exemplar(exemplar)?;
         }
 
         self.newline()?;
 
-        Ok(())
+        self.encode_created(created)
     }
 
     pub fn encode_gauge<GaugeValue: super::EncodeGaugeValue>(
         &mut self,
         v: &GaugeValue,
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_gauge_with_exemplar::<NoLabelSet, _, f64>(v, None)
+    }
+
+    /// Metrics of unknown type are exposed like gauges.
+    pub fn encode_unknown<GaugeValue: super::EncodeGaugeValue>(
+        &mut self,
+        v: &GaugeValue,
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_gauge(v)
+    }
+
+    pub fn encode_gauge_with_exemplar<
+        S: EncodeLabelSet,
+        GaugeValue: super::EncodeGaugeValue,
+        ExemplarValue: EncodeExemplarValue,
+    >(
+        &mut self,
+        v: &GaugeValue,
+        exemplar: Option<&Exemplar<S, ExemplarValue>>,
     ) -> Result<(), std::fmt::Error> {
         self.write_prefix_name_unit()?;
 
@@ -357,6 +840,10 @@ impl MetricEncoder<'_> {
             .into(),
         )?;
 
+        if let Some(exemplar) = exemplar.filter(|_| !self.legacy) {
+            self.encode_exemplar(exemplar)?;
+        }
+
         self.newline()?;
 
         Ok(())
@@ -377,6 +864,74 @@ impl MetricEncoder<'_> {
         Ok(())
     }
 
+    pub fn encode_stateset<'s>(
+        &mut self,
+        states: impl IntoIterator<Item = (&'s str, bool)>,
+    ) -> Result<(), std::fmt::Error> {
+        // The label name of a state set is the metric family name.
+        let mut label_name = String::new();
+        if let Some(prefix) = self.prefix {
+            label_name.push_str(prefix.as_str());
+            label_name.push(prefix.separator());
+        }
+        label_name.push_str(self.name);
+
+        for (state, enabled) in states {
+            self.write_prefix_name_unit()?;
+
+            self.encode_labels(Some(&[(label_name.as_str(), state)]))?;
+
+            self.writer.write_str(" ")?;
+            self.writer.write_str(if enabled { "1" } else { "0" })?;
+
+            self.newline()?;
+        }
+
+        Ok(())
+    }
+
+    /// Encode the given series, ordered by their label set if enabled via
+    /// [`encode_sorted`].
+    pub(crate) fn encode_series<'s, S, M>(
+        &mut self,
+        series: impl Iterator<Item = (&'s S, &'s M)>,
+    ) -> Result<(), std::fmt::Error>
+    where
+        S: EncodeLabelSet + 's,
+        M: super::EncodeMetric + 's,
+    {
+        if !self.sorted {
+            for (label_set, metric) in series {
+                metric.encode(self.encode_family(label_set)?.into())?;
+            }
+            return Ok(());
+        }
+
+        let mut sorted = series
+            .map(|(label_set, metric)| Ok((sorted_labels(&[label_set])?, label_set, metric)))
+            .collect::<Result<Vec<_>, std::fmt::Error>>()?;
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, label_set, metric) in sorted {
+            metric.encode(self.encode_family(label_set)?.into())?;
+        }
+        Ok(())
+    }
+
+    pub fn by_ref(&mut self) -> MetricEncoder<'_> {
+        MetricEncoder {
+            writer: self.writer,
+            prefix: self.prefix,
+            name: self.name,
+            unit: self.unit,
+            const_labels: self.const_labels,
+            family_labels: self.family_labels,
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
+        }
+    }
+
     /// Encode a set of labels. Used by wrapper metric types like
     /// [`Family`](crate::metrics::family::Family).
     pub fn encode_family<'s, S: EncodeLabelSet>(
@@ -392,48 +947,84 @@ impl MetricEncoder<'_> {
             unit: self.unit,
             const_labels: self.const_labels,
             family_labels: Some(label_set),
+            emit_created: self.emit_created,
+            exemplar_overflow: self.exemplar_overflow,
+            legacy: self.legacy,
+            sorted: self.sorted,
         })
     }
 
-    pub fn encode_histogram<S: EncodeLabelSet>(
+    pub fn encode_histogram<S: EncodeLabelSet, V: EncodeExemplarValue>(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+        exemplars: Option<&HashMap<usize, Exemplar<S, V>>>,
+        created: Option<SystemTime>,
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_buckets("sum", sum, "count", count, buckets, exemplars)?;
+        self.encode_created(created)
+    }
+
+    pub fn encode_gauge_histogram(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+    ) -> Result<(), std::fmt::Error> {
+        // The Prometheus text format 0.0.4 exposes gauge histograms as
+        // histograms, thus requires the histogram sample suffixes.
+        let (sum_suffix, count_suffix) = if self.legacy {
+            ("sum", "count")
+        } else {
+            ("gsum", "gcount")
+        };
+        self.encode_buckets::<NoLabelSet, f64>(sum_suffix, sum, count_suffix, count, buckets, None)
+    }
+
+    /// Encode the sum, count and cumulative buckets shared by histograms and
+    /// gauge histograms.
+    fn encode_buckets<S: EncodeLabelSet, V: EncodeExemplarValue>(
         &mut self,
+        sum_suffix: &'static str,
         sum: f64,
+        count_suffix: &'static str,
         count: u64,
-        buckets: &[(f64, u64)],
-        exemplars: Option<&HashMap<usize, Exemplar<S, f64>>>,
+        buckets: impl Iterator<Item = (f64, u64)>,
+        exemplars: Option<&HashMap<usize, Exemplar<S, V>>>,
     ) -> Result<(), std::fmt::Error> {
         self.write_prefix_name_unit()?;
-        self.write_suffix("sum")?;
+        self.write_suffix(sum_suffix)?;
         self.encode_labels::<NoLabelSet>(None)?;
         self.writer.write_str(" ")?;
         self.writer.write_str(dtoa::Buffer::new().format(sum))?;
         self.newline()?;
 
         self.write_prefix_name_unit()?;
-        self.write_suffix("count")?;
+        self.write_suffix(count_suffix)?;
         self.encode_labels::<NoLabelSet>(None)?;
         self.writer.write_str(" ")?;
         self.writer.write_str(itoa::Buffer::new().format(count))?;
         self.newline()?;
 
         let mut cummulative = 0;
-        for (i, (upper_bound, count)) in buckets.iter().enumerate() {
+        for (i, (upper_bound, count)) in buckets.enumerate() {
             cummulative += count;
 
             self.write_prefix_name_unit()?;
             self.write_suffix("bucket")?;
 
-            if *upper_bound == f64::MAX {
+            if upper_bound == f64::MAX {
                 self.encode_labels(Some(&[("le", "+Inf")]))?;
             } else {
-                self.encode_labels(Some(&[("le", *upper_bound)]))?;
+                self.encode_labels(Some(&[("le", upper_bound)]))?;
             }
 
             self.writer.write_str(" ")?;
             self.writer
                 .write_str(itoa::Buffer::new().format(cummulative))?;
 
-            if let Some(exemplar) = exemplars.and_then(|e| e.get(&i)) {
+            if let Some(exemplar) = exemplars.filter(|_| !self.legacy).and_then(|e| e.get(&i)) {
                 self.encode_exemplar(exemplar)?
             }
 
@@ -443,15 +1034,54 @@ impl MetricEncoder<'_> {
         Ok(())
     }
 
+    /// Encode a `_created` sample, if enabled and the creation time is known.
+    fn encode_created(&mut self, created: Option<SystemTime>) -> Result<(), std::fmt::Error> {
+        // The Prometheus text format 0.0.4 has no `_created` samples.
+        if self.legacy {
+            return Ok(());
+        }
+
+        let since_epoch = match created.filter(|_| self.emit_created) {
+            Some(created) => match created.duration_since(UNIX_EPOCH) {
+                Ok(since_epoch) => since_epoch,
+                Err(_) => return Ok(()),
+            },
+            None => return Ok(()),
+        };
+
+        self.write_prefix_name_unit()?;
+        self.write_suffix("created")?;
+        self.encode_labels::<NoLabelSet>(None)?;
+        self.writer.write_str(" ")?;
+        self.writer
+            .write_str(dtoa::Buffer::new().format(since_epoch.as_secs_f64()))?;
+        self.newline()
+    }
+
     /// Encode an exemplar for the given metric.
     fn encode_exemplar<S: EncodeLabelSet, V: EncodeExemplarValue>(
         &mut self,
         exemplar: &Exemplar<S, V>,
     ) -> Result<(), std::fmt::Error> {
+        let mut label_set_length = 0;
+        exemplar.label_set.encode(
+            LabelSetEncoder::new(&mut Sink, &mut true)
+                .with_length(&mut label_set_length)
+                .into(),
+        )?;
+        if !self.exemplar_overflow.check(label_set_length)? {
+            return Ok(());
+        }
+
         self.writer.write_str(" # {")?;
-        exemplar
-            .label_set
-            .encode(LabelSetEncoder::new(self.writer).into())?;
+        if self.sorted {
+            self.writer
+                .write_str(&sorted_labels(&[&exemplar.label_set])?)?;
+        } else {
+            exemplar
+                .label_set
+                .encode(LabelSetEncoder::new(self.writer, &mut true).into())?;
+        }
         self.writer.write_str("} ")?;
         exemplar.value.encode(
             ExemplarValueEncoder {
@@ -459,7 +1089,12 @@ impl MetricEncoder<'_> {
             }
             .into(),
         )?;
-        Ok(())
+        if let Some(Ok(since_epoch)) = exemplar.timestamp.map(|t| t.duration_since(UNIX_EPOCH)) {
+            self.writer.write_str(" ")?;
+            self.writer
+                .write_str(dtoa::Buffer::new().format(since_epoch.as_secs_f64()))?;
+        }
+        Ok(())
     }
 
     fn newline(&mut self) -> Result<(), std::fmt::Error> {
@@ -468,7 +1103,7 @@ impl MetricEncoder<'_> {
     fn write_prefix_name_unit(&mut self) -> Result<(), std::fmt::Error> {
         if let Some(prefix) = self.prefix {
             self.writer.write_str(prefix.as_str())?;
-            self.writer.write_str("_")?;
+            self.writer.write_char(prefix.separator())?;
         }
         self.writer.write_str(self.name)?;
         if let Some(unit) = self.unit {
@@ -501,15 +1136,23 @@ impl MetricEncoder<'_> {
 
         self.writer.write_str("{")?;
 
+        if self.sorted {
+            let mut label_sets: Vec<&dyn super::EncodeLabelSet> = vec![&self.const_labels];
+            label_sets.extend(additional_labels.map(|l| l as &dyn super::EncodeLabelSet));
+            label_sets.extend(self.family_labels);
+            self.writer.write_str(&sorted_labels(&label_sets)?)?;
+            return self.writer.write_str("}");
+        }
+
         self.const_labels
-            .encode(LabelSetEncoder::new(self.writer).into())?;
+            .encode(LabelSetEncoder::new(self.writer, &mut true).into())?;
 
         if let Some(additional_labels) = additional_labels {
             if !self.const_labels.is_empty() {
                 self.writer.write_str(",")?;
             }
 
-            additional_labels.encode(LabelSetEncoder::new(self.writer).into())?;
+            additional_labels.encode(LabelSetEncoder::new(self.writer, &mut true).into())?;
         }
 
         /// Writer impl which prepends a comma on the first call to write output to the wrapped writer
@@ -539,9 +1182,9 @@ impl MetricEncoder<'_> {
                     writer: self.writer,
                     should_prepend: true,
                 };
-                labels.encode(LabelSetEncoder::new(&mut writer).into())?;
+                labels.encode(LabelSetEncoder::new(&mut writer, &mut true).into())?;
             } else {
-                labels.encode(LabelSetEncoder::new(self.writer).into())?;
+                labels.encode(LabelSetEncoder::new(self.writer, &mut true).into())?;
             };
         }
 
@@ -623,7 +1266,13 @@ impl ExemplarValueEncoder<'_> {
 
 pub(crate) struct LabelSetEncoder<'a> {
     writer: &'a mut dyn Write,
-    first: bool,
+    // Shared with encoders created via `by_ref`, to not prepend a comma to the
+    // first label across all of them.
+    first: &'a mut bool,
+    /// Combined length of the label names and values written, if tracked.
+    length: Option<&'a mut usize>,
+    /// Collects each label separately instead of writing to `writer`, if set.
+    sorted: Option<&'a mut Vec<String>>,
 }
 
 impl std::fmt::Debug for LabelSetEncoder<'_> {
@@ -635,19 +1284,52 @@ impl std::fmt::Debug for LabelSetEncoder<'_> {
 }
 
 impl<'a> LabelSetEncoder<'a> {
-    fn new(writer: &'a mut dyn Write) -> Self {
+    fn new(writer: &'a mut dyn Write, first: &'a mut bool) -> Self {
         Self {
             writer,
-            first: true,
+            first,
+            length: None,
+            sorted: None,
         }
     }
 
-    pub fn encode_label(&mut self) -> LabelEncoder {
-        let first = self.first;
-        self.first = false;
+    /// Track the combined length of the label names and values in `length`.
+    fn with_length(mut self, length: &'a mut usize) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Collect each label into `labels` instead of writing it.
+    fn with_sorted(mut self, labels: &'a mut Vec<String>) -> Self {
+        self.sorted = Some(labels);
+        self
+    }
+
+    pub fn encode_label(&mut self) -> LabelEncoder<'_> {
+        if let Some(labels) = self.sorted.as_deref_mut() {
+            labels.push(String::new());
+            return LabelEncoder {
+                writer: labels.last_mut().expect("label to be pushed"),
+                first: true,
+                length: self.length.as_deref_mut(),
+            };
+        }
+
+        let first = *self.first;
+        *self.first = false;
         LabelEncoder {
             writer: self.writer,
             first,
+            length: self.length.as_deref_mut(),
+        }
+    }
+
+    pub fn by_ref(&mut self) -> LabelSetEncoder<'_> {
+        LabelSetEncoder {
+            writer: self.writer,
+            first: self.first,
+            length: self.length.as_deref_mut(),
+            sorted: self.sorted.as_deref_mut(),
         }
     }
 }
@@ -655,6 +1337,7 @@ impl<'a> LabelSetEncoder<'a> {
 pub(crate) struct LabelEncoder<'a> {
     writer: &'a mut dyn Write,
     first: bool,
+    length: Option<&'a mut usize>,
 }
 
 impl std::fmt::Debug for LabelEncoder<'_> {
@@ -666,18 +1349,20 @@ impl std::fmt::Debug for LabelEncoder<'_> {
 }
 
 impl LabelEncoder<'_> {
-    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder, std::fmt::Error> {
+    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder<'_>, std::fmt::Error> {
         if !self.first {
             self.writer.write_str(",")?;
         }
         Ok(LabelKeyEncoder {
             writer: self.writer,
+            length: self.length.as_deref_mut(),
         })
     }
 }
 
 pub(crate) struct LabelKeyEncoder<'a> {
     writer: &'a mut dyn Write,
+    length: Option<&'a mut usize>,
 }
 
 impl std::fmt::Debug for LabelKeyEncoder<'_> {
@@ -691,18 +1376,23 @@ impl<'a> LabelKeyEncoder<'a> {
         self.writer.write_str("=\"")?;
         Ok(LabelValueEncoder {
             writer: self.writer,
+            length: self.length,
         })
     }
 }
 
 impl std::fmt::Write for LabelKeyEncoder<'_> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if let Some(length) = self.length.as_deref_mut() {
+            *length += s.chars().count();
+        }
         self.writer.write_str(s)
     }
 }
 
 pub(crate) struct LabelValueEncoder<'a> {
     writer: &'a mut dyn Write,
+    length: Option<&'a mut usize>,
 }
 
 impl std::fmt::Debug for LabelValueEncoder<'_> {
@@ -719,6 +1409,21 @@ impl LabelValueEncoder<'_> {
 
 impl std::fmt::Write for LabelValueEncoder<'_> {
     fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if let Some(length) = self.length.as_deref_mut() {
+            *length += s.chars().count();
+        }
+
+        // Escape per the OpenMetrics text format, writing unescaped runs as is.
+        let mut s = s;
+        while let Some(i) = s.find(['"', '\\', '\n']) {
+            self.writer.write_str(&s[..i])?;
+            self.writer.write_str(match s.as_bytes()[i] {
+                b'"' => "\\\"",
+                b'\\' => "\\\\",
+                _ => "\\n",
+            })?;
+            s = &s[i + 1..];
+        }
         self.writer.write_str(s)
     }
 }
@@ -729,13 +1434,16 @@ mod tests {
     use crate::metrics::exemplar::HistogramWithExemplars;
     use crate::metrics::family::Family;
     use crate::metrics::gauge::Gauge;
+    use crate::metrics::gauge_histogram::GaugeHistogram;
     use crate::metrics::histogram::{exponential_buckets, Histogram};
     use crate::metrics::info::Info;
+    use crate::metrics::stateset::StateSet;
     use crate::metrics::{counter::Counter, exemplar::CounterWithExemplar};
     use pyo3::{prelude::*, types::PyModule};
     use std::borrow::Cow;
+    use std::collections::BTreeMap;
     use std::fmt::Error;
-    use std::sync::atomic::{AtomicI32, AtomicU32};
+    use std::sync::atomic::{AtomicI32, AtomicU32, AtomicU64};
 
     #[test]
     fn encode_counter() {
@@ -754,6 +1462,126 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_escaped_help() {
+        let mut registry = Registry::default();
+        registry.register("multi_line", "line1\nline2", Counter::<u64>::default());
+        registry.register(
+            "special_chars",
+            "C:\\ \"quoted\"\r\nend",
+            Counter::<u64>::default(),
+        );
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP multi_line line1\\nline2.\n".to_owned()
+            + "# TYPE multi_line counter\n"
+            + "multi_line_total 0\n"
+            + "# HELP special_chars C:\\\\ \\\"quoted\\\"\\nend.\n"
+            + "# TYPE special_chars counter\n"
+            + "special_chars_total 0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        parse_with_python_client(encoded);
+    }
+
+    #[test]
+    fn encode_escaped_label_values() {
+        let mut registry = Registry::default();
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+        registry.register("requests", "Requests", family.clone());
+        family
+            .get_or_create(&vec![
+                ("path".to_string(), "/a\"b".to_string()),
+                ("dir".to_string(), "C:\\tmp".to_string()),
+                ("text".to_string(), "line1\nline2".to_string()),
+            ])
+            .inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total{path=\"/a\\\"b\",dir=\"C:\\\\tmp\",text=\"line1\\nline2\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        parse_with_python_client(encoded);
+    }
+
+    #[test]
+    fn encode_map_label_sets() {
+        let mut registry = Registry::default();
+        let family = Family::<BTreeMap<String, String>, Counter>::default();
+        registry.register("requests", "Requests", family.clone());
+        family
+            .get_or_create(&BTreeMap::from([
+                ("path".to_string(), "/".to_string()),
+                ("method".to_string(), "GET".to_string()),
+            ]))
+            .inc();
+        let errors = CounterWithExemplar::<HashMap<String, String>>::default();
+        registry.register("errors", "Errors", errors.clone());
+        errors.inc_by(
+            1,
+            Some(HashMap::from([("trace_id".to_string(), "42".to_string())])),
+        );
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total{method=\"GET\",path=\"/\"} 1\n"
+            + "# HELP errors Errors.\n"
+            + "# TYPE errors counter\n"
+            + "errors_total 1 # {trace_id=\"42\"} 1.0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_sorted_series_and_labels() {
+        let mut registry =
+            Registry::with_labels(std::iter::once((Cow::Borrowed("zone"), Cow::Borrowed("a"))));
+        let requests = Family::<Vec<(&str, &str)>, Counter>::default();
+        registry.register("requests", "Requests", requests.clone());
+        requests.init(["/c", "/a", "/b"].map(|path| vec![("path", path), ("method", "GET")]));
+        let latency =
+            Family::<Vec<(&str, &str)>, Histogram>::new_with_constructor(|| Histogram::new([1.0]));
+        registry.register("latency", "Latency", latency.clone());
+        latency.get_or_create(&vec![("path", "/")]).observe(0.5);
+        let errors = CounterWithExemplar::<HashMap<&str, &str>>::default();
+        registry.register("errors", "Errors", errors.clone());
+        errors.inc_by(
+            1,
+            Some(HashMap::from([("trace_id", "1"), ("span_id", "2")])),
+        );
+
+        let mut encoded = String::new();
+        encode_sorted(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total{method=\"GET\",path=\"/a\",zone=\"a\"} 0\n"
+            + "requests_total{method=\"GET\",path=\"/b\",zone=\"a\"} 0\n"
+            + "requests_total{method=\"GET\",path=\"/c\",zone=\"a\"} 0\n"
+            + "# HELP latency Latency.\n"
+            + "# TYPE latency histogram\n"
+            + "latency_sum{path=\"/\",zone=\"a\"} 0.5\n"
+            + "latency_count{path=\"/\",zone=\"a\"} 1\n"
+            + "latency_bucket{le=\"1.0\",path=\"/\",zone=\"a\"} 1\n"
+            + "latency_bucket{le=\"+Inf\",path=\"/\",zone=\"a\"} 1\n"
+            + "# HELP errors Errors.\n"
+            + "# TYPE errors counter\n"
+            + "errors_total{zone=\"a\"} 1 # {span_id=\"2\",trace_id=\"1\"} 1.0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
     #[test]
     fn encode_counter_with_unit() {
         let mut registry = Registry::default();
@@ -773,6 +1601,36 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_created() {
+        let mut registry = Registry::default();
+        registry.register("my_counter", "My counter", Counter::<u64>::default());
+        registry.register(
+            "my_histogram",
+            "My histogram",
+            Histogram::new(exponential_buckets(1.0, 2.0, 2)),
+        );
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        assert!(!encoded.contains("_created"));
+
+        registry.set_emit_created(true);
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        for name in ["my_counter_created", "my_histogram_created"] {
+            let line = encoded
+                .lines()
+                .find(|l| l.starts_with(name))
+                .unwrap_or_else(|| panic!("missing {name} in {encoded}"));
+            let value: f64 = line.split(' ').nth(1).unwrap().parse().unwrap();
+            assert!(value > 0.0);
+        }
+
+        parse_with_python_client(encoded);
+    }
+
     #[test]
     fn encode_counter_with_exemplar() {
         let mut registry = Registry::default();
@@ -802,6 +1660,29 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_unknown() {
+        use crate::metrics::unknown::Unknown;
+
+        let mut registry = Registry::default();
+        let unknown: Unknown = Unknown::default();
+        registry.register("my_unknown", "My unknown", unknown.clone());
+        unknown.set(1.5);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP my_unknown My unknown.\n".to_owned()
+            + "# TYPE my_unknown unknown\n"
+            + "my_unknown 1.5\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        let mut encoded = String::new();
+        crate::encoding::prometheus_text::encode(&mut encoded, &registry).unwrap();
+        assert!(encoded.contains("# TYPE my_unknown untyped\nmy_unknown 1.5\n"));
+    }
+
     #[test]
     fn encode_gauge() {
         let mut registry = Registry::default();
@@ -823,6 +1704,23 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_gauge_u64_max() {
+        let mut registry = Registry::default();
+        let gauge = Gauge::<u64, AtomicU64>::default();
+        registry.register("my_gauge", "My gauge", gauge.clone());
+        gauge.set(u64::MAX);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP my_gauge My gauge.\n".to_owned()
+            + "# TYPE my_gauge gauge\n"
+            + "my_gauge 18446744073709553000.0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
     #[test]
     fn encode_counter_family() {
         let mut registry = Registry::default();
@@ -891,6 +1789,63 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_gauge_histogram() {
+        let mut registry = Registry::default();
+        let histogram = GaugeHistogram::new([1.0, 10.0]);
+        registry.register("queue_items", "Items in queue", histogram.clone());
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(5.0);
+        histogram.dec(0.5);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP queue_items Items in queue.\n".to_owned()
+            + "# TYPE queue_items gaugehistogram\n"
+            + "queue_items_gsum 10.0\n"
+            + "queue_items_gcount 2\n"
+            + "queue_items_bucket{le=\"1.0\"} 0\n"
+            + "queue_items_bucket{le=\"10.0\"} 2\n"
+            + "queue_items_bucket{le=\"+Inf\"} 2\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_stateset() {
+        #[derive(Clone, Copy, Debug)]
+        enum Mode {
+            Primary,
+            Standby,
+        }
+
+        impl crate::encoding::EncodeStateSet for Mode {
+            const STATES: &'static [&'static str] = &["primary", "standby"];
+
+            fn index(&self) -> usize {
+                *self as usize
+            }
+        }
+
+        let mut registry = Registry::default();
+        let sub_registry = registry.sub_registry_with_prefix("db");
+        let mode = StateSet::new(Mode::Primary);
+        sub_registry.register("mode", "Replication mode", mode.clone());
+        mode.set(Mode::Standby);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP db_mode Replication mode.\n".to_owned()
+            + "# TYPE db_mode stateset\n"
+            + "db_mode{db_mode=\"primary\"} 0\n"
+            + "db_mode{db_mode=\"standby\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
     #[test]
     fn encode_histogram() {
         let mut registry = Registry::default();
@@ -981,6 +1936,123 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_exemplar_timestamp() {
+        let mut registry = Registry::default();
+        let counter: CounterWithExemplar<[(&str, &str); 1]> = Default::default();
+        registry.register("my_counter", "My counter", counter.clone());
+        counter.inc_by_with_timestamp(
+            1,
+            Some([("trace_id", "42")]),
+            Some(UNIX_EPOCH + std::time::Duration::from_millis(1_500)),
+        );
+
+        let histogram = HistogramWithExemplars::new([1.0].into_iter());
+        registry.register("my_histogram", "My histogram", histogram.clone());
+        histogram.observe_with_timestamp(
+            0.5,
+            Some([("trace_id", "43")]),
+            Some(UNIX_EPOCH + std::time::Duration::from_secs(2)),
+        );
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP my_counter My counter.\n".to_owned()
+            + "# TYPE my_counter counter\n"
+            + "my_counter_total 1 # {trace_id=\"42\"} 1.0 1.5\n"
+            + "# HELP my_histogram My histogram.\n"
+            + "# TYPE my_histogram histogram\n"
+            + "my_histogram_sum 0.5\n"
+            + "my_histogram_count 1\n"
+            + "my_histogram_bucket{le=\"1.0\"} 1 # {trace_id=\"43\"} 0.5 2.0\n"
+            + "my_histogram_bucket{le=\"+Inf\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        parse_with_python_client(encoded);
+    }
+
+    #[test]
+    fn encode_exemplar_label_set_length_limit() {
+        use crate::metrics::exemplar::ExemplarLabelOverflow;
+
+        let mut registry = Registry::default();
+        let counter: CounterWithExemplar<Vec<(String, String)>> = Default::default();
+        registry.register("my_counter", "My counter", counter.clone());
+
+        // 8 characters of label name and 120 of label value are within the
+        // limit.
+        counter.inc_by(1, Some(vec![("trace_id".to_string(), "a".repeat(120))]));
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        assert!(encoded.contains(&format!("# {{trace_id=\"{}\"}} 1", "a".repeat(120))));
+
+        // One more character exceeds the limit, thus the exemplar is omitted.
+        counter.inc_by(1, Some(vec![("trace_id".to_string(), "a".repeat(121))]));
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        let expected = "# HELP my_counter My counter.\n".to_owned()
+            + "# TYPE my_counter counter\n"
+            + "my_counter_total 2\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        registry.set_exemplar_overflow(ExemplarLabelOverflow::Error);
+        let mut encoded = String::new();
+        assert!(encode(&mut encoded, &registry).is_err());
+    }
+
+    #[test]
+    fn encode_gauge_with_exemplar() {
+        use crate::metrics::exemplar::GaugeWithExemplar;
+
+        let mut registry = Registry::default();
+        let gauge: GaugeWithExemplar<[(&str, &str); 1]> = Default::default();
+        registry.register("in_flight", "In-flight requests", gauge.clone());
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        let expected = "# HELP in_flight In-flight requests.\n".to_owned()
+            + "# TYPE in_flight gauge\n"
+            + "in_flight 0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        gauge.set(3, Some([("trace_id", "42")]));
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        let expected = "# HELP in_flight In-flight requests.\n".to_owned()
+            + "# TYPE in_flight gauge\n"
+            + "in_flight 3 # {trace_id=\"42\"} 3.0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_histogram_with_exemplar_value() {
+        let mut registry = Registry::default();
+        let histogram =
+            HistogramWithExemplars::<[(&str, &str); 1], u64>::new([1.0, 2.0].into_iter());
+        registry.register("my_histogram", "My histogram", histogram.clone());
+        histogram.observe_with_exemplar(1.5, Some(([("sample", "a")], 7)));
+        histogram.observe_with_exemplar(0.5, None);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP my_histogram My histogram.\n".to_owned()
+            + "# TYPE my_histogram histogram\n"
+            + "my_histogram_sum 2.0\n"
+            + "my_histogram_count 2\n"
+            + "my_histogram_bucket{le=\"1.0\"} 1\n"
+            + "my_histogram_bucket{le=\"2.0\"} 2 # {sample=\"a\"} 7.0\n"
+            + "my_histogram_bucket{le=\"+Inf\"} 2\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
     #[test]
     fn sub_registry_with_prefix_and_label() {
         let top_level_metric_name = "my_top_level_metric";
@@ -1055,6 +2127,98 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[test]
+    fn encode_const_metrics_with_labels() {
+        use crate::encoding::EncodeMetric;
+        use crate::metrics::counter::ConstCounter;
+        use crate::metrics::gauge::ConstGauge;
+        use crate::metrics::TypedMetric;
+
+        #[derive(Debug)]
+        struct Collector {}
+
+        impl crate::collector::Collector for Collector {
+            fn encode(
+                &self,
+                mut encoder: crate::encoding::DescriptorEncoder,
+            ) -> Result<(), std::fmt::Error> {
+                let mut metric_encoder = encoder.encode_descriptor(
+                    "disk_free_bytes",
+                    "Free disk space",
+                    None,
+                    ConstGauge::<i64>::TYPE,
+                )?;
+                for (device, free) in [("sda", 42i64), ("sdb", 7)] {
+                    ConstGauge::with_labels(free, [("device", device)])
+                        .encode(metric_encoder.by_ref())?;
+                }
+
+                let metric_encoder = encoder.encode_descriptor(
+                    "disk_reads",
+                    "Disk reads",
+                    None,
+                    ConstCounter::<u64>::TYPE,
+                )?;
+                ConstCounter::with_labels(3u64, [("device", "sda")]).encode(metric_encoder)?;
+                Ok(())
+            }
+        }
+
+        let mut registry =
+            Registry::with_labels([(Cow::Borrowed("host"), Cow::Borrowed("a"))].into_iter());
+        registry.register_collector(Box::new(Collector {}));
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP disk_free_bytes Free disk space\n".to_owned()
+            + "# TYPE disk_free_bytes gauge\n"
+            + "disk_free_bytes{host=\"a\",device=\"sda\"} 42\n"
+            + "disk_free_bytes{host=\"a\",device=\"sdb\"} 7\n"
+            + "# HELP disk_reads Disk reads\n"
+            + "# TYPE disk_reads counter\n"
+            + "disk_reads_total{host=\"a\",device=\"sda\"} 3\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn collector_with_registry_labels() {
+        use crate::encoding::EncodeMetric;
+        use crate::metrics::counter::ConstCounter;
+
+        fn collector() -> impl crate::collector::Collector {
+            crate::collector::from_fn(|mut encoder| {
+                let counter = ConstCounter::new(1u64);
+                let metric_encoder =
+                    encoder.encode_descriptor("events", "Events", None, counter.metric_type())?;
+                counter.encode(metric_encoder)
+            })
+        }
+
+        let mut registry = Registry::with_prefix_and_labels(
+            "app",
+            [(Cow::Borrowed("host"), Cow::Borrowed("a"))].into_iter(),
+        );
+        registry.register_collector(Box::new(collector()));
+        registry
+            .sub_registry_with_label((Cow::Borrowed("shard"), Cow::Borrowed("1")))
+            .sub_registry_with_prefix("db")
+            .register_collector(Box::new(collector()));
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP app_events Events\n".to_owned()
+            + "# TYPE app_events counter\n"
+            + "app_events_total{host=\"a\"} 1\n"
+            + "# HELP app_db_events Events\n"
+            + "# TYPE app_db_events counter\n"
+            + "app_db_events_total{host=\"a\",shard=\"1\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
     #[test]
     fn sub_registry_collector() {
         use crate::encoding::EncodeMetric;
@@ -1114,6 +2278,117 @@ mod tests {
         parse_with_python_client(encoded);
     }
 
+    #[cfg(feature = "async")]
+    #[test]
+    fn encode_async_collector() {
+        use crate::collector::{from_fn, AsyncCollector, Collector};
+        use crate::encoding::EncodeMetric;
+        use crate::metrics::gauge::ConstGauge;
+        use std::future::Future;
+        use std::pin::Pin;
+
+        #[derive(Debug)]
+        struct Connections {}
+
+        impl Connections {
+            async fn query(&self) -> i64 {
+                42
+            }
+        }
+
+        impl AsyncCollector for Connections {
+            fn collect(&self) -> Pin<Box<dyn Future<Output = Box<dyn Collector>> + Send + '_>> {
+                Box::pin(async move {
+                    let connections = self.query().await;
+                    Box::new(from_fn(move |mut encoder| {
+                        let gauge = ConstGauge::new(connections);
+                        let metric_encoder = encoder.encode_descriptor(
+                            "connections",
+                            "Open connections",
+                            None,
+                            gauge.metric_type(),
+                        )?;
+                        gauge.encode(metric_encoder)
+                    })) as Box<dyn Collector>
+                })
+            }
+        }
+
+        fn assert_send<T: Send>(t: T) -> T {
+            t
+        }
+
+        let mut registry = Registry::default();
+        let counter: Counter = Counter::default();
+        registry.register("requests", "Requests", counter.clone());
+        counter.inc();
+        registry
+            .sub_registry_with_prefix("db")
+            .register_async_collector(Box::new(Connections {}));
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        let mut encoded = String::new();
+        futures::executor::block_on(assert_send(encode_async(&mut encoded, &registry))).unwrap();
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total 1\n"
+            + "# HELP db_connections Open connections\n"
+            + "# TYPE db_connections gauge\n"
+            + "db_connections 42\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_to_vec_matches_string() {
+        let mut registry = Registry::default();
+        let counter: Counter = Counter::default();
+        counter.inc();
+        registry.register("my_counter", "Zähler über alles", counter);
+
+        let string = encode_to_string(&registry).unwrap();
+        assert_eq!(string.as_bytes(), encode_to_vec(&registry).unwrap());
+    }
+
+    #[test]
+    fn encode_to_io_writer() {
+        let mut registry = Registry::default();
+        let family = Family::<[(&str, u64); 1], Counter>::default();
+        registry.register("my_counter", "My counter", family.clone());
+        for i in 0..1000 {
+            family.get_or_create(&[("id", i)]).inc();
+        }
+
+        let mut expected = String::new();
+        encode(&mut expected, &registry).unwrap();
+
+        let mut encoded = Vec::new();
+        encode_to_io(&mut encoded, &registry).unwrap();
+        assert_eq!(expected.as_bytes(), encoded.as_slice());
+
+        struct Failing;
+
+        impl std::io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let err = encode_to_io(Failing, &registry).unwrap_err();
+        assert_eq!(std::io::ErrorKind::BrokenPipe, err.kind());
+    }
+
     #[test]
     fn encode_registry_eof() {
         let mut orders_registry = Registry::default();
@@ -1160,6 +2435,269 @@ mod tests {
         assert_eq!(&response[response.len() - 20..], "ogins_total 0\n# EOF\n");
     }
 
+    #[test]
+    fn encode_wrapping_and_atomic_label_values() {
+        use std::num::Wrapping;
+
+        let mut registry = Registry::default();
+        let family = Family::<Vec<(String, Wrapping<u32>)>, Counter>::default();
+        registry.register("my_counter", "My counter", family.clone());
+        family
+            .get_or_create(&vec![(
+                "generation".to_string(),
+                Wrapping(u32::MAX) + Wrapping(2),
+            )])
+            .inc();
+
+        static GENERATION: AtomicU32 = AtomicU32::new(7);
+        let counter_with_exemplar = CounterWithExemplar::<Vec<(&str, &AtomicU32)>>::default();
+        registry.register(
+            "my_other_counter",
+            "My other counter",
+            counter_with_exemplar.clone(),
+        );
+        counter_with_exemplar.inc_by(1, Some(vec![("generation", &GENERATION)]));
+        GENERATION.store(8, std::sync::atomic::Ordering::Relaxed);
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP my_counter My counter.\n".to_owned()
+            + "# TYPE my_counter counter\n"
+            + "my_counter_total{generation=\"1\"} 1\n"
+            + "# HELP my_other_counter My other counter.\n"
+            + "# TYPE my_other_counter counter\n"
+            + "my_other_counter_total 1 # {generation=\"8\"} 1.0\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_radix_label_values() {
+        use crate::encoding::{Binary, Hex, Octal};
+
+        let mut registry = Registry::default();
+
+        let hex = Family::<[(&str, Hex<u32>); 1], Counter>::default();
+        registry.register("hex", "Hex", hex.clone());
+        hex.get_or_create(&[("flags", Hex(0xDEAD))]).inc();
+
+        let octal = Family::<[(&str, Octal<u32>); 1], Counter>::default();
+        registry.register("octal", "Octal", octal.clone());
+        octal.get_or_create(&[("mode", Octal(0o644))]).inc();
+
+        let binary = Family::<[(&str, Binary<u8>); 1], Counter>::default();
+        registry.register("binary", "Binary", binary.clone());
+        binary.get_or_create(&[("bits", Binary(0b101))]).inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP hex Hex.\n".to_owned()
+            + "# TYPE hex counter\n"
+            + "hex_total{flags=\"dead\"} 1\n"
+            + "# HELP octal Octal.\n"
+            + "# TYPE octal counter\n"
+            + "octal_total{mode=\"644\"} 1\n"
+            + "# HELP binary Binary.\n"
+            + "# TYPE binary counter\n"
+            + "binary_total{bits=\"101\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_std_label_values() {
+        use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+        use std::num::NonZeroU64;
+        use std::time::Duration;
+
+        let mut registry = Registry::default();
+
+        let ip = Family::<[(&str, IpAddr); 1], Counter>::default();
+        registry.register("ip", "IP", ip.clone());
+        ip.get_or_create(&[("ip", IpAddr::V6(Ipv6Addr::LOCALHOST))])
+            .inc();
+
+        let socket = Family::<[(&str, SocketAddr); 1], Counter>::default();
+        registry.register("socket", "Socket", socket.clone());
+        socket
+            .get_or_create(&[("addr", "10.0.0.1:8080".parse().unwrap())])
+            .inc();
+
+        let duration = Family::<[(&str, Duration); 1], Counter>::default();
+        registry.register("duration", "Duration", duration.clone());
+        duration
+            .get_or_create(&[("timeout", Duration::from_millis(1500))])
+            .inc();
+
+        let non_zero = Family::<[(&str, NonZeroU64); 1], Counter>::default();
+        registry.register("non_zero", "Non zero", non_zero.clone());
+        non_zero
+            .get_or_create(&[("shard", NonZeroU64::new(3).unwrap())])
+            .inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP ip IP.\n".to_owned()
+            + "# TYPE ip counter\n"
+            + "ip_total{ip=\"::1\"} 1\n"
+            + "# HELP socket Socket.\n"
+            + "# TYPE socket counter\n"
+            + "socket_total{addr=\"10.0.0.1:8080\"} 1\n"
+            + "# HELP duration Duration.\n"
+            + "# TYPE duration counter\n"
+            + "duration_total{timeout=\"1.5\"} 1\n"
+            + "# HELP non_zero Non zero.\n"
+            + "# TYPE non_zero counter\n"
+            + "non_zero_total{shard=\"3\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_char_label_value() {
+        let mut registry = Registry::default();
+        let family = Family::<[(&str, char); 1], Counter>::default();
+        registry.register("grade", "Grade", family.clone());
+        family.get_or_create(&[("grade", 'A')]).inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP grade Grade.\n".to_owned()
+            + "# TYPE grade counter\n"
+            + "grade_total{grade=\"A\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[cfg(feature = "hex")]
+    #[test]
+    fn encode_hex_label_value() {
+        let mut registry = Registry::default();
+        let family = Family::<[(&str, [u8; 4]); 1], Counter>::default();
+        registry.register("trace", "Trace", family.clone());
+        family
+            .get_or_create(&[("prefix", [0xde, 0xad, 0x0b, 0xef])])
+            .inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP trace Trace.\n".to_owned()
+            + "# TYPE trace counter\n"
+            + "trace_total{prefix=\"dead0bef\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn encode_uuid_label_value() {
+        let mut registry = Registry::default();
+        let family = Family::<[(&str, uuid::Uuid); 1], Counter>::default();
+        registry.register("requests", "Requests", family.clone());
+        family
+            .get_or_create(&[(
+                "tenant",
+                uuid::Uuid::from_u128(0x67e55044_10b1_426f_9247_bb680e5fe0c8),
+            )])
+            .inc();
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+
+        let expected = "# HELP requests Requests.\n".to_owned()
+            + "# TYPE requests counter\n"
+            + "requests_total{tenant=\"67e55044-10b1-426f-9247-bb680e5fe0c8\"} 1\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+    }
+
+    #[test]
+    fn encode_filtered() {
+        use crate::encoding::EncodeMetric;
+
+        #[derive(Debug)]
+        struct Collector;
+
+        impl crate::collector::Collector for Collector {
+            fn encode(
+                &self,
+                mut encoder: crate::encoding::DescriptorEncoder,
+            ) -> Result<(), std::fmt::Error> {
+                for name in ["collected_a", "collected_b"] {
+                    let counter = crate::metrics::counter::ConstCounter::new(42u64);
+                    let metric_encoder = encoder.encode_descriptor(
+                        name,
+                        "some help",
+                        None,
+                        counter.metric_type(),
+                    )?;
+                    counter.encode(metric_encoder)?;
+                }
+                Ok(())
+            }
+        }
+
+        let mut registry = Registry::default();
+        let counter: Counter = Counter::default();
+        registry.register("top_level", "some help", counter.clone());
+
+        let sub_registry = registry.sub_registry_with_prefix("db");
+        sub_registry.register_with_unit("latency", "some help", Unit::Seconds, counter.clone());
+        sub_registry.register("queries", "some help", counter);
+        sub_registry.register_collector(Box::new(Collector));
+
+        let mut encoded = String::new();
+        registry
+            .encode_filtered(&mut encoded, |name| {
+                name == "db_latency_seconds" || name == "db_collected_b"
+            })
+            .unwrap();
+
+        let expected = "# HELP db_latency_seconds some help.\n".to_owned()
+            + "# TYPE db_latency_seconds counter\n"
+            + "# UNIT db_latency_seconds seconds\n"
+            + "db_latency_seconds_total 0\n"
+            + "# HELP db_collected_b some help\n"
+            + "# TYPE db_collected_b counter\n"
+            + "db_collected_b_total 42\n"
+            + "# EOF\n";
+        assert_eq!(expected, encoded);
+
+        encoded.clear();
+        registry.encode_filtered(&mut encoded, |_| false).unwrap();
+        assert_eq!("# EOF\n", encoded);
+    }
+
+    #[test]
+    fn encode_filtered_skips_excluded_metrics() {
+        use crate::encoding::{EncodeMetric, MetricEncoder};
+
+        #[derive(Debug)]
+        struct Unencodable;
+
+        impl EncodeMetric for Unencodable {
+            fn encode(&self, _encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+                panic!("excluded metric encoded");
+            }
+
+            fn metric_type(&self) -> MetricType {
+                MetricType::Counter
+            }
+        }
+
+        let mut registry = Registry::default();
+        registry.register("excluded", "some help", Unencodable);
+
+        let mut encoded = String::new();
+        registry.encode_filtered(&mut encoded, |_| false).unwrap();
+        assert_eq!("# EOF\n", encoded);
+    }
+
     fn parse_with_python_client(input: String) {
         pyo3::prepare_freethreaded_python();
 
@@ -0,0 +1,575 @@
+//! Access to the current values of all metrics of a [`Registry`](crate::registry::Registry) through a
+//! [`MetricVisitor`], independent of any exposition format.
+//!
+//! Useful to feed metric values into non-Prometheus sinks, e.g. StatsD or
+//! OTLP, without implementing [`EncodeMetric`](super::EncodeMetric) anew.
+//!
+//! ```
+//! # use prometheus_client::encoding::visitor::{CounterValue, GaugeValue, MetricVisitor};
+//! # use prometheus_client::metrics::counter::Counter;
+//! # use prometheus_client::metrics::family::Family;
+//! # use prometheus_client::registry::Registry;
+//! #
+//! #[derive(Default)]
+//! struct Lines(Vec<String>);
+//!
+//! impl MetricVisitor for Lines {
+//!     fn visit_counter(&mut self, name: &str, labels: &[(String, String)], value: CounterValue) {
+//!         self.0.push(format!("{name} {labels:?} {value:?}"));
+//!     }
+//!
+//!     fn visit_gauge(&mut self, _: &str, _: &[(String, String)], _: GaugeValue) {}
+//!
+//!     fn visit_histogram(&mut self, _: &str, _: &[(String, String)], _: f64, _: u64, _: &[(f64, u64)]) {}
+//! }
+//!
+//! let mut registry = Registry::default();
+//! let requests = Family::<Vec<(String, String)>, Counter>::default();
+//! registry.register("requests", "Requests", requests.clone());
+//! requests.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+//!
+//! let mut lines = Lines::default();
+//! registry.visit(&mut lines).unwrap();
+//! assert_eq!(vec![r#"requests [("method", "GET")] Int(1)"#], lines.0);
+//! ```
+
+use std::{borrow::Cow, collections::HashMap, time::SystemTime};
+
+use crate::metrics::exemplar::Exemplar;
+use crate::metrics::MetricType;
+use crate::registry::{Prefix, Unit};
+
+use super::{EncodeCounterValue, EncodeExemplarValue, EncodeGaugeValue, EncodeLabelSet};
+
+/// Visitor of the current values of all metrics of a [`Registry`](crate::registry::Registry), see
+/// [`Registry::visit`](crate::registry::Registry::visit).
+///
+/// Each callback receives the name of the metric, including the prefix and
+/// unit suffix, as returned by
+/// [`Registry::metric_names`](crate::registry::Registry::metric_names), and the
+/// labels of the series, including the labels of the
+/// [`Registry`](crate::registry::Registry).
+///
+/// Exemplars are not visited.
+pub trait MetricVisitor {
+    /// Visit the descriptor of a metric, called before visiting the series of
+    /// the metric.
+    ///
+    /// Ignores descriptors by default.
+    fn visit_descriptor(
+        &mut self,
+        name: &str,
+        help: &str,
+        unit: Option<&Unit>,
+        metric_type: MetricType,
+    ) {
+        let _ = (name, help, unit, metric_type);
+    }
+
+    /// Visit a counter.
+    fn visit_counter(&mut self, name: &str, labels: &[(String, String)], value: CounterValue);
+
+    /// Visit a gauge.
+    fn visit_gauge(&mut self, name: &str, labels: &[(String, String)], value: GaugeValue);
+
+    /// Visit a histogram, given the upper bound and cumulative count of each
+    /// bucket, including the final `+Inf` bucket, represented by [`f64::MAX`].
+    fn visit_histogram(
+        &mut self,
+        name: &str,
+        labels: &[(String, String)],
+        sum: f64,
+        count: u64,
+        buckets: &[(f64, u64)],
+    );
+
+    /// Visit a gauge histogram, see [`MetricVisitor::visit_histogram`].
+    ///
+    /// Ignores gauge histograms by default.
+    fn visit_gauge_histogram(
+        &mut self,
+        name: &str,
+        labels: &[(String, String)],
+        sum: f64,
+        count: u64,
+        buckets: &[(f64, u64)],
+    ) {
+        let _ = (name, labels, sum, count, buckets);
+    }
+
+    /// Visit an info metric, given its info labels.
+    ///
+    /// Ignores info metrics by default.
+    fn visit_info(&mut self, name: &str, labels: &[(String, String)], info: &[(String, String)]) {
+        let _ = (name, labels, info);
+    }
+
+    /// Visit a state set, given the name of each state and whether it is
+    /// enabled.
+    ///
+    /// Ignores state sets by default.
+    fn visit_stateset(&mut self, name: &str, labels: &[(String, String)], states: &[(&str, bool)]) {
+        let _ = (name, labels, states);
+    }
+
+    /// Visit a metric of unknown type.
+    ///
+    /// Visits it as a gauge via [`MetricVisitor::visit_gauge`] by default.
+    fn visit_unknown(&mut self, name: &str, labels: &[(String, String)], value: GaugeValue) {
+        self.visit_gauge(name, labels, value);
+    }
+
+    /// Visit the creation time of the series visited last via
+    /// [`MetricVisitor::visit_counter`] or [`MetricVisitor::visit_histogram`],
+    /// if the metric tracks it, e.g.
+    /// [`Counter::created`](crate::metrics::counter::Counter::created).
+    ///
+    /// Ignores creation times by default.
+    fn visit_created(&mut self, name: &str, labels: &[(String, String)], created: SystemTime) {
+        let _ = (name, labels, created);
+    }
+}
+
+/// Value of a counter passed to [`MetricVisitor::visit_counter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CounterValue {
+    /// Integer counter value.
+    Int(u64),
+    /// Floating point counter value.
+    Double(f64),
+}
+
+/// Value of a gauge passed to [`MetricVisitor::visit_gauge`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GaugeValue {
+    /// Integer gauge value.
+    Int(i64),
+    /// Floating point gauge value.
+    Double(f64),
+}
+
+/// Metric Descriptor encoder passing metric values to a [`MetricVisitor`].
+///
+/// This is an inner type for [`super::DescriptorEncoder`].
+pub(crate) struct DescriptorEncoder<'a> {
+    visitor: &'a mut dyn MetricVisitor,
+    prefix: Option<&'a Prefix>,
+    labels: &'a [(Cow<'static, str>, Cow<'static, str>)],
+}
+
+impl std::fmt::Debug for DescriptorEncoder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DescriptorEncoder")
+            .field("prefix", &self.prefix)
+            .field("labels", &self.labels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DescriptorEncoder<'_> {
+    pub(crate) fn new(visitor: &mut dyn MetricVisitor) -> DescriptorEncoder<'_> {
+        DescriptorEncoder {
+            visitor,
+            prefix: Default::default(),
+            labels: Default::default(),
+        }
+    }
+
+    pub(crate) fn with_prefix_and_labels<'s>(
+        &'s mut self,
+        prefix: Option<&'s Prefix>,
+        labels: &'s [(Cow<'static, str>, Cow<'static, str>)],
+    ) -> DescriptorEncoder<'s> {
+        DescriptorEncoder {
+            visitor: self.visitor,
+            prefix,
+            labels,
+        }
+    }
+
+    pub fn encode_descriptor<'s>(
+        &'s mut self,
+        name: &str,
+        help: &str,
+        unit: Option<&Unit>,
+        metric_type: MetricType,
+    ) -> Result<MetricEncoder<'s>, std::fmt::Error> {
+        let mut full_name = String::new();
+        if let Some(prefix) = self.prefix {
+            full_name.push_str(prefix.as_str());
+            full_name.push(prefix.separator());
+        }
+        full_name.push_str(name);
+        if let Some(unit) = unit {
+            full_name.push('_');
+            full_name.push_str(unit.as_str());
+        }
+
+        self.visitor
+            .visit_descriptor(&full_name, help, unit, metric_type);
+
+        let mut labels = vec![];
+        self.labels.encode(
+            LabelSetEncoder {
+                labels: &mut labels,
+            }
+            .into(),
+        )?;
+
+        Ok(MetricEncoder {
+            visitor: self.visitor,
+            name: full_name,
+            metric_type,
+            labels,
+        })
+    }
+}
+
+/// Encoder passing metric values to a [`MetricVisitor`].
+///
+/// This is an inner type for [`super::MetricEncoder`].
+pub(crate) struct MetricEncoder<'a> {
+    visitor: &'a mut dyn MetricVisitor,
+    /// Name of the metric, including prefix and unit.
+    name: String,
+    /// OpenMetrics metric type of the metric.
+    metric_type: MetricType,
+    /// Labels of the series.
+    labels: Vec<(String, String)>,
+}
+
+impl std::fmt::Debug for MetricEncoder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MetricEncoder")
+            .field("name", &self.name)
+            .field("metric_type", &self.metric_type)
+            .field("labels", &self.labels)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MetricEncoder<'_> {
+    pub fn encode_counter<
+        S: EncodeLabelSet,
+        CounterValue: EncodeCounterValue,
+        ExemplarValue: EncodeExemplarValue,
+    >(
+        &mut self,
+        v: &CounterValue,
+        _exemplar: Option<&Exemplar<S, ExemplarValue>>,
+        created: Option<SystemTime>,
+    ) -> Result<(), std::fmt::Error> {
+        let mut value = self::CounterValue::Int(0);
+        v.encode(&mut CounterValueEncoder { value: &mut value }.into())?;
+        self.visitor.visit_counter(&self.name, &self.labels, value);
+        self.visit_created(created);
+        Ok(())
+    }
+
+    pub fn encode_gauge<GaugeValue: EncodeGaugeValue>(
+        &mut self,
+        v: &GaugeValue,
+    ) -> Result<(), std::fmt::Error> {
+        let mut value = self::GaugeValue::Int(0);
+        v.encode(&mut GaugeValueEncoder { value: &mut value }.into())?;
+        self.visitor.visit_gauge(&self.name, &self.labels, value);
+        Ok(())
+    }
+
+    pub fn encode_unknown<GaugeValue: EncodeGaugeValue>(
+        &mut self,
+        v: &GaugeValue,
+    ) -> Result<(), std::fmt::Error> {
+        let mut value = self::GaugeValue::Int(0);
+        v.encode(&mut GaugeValueEncoder { value: &mut value }.into())?;
+        self.visitor.visit_unknown(&self.name, &self.labels, value);
+        Ok(())
+    }
+
+    pub fn encode_info(&mut self, label_set: &impl EncodeLabelSet) -> Result<(), std::fmt::Error> {
+        let info = super::collect_labels(label_set)?;
+        self.visitor.visit_info(&self.name, &self.labels, &info);
+        Ok(())
+    }
+
+    pub fn encode_stateset<'s>(
+        &mut self,
+        states: impl IntoIterator<Item = (&'s str, bool)>,
+    ) -> Result<(), std::fmt::Error> {
+        let states = states.into_iter().collect::<Vec<_>>();
+        self.visitor
+            .visit_stateset(&self.name, &self.labels, &states);
+        Ok(())
+    }
+
+    pub fn encode_histogram<S: EncodeLabelSet, V: EncodeExemplarValue>(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+        _exemplars: Option<&HashMap<usize, Exemplar<S, V>>>,
+        created: Option<SystemTime>,
+    ) -> Result<(), std::fmt::Error> {
+        let buckets = cumulative(buckets);
+        self.visitor
+            .visit_histogram(&self.name, &self.labels, sum, count, &buckets);
+        self.visit_created(created);
+        Ok(())
+    }
+
+    fn visit_created(&mut self, created: Option<SystemTime>) {
+        if let Some(created) = created {
+            self.visitor
+                .visit_created(&self.name, &self.labels, created);
+        }
+    }
+
+    pub fn encode_gauge_histogram(
+        &mut self,
+        sum: f64,
+        count: u64,
+        buckets: impl Iterator<Item = (f64, u64)>,
+    ) -> Result<(), std::fmt::Error> {
+        let buckets = cumulative(buckets);
+        self.visitor
+            .visit_gauge_histogram(&self.name, &self.labels, sum, count, &buckets);
+        Ok(())
+    }
+
+    pub fn by_ref(&mut self) -> MetricEncoder<'_> {
+        MetricEncoder {
+            visitor: self.visitor,
+            name: self.name.clone(),
+            metric_type: self.metric_type,
+            labels: self.labels.clone(),
+        }
+    }
+
+    pub fn encode_family<S: EncodeLabelSet>(
+        &mut self,
+        label_set: &S,
+    ) -> Result<MetricEncoder<'_>, std::fmt::Error> {
+        let mut labels = self.labels.clone();
+        label_set.encode(
+            LabelSetEncoder {
+                labels: &mut labels,
+            }
+            .into(),
+        )?;
+
+        Ok(MetricEncoder {
+            visitor: self.visitor,
+            name: self.name.clone(),
+            metric_type: self.metric_type,
+            labels,
+        })
+    }
+}
+
+/// Accumulate the given bucket counts.
+fn cumulative(buckets: impl Iterator<Item = (f64, u64)>) -> Vec<(f64, u64)> {
+    let mut cumulative = 0;
+    buckets
+        .map(|(upper_bound, count)| {
+            cumulative += count;
+            (upper_bound, cumulative)
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub(crate) struct CounterValueEncoder<'a> {
+    value: &'a mut CounterValue,
+}
+
+impl CounterValueEncoder<'_> {
+    pub fn encode_f64(&mut self, v: f64) -> Result<(), std::fmt::Error> {
+        *self.value = CounterValue::Double(v);
+        Ok(())
+    }
+
+    pub fn encode_u64(&mut self, v: u64) -> Result<(), std::fmt::Error> {
+        *self.value = CounterValue::Int(v);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct GaugeValueEncoder<'a> {
+    value: &'a mut GaugeValue,
+}
+
+impl GaugeValueEncoder<'_> {
+    pub fn encode_u32(&mut self, v: u32) -> Result<(), std::fmt::Error> {
+        self.encode_i64(v as i64)
+    }
+
+    pub fn encode_i64(&mut self, v: i64) -> Result<(), std::fmt::Error> {
+        *self.value = GaugeValue::Int(v);
+        Ok(())
+    }
+
+    pub fn encode_f64(&mut self, v: f64) -> Result<(), std::fmt::Error> {
+        *self.value = GaugeValue::Double(v);
+        Ok(())
+    }
+}
+
+/// Get the given gauge value as [`f64`].
+pub(crate) fn gauge_value_f64(v: &impl EncodeGaugeValue) -> Result<f64, std::fmt::Error> {
+    let mut value = GaugeValue::Int(0);
+    v.encode(&mut GaugeValueEncoder { value: &mut value }.into())?;
+    Ok(match value {
+        GaugeValue::Int(v) => v as f64,
+        GaugeValue::Double(v) => v,
+    })
+}
+
+#[derive(Debug)]
+pub(crate) struct LabelSetEncoder<'a> {
+    labels: &'a mut Vec<(String, String)>,
+}
+
+impl<'a> LabelSetEncoder<'a> {
+    /// Encoder appending each label as name and value pair to `labels`.
+    pub(crate) fn new(labels: &'a mut Vec<(String, String)>) -> Self {
+        Self { labels }
+    }
+}
+
+impl LabelSetEncoder<'_> {
+    pub fn encode_label(&mut self) -> LabelEncoder<'_> {
+        LabelEncoder {
+            labels: self.labels,
+        }
+    }
+
+    pub fn by_ref(&mut self) -> LabelSetEncoder<'_> {
+        LabelSetEncoder {
+            labels: self.labels,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LabelEncoder<'a> {
+    labels: &'a mut Vec<(String, String)>,
+}
+
+impl LabelEncoder<'_> {
+    pub fn encode_label_key(&mut self) -> Result<LabelKeyEncoder<'_>, std::fmt::Error> {
+        self.labels.push(Default::default());
+
+        Ok(LabelKeyEncoder {
+            label: self.labels.last_mut().expect("To find pushed label."),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LabelKeyEncoder<'a> {
+    label: &'a mut (String, String),
+}
+
+impl std::fmt::Write for LabelKeyEncoder<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.label.0.write_str(s)
+    }
+}
+
+impl<'a> LabelKeyEncoder<'a> {
+    pub fn encode_label_value(self) -> Result<LabelValueEncoder<'a>, std::fmt::Error> {
+        Ok(LabelValueEncoder {
+            label_value: &mut self.label.1,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct LabelValueEncoder<'a> {
+    label_value: &'a mut String,
+}
+
+impl LabelValueEncoder<'_> {
+    pub fn finish(self) -> Result<(), std::fmt::Error> {
+        Ok(())
+    }
+}
+
+impl std::fmt::Write for LabelValueEncoder<'_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.label_value.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::counter::Counter;
+    use crate::metrics::family::Family;
+    use crate::metrics::gauge::Gauge;
+    use crate::metrics::histogram::Histogram;
+    use crate::metrics::info::Info;
+    use crate::registry::Registry;
+    use std::sync::atomic::AtomicU64;
+
+    #[derive(Debug, Default)]
+    struct Recorder(Vec<String>);
+
+    impl MetricVisitor for Recorder {
+        fn visit_counter(&mut self, name: &str, labels: &[(String, String)], value: CounterValue) {
+            self.0.push(format!("counter {name} {labels:?} {value:?}"));
+        }
+
+        fn visit_gauge(&mut self, name: &str, labels: &[(String, String)], value: GaugeValue) {
+            self.0.push(format!("gauge {name} {labels:?} {value:?}"));
+        }
+
+        fn visit_histogram(
+            &mut self,
+            name: &str,
+            labels: &[(String, String)],
+            sum: f64,
+            count: u64,
+            buckets: &[(f64, u64)],
+        ) {
+            self.0.push(format!(
+                "histogram {name} {labels:?} {sum} {count} {buckets:?}"
+            ));
+        }
+    }
+
+    #[test]
+    fn visit_registry() {
+        let mut registry =
+            Registry::with_labels([(Cow::Borrowed("env"), Cow::Borrowed("prod"))].into_iter());
+
+        let requests = Family::<Vec<(String, String)>, Counter>::default();
+        registry.register("requests", "Requests", requests.clone());
+        requests
+            .get_or_create(&vec![("method".to_string(), "GET".to_string())])
+            .inc_by(2);
+
+        let sub_registry = registry.sub_registry_with_prefix("db");
+        let load = Gauge::<f64, AtomicU64>::default();
+        sub_registry.register("load", "Load", load.clone());
+        load.set(0.5);
+        let latency = Histogram::new([1.0]);
+        sub_registry.register_with_unit("latency", "Latency", Unit::Seconds, latency.clone());
+        latency.observe(0.5);
+        latency.observe(5.0);
+        sub_registry.register("version", "Version", Info::new(vec![("v", "1")]));
+
+        let mut recorder = Recorder::default();
+        registry.visit(&mut recorder).unwrap();
+
+        assert_eq!(
+            vec![
+                r#"counter requests [("env", "prod"), ("method", "GET")] Int(2)"#,
+                r#"gauge db_load [("env", "prod")] Double(0.5)"#,
+                r#"histogram db_latency_seconds [("env", "prod")] 5.5 2 [(1.0, 1), (1.7976931348623157e308, 2)]"#,
+            ],
+            recorder.0
+        );
+    }
+}
@@ -16,7 +16,7 @@
 //! ```
 //! use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
 //! use prometheus_client::encoding::text::encode;
-//! use prometheus_client::metrics::counter::{Atomic, Counter};
+//! use prometheus_client::metrics::counter::Counter;
 //! use prometheus_client::metrics::family::Family;
 //! use prometheus_client::registry::Registry;
 //! use std::io::Write;
@@ -79,6 +79,9 @@
 //!
 //! [examples]: https://github.com/prometheus/client_rust/tree/master/examples
 
+#[cfg(feature = "axum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axum")))]
+pub mod axum;
 pub mod collector;
 pub mod encoding;
 pub mod metrics;
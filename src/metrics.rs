@@ -1,11 +1,34 @@
 //! Metric type implementations.
+//!
+//! # 32-bit storage
+//!
+//! [`Counter`](counter::Counter) and [`Gauge`](gauge::Gauge) can store their
+//! value in an [`AtomicU32`](std::sync::atomic::AtomicU32) instead of an
+//! [`AtomicU64`](std::sync::atomic::AtomicU64), using [`u32`] or [`f32`] on the
+//! interface. This halves the memory footprint and is available on all
+//! targets, including 32-bit ones without 64-bit atomics, at the cost of range
+//! and precision. Prefer the 64-bit types where neither matters.
 
 pub mod counter;
 pub mod exemplar;
 pub mod family;
 pub mod gauge;
+pub mod gauge_histogram;
 pub mod histogram;
 pub mod info;
+pub mod stateset;
+pub mod unknown;
+
+/// The current time, used as the creation time of metrics.
+///
+/// `None` on platforms without a system clock.
+pub(crate) fn now() -> Option<std::time::SystemTime> {
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    return None;
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    Some(std::time::SystemTime::now())
+}
 
 /// A metric that is aware of its Open Metrics metric type.
 pub trait TypedMetric {
@@ -14,19 +37,21 @@ pub trait TypedMetric {
 }
 
 /// OpenMetrics metric type.
+///
+/// Marked `#[non_exhaustive]`, as further metric types may be supported in the
+/// future.
 #[derive(Clone, Copy, Debug)]
 #[allow(missing_docs)]
+#[non_exhaustive]
 pub enum MetricType {
     Counter,
     Gauge,
+    GaugeHistogram,
     Histogram,
     Info,
+    StateSet,
+    Summary,
     Unknown,
-    // Not (yet) supported metric types.
-    //
-    // GaugeHistogram,
-    // StateSet,
-    // Summary
 }
 
 impl MetricType {
@@ -35,9 +60,33 @@ impl MetricType {
         match self {
             MetricType::Counter => "counter",
             MetricType::Gauge => "gauge",
+            MetricType::GaugeHistogram => "gaugehistogram",
             MetricType::Histogram => "histogram",
             MetricType::Info => "info",
+            MetricType::StateSet => "stateset",
+            MetricType::Summary => "summary",
             MetricType::Unknown => "unknown",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metric_type_as_str() {
+        for (metric_type, expected) in [
+            (MetricType::Counter, "counter"),
+            (MetricType::Gauge, "gauge"),
+            (MetricType::GaugeHistogram, "gaugehistogram"),
+            (MetricType::Histogram, "histogram"),
+            (MetricType::Info, "info"),
+            (MetricType::StateSet, "stateset"),
+            (MetricType::Summary, "summary"),
+            (MetricType::Unknown, "unknown"),
+        ] {
+            assert_eq!(expected, metric_type.as_str());
+        }
+    }
+}
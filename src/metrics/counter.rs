@@ -2,14 +2,16 @@
 //!
 //! See [`Counter`] for details.
 
-use crate::encoding::{EncodeMetric, MetricEncoder, NoLabelSet};
+use crate::encoding::{EncodeLabelSet, EncodeMetric, MetricEncoder, NoLabelSet};
 
 use super::{MetricType, TypedMetric};
+use std::cell::Cell;
 use std::marker::PhantomData;
 #[cfg(target_has_atomic = "64")]
 use std::sync::atomic::AtomicU64;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::SystemTime;
 
 /// Open Metrics [`Counter`] to measure discrete events.
 ///
@@ -40,10 +42,22 @@ use std::sync::Arc;
 /// counter.inc();
 /// let _value: f64 = counter.get();
 /// ```
+///
+/// ## Using [`AtomicU32`] as storage and [`f32`] on the interface
+///
+/// See the [module documentation](super#32-bit-storage) for the trade-offs.
+///
+/// ```
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use std::sync::atomic::AtomicU32;
+/// let counter = Counter::<f32, AtomicU32>::default();
+/// counter.inc_by(0.5);
+/// let _value: f32 = counter.get();
+/// ```
 #[cfg(target_has_atomic = "64")]
 #[derive(Debug)]
 pub struct Counter<N = u64, A = AtomicU64> {
-    value: Arc<A>,
+    inner: Arc<Inner<A>>,
     phantom: PhantomData<N>,
 }
 
@@ -51,14 +65,72 @@ pub struct Counter<N = u64, A = AtomicU64> {
 #[cfg(not(target_has_atomic = "64"))]
 #[derive(Debug)]
 pub struct Counter<N = u32, A = AtomicU32> {
-    value: Arc<A>,
+    inner: Arc<Inner<A>>,
     phantom: PhantomData<N>,
 }
 
+#[derive(Debug)]
+struct Inner<A> {
+    value: A,
+    created: Created,
+}
+
+/// Creation time of a [`Counter`], stored as nanoseconds since the Unix epoch,
+/// `0` if unknown.
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug)]
+struct Created(AtomicU64);
+
+/// Creation time of a [`Counter`]. Behind a lock on targets without 64-bit
+/// atomics.
+#[cfg(not(target_has_atomic = "64"))]
+#[derive(Debug)]
+struct Created(parking_lot::Mutex<Option<SystemTime>>);
+
+#[cfg(target_has_atomic = "64")]
+impl Created {
+    fn now() -> Self {
+        Self(AtomicU64::new(Self::to_nanos(super::now())))
+    }
+
+    fn get(&self) -> Option<SystemTime> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(SystemTime::UNIX_EPOCH + std::time::Duration::from_nanos(nanos)),
+        }
+    }
+
+    fn reset(&self) {
+        self.0
+            .store(Self::to_nanos(super::now()), Ordering::Relaxed);
+    }
+
+    fn to_nanos(time: Option<SystemTime>) -> u64 {
+        time.and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .and_then(|since_epoch| u64::try_from(since_epoch.as_nanos()).ok())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(not(target_has_atomic = "64"))]
+impl Created {
+    fn now() -> Self {
+        Self(parking_lot::Mutex::new(super::now()))
+    }
+
+    fn get(&self) -> Option<SystemTime> {
+        *self.0.lock()
+    }
+
+    fn reset(&self) {
+        *self.0.lock() = super::now();
+    }
+}
+
 impl<N, A> Clone for Counter<N, A> {
     fn clone(&self) -> Self {
         Self {
-            value: self.value.clone(),
+            inner: self.inner.clone(),
             phantom: PhantomData,
         }
     }
@@ -67,7 +139,10 @@ impl<N, A> Clone for Counter<N, A> {
 impl<N, A: Default> Default for Counter<N, A> {
     fn default() -> Self {
         Counter {
-            value: Arc::new(A::default()),
+            inner: Arc::new(Inner {
+                value: A::default(),
+                created: Created::now(),
+            }),
             phantom: PhantomData,
         }
     }
@@ -76,17 +151,69 @@ impl<N, A: Default> Default for Counter<N, A> {
 impl<N, A: Atomic<N>> Counter<N, A> {
     /// Increase the [`Counter`] by 1, returning the previous value.
     pub fn inc(&self) -> N {
-        self.value.inc()
+        self.inner.value.inc()
     }
 
     /// Increase the [`Counter`] by `v`, returning the previous value.
     pub fn inc_by(&self, v: N) -> N {
-        self.value.inc_by(v)
+        self.inner.value.inc_by(v)
     }
 
     /// Get the current value of the [`Counter`].
     pub fn get(&self) -> N {
-        self.value.get()
+        self.inner.value.get()
+    }
+
+    /// Create a new [`Counter`] starting at `value`, e.g. to resume a counter
+    /// persisted via [`Counter::get`] across a graceful restart.
+    ///
+    /// With the `serde` feature enabled, [`Counter`] implements `Serialize`
+    /// and `Deserialize` based on its current value.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// let counter: Counter = Counter::from_snapshot(42);
+    /// assert_eq!(42, counter.get());
+    /// ```
+    pub fn from_snapshot(value: N) -> Self
+    where
+        A: Default,
+    {
+        let counter = Self::default();
+        counter.inc_by(value);
+        counter
+    }
+
+    /// The time the [`Counter`] was created or last reset at.
+    ///
+    /// Exposed as `_created` sample if enabled via
+    /// [`Registry::set_emit_created`](crate::registry::Registry::set_emit_created).
+    /// `None` on platforms without a system clock.
+    pub fn created(&self) -> Option<SystemTime> {
+        self.inner.created.get()
+    }
+
+    /// Take a [`CounterSnapshot`] of the current value and creation time of
+    /// the [`Counter`], e.g. to detect a reset by comparing it to an earlier
+    /// snapshot.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// let counter: Counter = Counter::default();
+    /// counter.inc_by(2);
+    /// let earlier = counter.snapshot();
+    ///
+    /// counter.inc();
+    /// assert!(!counter.snapshot().is_reset_since(&earlier));
+    ///
+    /// counter.reset();
+    /// assert!(counter.snapshot().is_reset_since(&earlier));
+    /// ```
+    pub fn snapshot(&self) -> CounterSnapshot<N> {
+        CounterSnapshot {
+            created: self.created(),
+            value: self.get(),
+        }
     }
 
     /// Exposes the inner atomic type of the [`Counter`].
@@ -98,7 +225,138 @@ impl<N, A: Atomic<N>> Counter<N, A> {
     /// Metrics counter namely that the value is monotonically increasing, i.e.
     /// either stays the same or increases.
     pub fn inner(&self) -> &A {
-        &self.value
+        &self.inner.value
+    }
+}
+
+#[cfg(target_has_atomic = "64")]
+impl<N> Counter<N, AtomicU64> {
+    /// Reset the [`Counter`] to zero.
+    ///
+    /// Counters are expected to be monotonically increasing. Resetting is
+    /// intended for test isolation and should otherwise only be used when the
+    /// monitored process is restarted logically. Sets [`Counter::created`] to
+    /// the current time, signaling the reset to scrapers.
+    pub fn reset(&self) {
+        // An all-zero bit pattern is `0` for both `u64` and `f64`.
+        self.inner.value.store(0, Ordering::Relaxed);
+        self.inner.created.reset();
+    }
+}
+
+impl<N> Counter<N, AtomicU32> {
+    /// Reset the [`Counter`] to zero.
+    ///
+    /// Counters are expected to be monotonically increasing. Resetting is
+    /// intended for test isolation and should otherwise only be used when the
+    /// monitored process is restarted logically. Sets [`Counter::created`] to
+    /// the current time, signaling the reset to scrapers.
+    pub fn reset(&self) {
+        // An all-zero bit pattern is `0` for both `u32` and `f32`.
+        self.inner.value.store(0, Ordering::Relaxed);
+        self.inner.created.reset();
+    }
+}
+
+macro_rules! impl_try_inc_by {
+    ($($float:ident),*) => {$(
+        impl<A: Atomic<$float>> Counter<$float, A> {
+            /// Increase the [`Counter`] by `v`, unless `v` is negative or not
+            /// finite and would thus break the monotonicity of the [`Counter`].
+            ///
+            /// [`Counter::inc_by`] panics on such values in debug builds.
+            ///
+            /// ```
+            /// # use prometheus_client::metrics::counter::Counter;
+            /// # use std::sync::atomic::AtomicU64;
+            /// let counter = Counter::<f64, AtomicU64>::default();
+            /// assert!(counter.try_inc_by(1.5).is_ok());
+            /// assert!(counter.try_inc_by(-1.0).is_err());
+            /// assert!(counter.try_inc_by(f64::NAN).is_err());
+            /// assert_eq!(1.5, counter.get());
+            /// ```
+            pub fn try_inc_by(&self, v: $float) -> Result<(), NonMonotonic> {
+                if !(v.is_finite() && v >= 0.0) {
+                    return Err(NonMonotonic { value: v as f64 });
+                }
+                self.inc_by(v);
+                Ok(())
+            }
+        }
+    )*};
+}
+
+impl_try_inc_by!(f64, f32);
+
+/// Value and creation time of a [`Counter`] at a point in time, see
+/// [`Counter::snapshot`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CounterSnapshot<N> {
+    value: N,
+    created: Option<SystemTime>,
+}
+
+impl<N: Copy + PartialOrd> CounterSnapshot<N> {
+    /// The value of the [`Counter`].
+    pub fn value(&self) -> N {
+        self.value
+    }
+
+    /// The time the [`Counter`] was created or last reset at, see
+    /// [`Counter::created`].
+    pub fn created(&self) -> Option<SystemTime> {
+        self.created
+    }
+
+    /// Whether the [`Counter`] was presumably reset since the `earlier`
+    /// snapshot, i.e. its value decreased or it was created anew or reset via
+    /// [`Counter::reset`].
+    pub fn is_reset_since(&self, earlier: &Self) -> bool {
+        self.value < earlier.value || self.created != earlier.created
+    }
+}
+
+/// Error returned by [`Counter::try_inc_by`] for increments that are negative
+/// or not finite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NonMonotonic {
+    value: f64,
+}
+
+impl NonMonotonic {
+    /// The rejected increment.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+}
+
+impl std::fmt::Display for NonMonotonic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "counter increment must be finite and non-negative, got {}",
+            self.value
+        )
+    }
+}
+
+impl std::error::Error for NonMonotonic {}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<N: serde::Serialize, A: Atomic<N>> serde::Serialize for Counter<N, A> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, N: serde::Deserialize<'de>, A: Atomic<N> + Default> serde::Deserialize<'de>
+    for Counter<N, A>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        N::deserialize(deserializer).map(Self::from_snapshot)
     }
 }
 
@@ -114,95 +372,234 @@ pub trait Atomic<N> {
     fn get(&self) -> N;
 }
 
+macro_rules! impl_atomic_integer {
+    ($($num:ident => $atomic:ident),*) => {$(
+        impl Atomic<$num> for $atomic {
+            fn inc(&self) -> $num {
+                self.inc_by(1)
+            }
+
+            fn inc_by(&self, v: $num) -> $num {
+                self.fetch_add(v, Ordering::Relaxed)
+            }
+
+            fn get(&self) -> $num {
+                self.load(Ordering::Relaxed)
+            }
+        }
+    )*};
+}
+
+/// Implements [`Atomic`] for a float type, stored as its bit pattern in the
+/// unsigned atomic integer of the same width.
+macro_rules! impl_atomic_float {
+    ($($float:ident => $atomic:ident),*) => {$(
+        impl Atomic<$float> for $atomic {
+            fn inc(&self) -> $float {
+                self.inc_by(1.0)
+            }
+
+            fn inc_by(&self, v: $float) -> $float {
+                debug_assert!(
+                    v.is_finite() && v >= 0.0,
+                    "counter increment must be finite and non-negative, got {v}"
+                );
+                let previous = self
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                        Some(($float::from_bits(bits) + v).to_bits())
+                    })
+                    .unwrap_or_else(|bits| bits);
+                $float::from_bits(previous)
+            }
+
+            fn get(&self) -> $float {
+                $float::from_bits(self.load(Ordering::Relaxed))
+            }
+        }
+    )*};
+}
+
+impl_atomic_integer!(u32 => AtomicU32);
+impl_atomic_float!(f32 => AtomicU32);
+
 #[cfg(target_has_atomic = "64")]
-impl Atomic<u64> for AtomicU64 {
-    fn inc(&self) -> u64 {
-        self.inc_by(1)
+impl_atomic_integer!(u64 => AtomicU64);
+#[cfg(target_has_atomic = "64")]
+impl_atomic_float!(f64 => AtomicU64);
+
+impl<N, A> TypedMetric for Counter<N, A> {
+    const TYPE: MetricType = MetricType::Counter;
+}
+
+impl<N, A> EncodeMetric for Counter<N, A>
+where
+    N: crate::encoding::EncodeCounterValue,
+    A: Atomic<N>,
+{
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode_counter_with_created::<NoLabelSet, _, u64>(&self.get(), None, self.created())
     }
 
-    fn inc_by(&self, v: u64) -> u64 {
-        self.fetch_add(v, Ordering::Relaxed)
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
     }
+}
 
-    fn get(&self) -> u64 {
-        self.load(Ordering::Relaxed)
+/// As a [`Counter`], but spreading increments across multiple atomics to reduce
+/// contention when many threads update the same counter concurrently.
+///
+/// A [`ShardedCounter`] holds one atomic per available CPU, each padded to its
+/// own cache line. Every thread increments a single shard, while
+/// [`ShardedCounter::get`] and encoding sum up all shards. This trades memory,
+/// i.e. 128 bytes per shard instead of 8 bytes per [`Counter`], and slower
+/// reads for faster writes. Only use it for counters that are shown to be
+/// contended.
+///
+/// ```
+/// # use prometheus_client::metrics::counter::ShardedCounter;
+/// let counter = ShardedCounter::default();
+///
+/// std::thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|| counter.inc());
+///     }
+/// });
+///
+/// assert_eq!(4, counter.get());
+/// ```
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Clone)]
+pub struct ShardedCounter {
+    shards: Arc<[Shard]>,
+}
+
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug, Default)]
+#[repr(align(128))]
+struct Shard(AtomicU64);
+
+#[cfg(target_has_atomic = "64")]
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        let shards = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        Self::with_shards(shards)
     }
 }
 
-impl Atomic<u32> for AtomicU32 {
-    fn inc(&self) -> u32 {
+#[cfg(target_has_atomic = "64")]
+impl ShardedCounter {
+    /// Create a new [`ShardedCounter`] with the given number of shards, instead
+    /// of one per available CPU.
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            shards: (0..shards.max(1)).map(|_| Shard::default()).collect(),
+        }
+    }
+
+    /// Increase the [`ShardedCounter`] by 1.
+    pub fn inc(&self) {
         self.inc_by(1)
     }
 
-    fn inc_by(&self, v: u32) -> u32 {
-        self.fetch_add(v, Ordering::Relaxed)
+    /// Increase the [`ShardedCounter`] by `v`.
+    pub fn inc_by(&self, v: u64) {
+        self.shard().0.fetch_add(v, Ordering::Relaxed);
     }
 
-    fn get(&self) -> u32 {
-        self.load(Ordering::Relaxed)
+    /// Get the current value of the [`ShardedCounter`], i.e. the sum of all
+    /// shards.
+    pub fn get(&self) -> u64 {
+        self.shards.iter().fold(0, |sum, shard| {
+            sum.wrapping_add(shard.0.load(Ordering::Relaxed))
+        })
+    }
+
+    fn shard(&self) -> &Shard {
+        static NEXT_THREAD: AtomicUsize = AtomicUsize::new(0);
+        thread_local! {
+            static THREAD: usize = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+        }
+
+        &self.shards[THREAD.with(|t| *t) % self.shards.len()]
     }
 }
 
 #[cfg(target_has_atomic = "64")]
-impl Atomic<f64> for AtomicU64 {
-    fn inc(&self) -> f64 {
-        self.inc_by(1.0)
-    }
-
-    fn inc_by(&self, v: f64) -> f64 {
-        let mut old_u64 = self.load(Ordering::Relaxed);
-        let mut old_f64;
-        loop {
-            old_f64 = f64::from_bits(old_u64);
-            let new = f64::to_bits(old_f64 + v);
-            match self.compare_exchange_weak(old_u64, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u64 = x,
-            }
-        }
+impl TypedMetric for ShardedCounter {
+    const TYPE: MetricType = MetricType::Counter;
+}
 
-        old_f64
+#[cfg(target_has_atomic = "64")]
+impl EncodeMetric for ShardedCounter {
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode_counter::<NoLabelSet, _, u64>(&self.get(), None)
     }
 
-    fn get(&self) -> f64 {
-        f64::from_bits(self.load(Ordering::Relaxed))
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
     }
 }
 
-impl Atomic<f32> for AtomicU32 {
-    fn inc(&self) -> f32 {
-        self.inc_by(1.0)
+/// As a [`Counter`], but backed by a [`Cell`] instead of an atomic, avoiding
+/// synchronization costs in hot loops confined to a single thread.
+///
+/// [`LocalCounter`] is neither [`Sync`] nor [`Clone`] and can thus not be
+/// registered with a [`Registry`](crate::registry::Registry) directly. Instead
+/// use it as a thread-local accumulator, periodically flushing it into a shared
+/// [`Counter`] via [`LocalCounter::flush`].
+///
+/// ```
+/// # use prometheus_client::metrics::counter::{Counter, LocalCounter};
+/// let shared: Counter = Counter::default();
+///
+/// let local = LocalCounter::default();
+/// for _ in 0..1000 {
+///     local.inc();
+/// }
+/// local.flush(&shared);
+///
+/// assert_eq!(1000, shared.get());
+/// assert_eq!(0, local.get());
+/// ```
+#[derive(Debug, Default)]
+pub struct LocalCounter {
+    value: Cell<u64>,
+}
+
+impl LocalCounter {
+    /// Increase the [`LocalCounter`] by 1, returning the previous value.
+    pub fn inc(&self) -> u64 {
+        self.inc_by(1)
     }
 
-    fn inc_by(&self, v: f32) -> f32 {
-        let mut old_u32 = self.load(Ordering::Relaxed);
-        let mut old_f32;
-        loop {
-            old_f32 = f32::from_bits(old_u32);
-            let new = f32::to_bits(old_f32 + v);
-            match self.compare_exchange_weak(old_u32, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u32 = x,
-            }
-        }
+    /// Increase the [`LocalCounter`] by `v`, returning the previous value.
+    pub fn inc_by(&self, v: u64) -> u64 {
+        let previous = self.value.get();
+        self.value.set(previous.wrapping_add(v));
+        previous
+    }
 
-        old_f32
+    /// Get the current value of the [`LocalCounter`].
+    pub fn get(&self) -> u64 {
+        self.value.get()
     }
 
-    fn get(&self) -> f32 {
-        f32::from_bits(self.load(Ordering::Relaxed))
+    /// Add the current value of the [`LocalCounter`] to `counter` and reset the
+    /// [`LocalCounter`] to zero, returning the flushed value.
+    pub fn flush<A: Atomic<u64>>(&self, counter: &Counter<u64, A>) -> u64 {
+        let v = self.value.take();
+        counter.inc_by(v);
+        v
     }
 }
 
-impl<N, A> TypedMetric for Counter<N, A> {
+impl TypedMetric for LocalCounter {
     const TYPE: MetricType = MetricType::Counter;
 }
 
-impl<N, A> EncodeMetric for Counter<N, A>
-where
-    N: crate::encoding::EncodeCounterValue,
-    A: Atomic<N>,
-{
+impl EncodeMetric for LocalCounter {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
         encoder.encode_counter::<NoLabelSet, _, u64>(&self.get(), None)
     }
@@ -215,28 +612,67 @@ where
 /// As a [`Counter`], but constant, meaning it cannot change once created.
 ///
 /// Needed for advanced use-cases, e.g. in combination with [`Collector`](crate::collector::Collector).
-#[derive(Debug, Default)]
-pub struct ConstCounter<N = u64> {
+#[derive(Debug)]
+pub struct ConstCounter<N = u64, S = NoLabelSet> {
     value: N,
+    label_set: Option<S>,
+}
+
+impl<N: Default, S> Default for ConstCounter<N, S> {
+    fn default() -> Self {
+        Self {
+            value: Default::default(),
+            label_set: None,
+        }
+    }
 }
 
 impl<N> ConstCounter<N> {
     /// Creates a new [`ConstCounter`].
     pub fn new(value: N) -> Self {
-        Self { value }
+        Self {
+            value,
+            label_set: None,
+        }
     }
 }
 
-impl<N> TypedMetric for ConstCounter<N> {
+impl<N, S> ConstCounter<N, S> {
+    /// Creates a new [`ConstCounter`] with the given label set, e.g. to expose one
+    /// sample per device from a [`Collector`](crate::collector::Collector).
+    ///
+    /// Encode multiple [`ConstCounter`]s with different label sets into the
+    /// same [`MetricEncoder`], see [`MetricEncoder::by_ref`], to expose them as
+    /// a single metric family.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::ConstCounter;
+    /// let metric = ConstCounter::with_labels(42u64, vec![("device", "sda")]);
+    /// ```
+    pub fn with_labels(value: N, label_set: S) -> Self {
+        Self {
+            value,
+            label_set: Some(label_set),
+        }
+    }
+}
+
+impl<N, S> TypedMetric for ConstCounter<N, S> {
     const TYPE: MetricType = MetricType::Counter;
 }
 
-impl<N> EncodeMetric for ConstCounter<N>
+impl<N, S> EncodeMetric for ConstCounter<N, S>
 where
     N: crate::encoding::EncodeCounterValue,
+    S: EncodeLabelSet,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        encoder.encode_counter::<NoLabelSet, _, u64>(&self.value, None)
+        match &self.label_set {
+            Some(label_set) => encoder
+                .encode_family(label_set)?
+                .encode_counter::<NoLabelSet, _, u64>(&self.value, None),
+            None => encoder.encode_counter::<NoLabelSet, _, u64>(&self.value, None),
+        }
     }
 
     fn metric_type(&self) -> MetricType {
@@ -256,16 +692,121 @@ mod tests {
         assert_eq!(1, counter.get());
     }
 
+    #[test]
+    fn try_inc_by() {
+        let counter = Counter::<f32, AtomicU32>::default();
+        assert_eq!(Ok(()), counter.try_inc_by(2.5));
+        assert_eq!(-1.0, counter.try_inc_by(-1.0).unwrap_err().value());
+        assert!(counter.try_inc_by(f32::INFINITY).is_err());
+        assert!(counter.try_inc_by(f32::NAN).is_err());
+        assert_eq!(2.5, counter.get());
+    }
+
+    #[test]
+    fn snapshot() {
+        let counter: Counter = Counter::default();
+        counter.inc_by(3);
+        let snapshot = counter.snapshot();
+        assert_eq!(3, snapshot.value());
+        assert_eq!(counter.created(), snapshot.created());
+        assert!(!counter.snapshot().is_reset_since(&snapshot));
+
+        // A restarted process creates the counter anew.
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        let restarted: Counter = Counter::from_snapshot(5);
+        assert!(restarted.snapshot().is_reset_since(&snapshot));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "non-negative")]
+    fn inc_by_negative_float_panics_in_debug() {
+        let counter = Counter::<f32, AtomicU32>::default();
+        counter.inc_by(-1.0);
+    }
+
+    #[test]
+    fn reset() {
+        let counter: Counter = Counter::default();
+        counter.inc_by(42);
+        counter.reset();
+        assert_eq!(0, counter.get());
+
+        let counter = Counter::<f32, AtomicU32>::default();
+        counter.inc_by(1.5);
+        counter.reset();
+        assert_eq!(0.0, counter.get());
+    }
+
+    #[test]
+    fn reset_updates_created() {
+        let counter: Counter = Counter::default();
+        counter.inc_by(2);
+        let earlier = counter.snapshot();
+
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        counter.clone().reset();
+        counter.inc_by(5);
+
+        assert!(counter.created() > earlier.created());
+        assert!(counter.snapshot().is_reset_since(&earlier));
+    }
+
+    #[cfg(all(feature = "serde", target_has_atomic = "64"))]
+    #[test]
+    fn serde() {
+        let counter: Counter = Counter::default();
+        counter.inc_by(42);
+
+        let serialized = serde_json::to_string(&counter).unwrap();
+        assert_eq!("42", serialized);
+
+        let restored: Counter = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(42, restored.get());
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn sharded_counter() {
+        let counter = ShardedCounter::with_shards(4);
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                let counter = counter.clone();
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        counter.inc();
+                    }
+                    counter.inc_by(10);
+                });
+            }
+        });
+        assert_eq!(8 * 110, counter.get());
+    }
+
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn local_counter_flush() {
+        let counter: Counter = Counter::default();
+        let local = LocalCounter::default();
+        assert_eq!(0, local.inc());
+        assert_eq!(1, local.inc_by(2));
+
+        assert_eq!(3, local.flush(&counter));
+        assert_eq!(0, local.get());
+        assert_eq!(3, counter.get());
+    }
+
     #[cfg(target_has_atomic = "64")]
     #[test]
     fn f64_stored_in_atomic_u64() {
         fn prop(fs: Vec<f64>) {
             let fs: Vec<f64> = fs
                 .into_iter()
-                // Map infinite, subnormal and NaN to 0.0.
-                .map(|f| if f.is_normal() { f } else { 0.0 })
+                // Map infinite, subnormal and NaN to 0.0. Counters only
+                // accept non-negative increments.
+                .map(|f| if f.is_normal() { f.abs() } else { 0.0 })
                 .collect();
-            let sum = fs.iter().sum();
+            let sum: f64 = fs.iter().sum();
             let counter = Counter::<f64, AtomicU64>::default();
             for f in fs {
                 counter.inc_by(f);
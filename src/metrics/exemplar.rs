@@ -1,27 +1,64 @@
-//! Module implementing an Open Metrics exemplars for counters and histograms.
+//! Module implementing an Open Metrics exemplars for counters, gauges and
+//! histograms.
 //!
-//! See [`CounterWithExemplar`] and [`HistogramWithExemplars`] for details.
+//! See [`CounterWithExemplar`], [`GaugeWithExemplar`] and
+//! [`HistogramWithExemplars`] for details.
 
 use crate::encoding::{
-    EncodeCounterValue, EncodeExemplarValue, EncodeLabelSet, EncodeMetric, MetricEncoder,
+    EncodeCounterValue, EncodeExemplarValue, EncodeGaugeValue, EncodeLabelSet, EncodeMetric,
+    MetricEncoder,
 };
 
 use super::counter::{self, Counter};
+use super::gauge::{self, Gauge};
 use super::histogram::Histogram;
 use super::{MetricType, TypedMetric};
 use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
 use std::collections::HashMap;
 #[cfg(not(target_has_atomic = "64"))]
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicI32, AtomicU32};
 #[cfg(target_has_atomic = "64")]
-use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicI64, AtomicU64};
 use std::sync::Arc;
+use std::time::SystemTime;
 
 /// An OpenMetrics exemplar.
 #[derive(Debug)]
 pub struct Exemplar<S, V> {
     pub(crate) label_set: S,
     pub(crate) value: V,
+    /// Time at which the exemplar was recorded, if provided. Encoded as the
+    /// exemplar timestamp.
+    pub(crate) timestamp: Option<SystemTime>,
+}
+
+/// Maximum combined length, in UTF-8 characters, of the label names and values
+/// of an [`Exemplar`] as defined by OpenMetrics.
+pub const MAX_EXEMPLAR_LABEL_SET_LENGTH: usize = 128;
+
+/// Handling of [`Exemplar`]s whose label set exceeds
+/// [`MAX_EXEMPLAR_LABEL_SET_LENGTH`], see
+/// [`Registry::set_exemplar_overflow`](crate::registry::Registry::set_exemplar_overflow).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExemplarLabelOverflow {
+    /// Encode the metric without the [`Exemplar`].
+    #[default]
+    Omit,
+    /// Fail encoding with [`std::fmt::Error`].
+    Error,
+}
+
+impl ExemplarLabelOverflow {
+    /// Whether to encode an [`Exemplar`] with a label set of the given length.
+    pub(crate) fn check(self, label_set_length: usize) -> Result<bool, std::fmt::Error> {
+        if label_set_length <= MAX_EXEMPLAR_LABEL_SET_LENGTH {
+            return Ok(true);
+        }
+        match self {
+            ExemplarLabelOverflow::Omit => Ok(false),
+            ExemplarLabelOverflow::Error => Err(std::fmt::Error),
+        }
+    }
 }
 
 /////////////////////////////////////////////////////////////////////////////////
@@ -116,11 +153,34 @@ impl<S, N: Clone, A: counter::Atomic<N>> CounterWithExemplar<S, N, A> {
     /// Increase the [`CounterWithExemplar`] by `v`, updating the [`Exemplar`]
     /// if a label set is provided, returning the previous value.
     pub fn inc_by(&self, v: N, label_set: Option<S>) -> N {
+        self.inc_by_with_timestamp(v, label_set, None)
+    }
+
+    /// Like [`CounterWithExemplar::inc_by`], additionally recording the time
+    /// of the [`Exemplar`], e.g. to correlate it with a trace.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::exemplar::CounterWithExemplar;
+    /// # use std::time::SystemTime;
+    /// let counter = CounterWithExemplar::<Vec<(String, String)>>::default();
+    /// counter.inc_by_with_timestamp(
+    ///     1,
+    ///     Some(vec![("trace_id".to_string(), "3a2f90c9f80b894f".to_string())]),
+    ///     Some(SystemTime::now()),
+    /// );
+    /// ```
+    pub fn inc_by_with_timestamp(
+        &self,
+        v: N,
+        label_set: Option<S>,
+        timestamp: Option<SystemTime>,
+    ) -> N {
         let mut inner = self.inner.write();
 
         inner.exemplar = label_set.map(|label_set| Exemplar {
             label_set,
             value: v.clone(),
+            timestamp,
         });
 
         inner.counter.inc_by(v)
@@ -128,13 +188,26 @@ impl<S, N: Clone, A: counter::Atomic<N>> CounterWithExemplar<S, N, A> {
 
     /// Get the current value of the [`CounterWithExemplar`] as well as its
     /// [`Exemplar`] if any.
-    pub fn get(&self) -> (N, MappedRwLockReadGuard<Option<Exemplar<S, N>>>) {
+    pub fn get(&self) -> (N, MappedRwLockReadGuard<'_, Option<Exemplar<S, N>>>) {
         let inner = self.inner.read();
         let value = inner.counter.get();
         let exemplar = RwLockReadGuard::map(inner, |inner| &inner.exemplar);
         (value, exemplar)
     }
 
+    /// Get the current value of the [`CounterWithExemplar`], holding the lock
+    /// only while reading the value.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::exemplar::CounterWithExemplar;
+    /// let counter = CounterWithExemplar::<Vec<(String, String)>>::default();
+    /// counter.inc_by(2, None);
+    /// assert_eq!(2, counter.value());
+    /// ```
+    pub fn value(&self) -> N {
+        self.inner.read().counter.get()
+    }
+
     /// Exposes the inner atomic type of the [`CounterWithExemplar`].
     ///
     /// This should only be used for advanced use-cases which are not directly
@@ -143,7 +216,7 @@ impl<S, N: Clone, A: counter::Atomic<N>> CounterWithExemplar<S, N, A> {
     /// The caller of this function has to uphold the property of an Open
     /// Metrics counter namely that the value is monotonically increasing, i.e.
     /// either stays the same or increases.
-    pub fn inner(&self) -> MappedRwLockReadGuard<A> {
+    pub fn inner(&self) -> MappedRwLockReadGuard<'_, A> {
         RwLockReadGuard::map(self.inner.read(), |inner| inner.counter.inner())
     }
 }
@@ -156,8 +229,14 @@ where
     A: counter::Atomic<N>,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        let (value, exemplar) = self.get();
-        encoder.encode_counter(&value, exemplar.as_ref())
+        // Read all values through a single guard, as a second read lock on
+        // the same thread deadlocks if a writer is queued in between.
+        let inner = self.inner.read();
+        encoder.encode_counter_with_created(
+            &inner.counter.get(),
+            inner.exemplar.as_ref(),
+            inner.counter.created(),
+        )
     }
 
     fn metric_type(&self) -> MetricType {
@@ -165,12 +244,126 @@ where
     }
 }
 
+/////////////////////////////////////////////////////////////////////////////////
+// Gauge
+
+/// Open Metrics [`Gauge`] with an [`Exemplar`] to both record current
+/// measurements and track references to data outside of the metric set.
+///
+/// Note that OpenMetrics 1.0 only defines exemplars for counters and histogram
+/// buckets. While e.g. Prometheus accepts exemplars on gauges in the text
+/// format, strict parsers may reject them. The protobuf format has no field for
+/// gauge exemplars, thus the [`Exemplar`] is not encoded there.
+///
+/// ```
+/// # use prometheus_client::metrics::exemplar::GaugeWithExemplar;
+/// let in_flight = GaugeWithExemplar::<Vec<(String, String)>>::default();
+/// in_flight.set(3, Some(vec![("trace_id".to_string(), "3a2f90c9f80b894f".to_string())]));
+/// let _value: (i64, _) = in_flight.get();
+/// ```
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug)]
+pub struct GaugeWithExemplar<S, N = i64, A = AtomicI64> {
+    pub(crate) inner: Arc<RwLock<GaugeWithExemplarInner<S, N, A>>>,
+}
+
+/// Open Metrics [`Gauge`] with an [`Exemplar`] to both record current
+/// measurements and track references to data outside of the metric set.
+#[cfg(not(target_has_atomic = "64"))]
+#[derive(Debug)]
+pub struct GaugeWithExemplar<S, N = i32, A = AtomicI32> {
+    pub(crate) inner: Arc<RwLock<GaugeWithExemplarInner<S, N, A>>>,
+}
+
+impl<S, N, A> TypedMetric for GaugeWithExemplar<S, N, A> {
+    const TYPE: MetricType = MetricType::Gauge;
+}
+
+impl<S, N, A> Clone for GaugeWithExemplar<S, N, A> {
+    fn clone(&self) -> Self {
+        GaugeWithExemplar {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// An OpenMetrics [`Gauge`] in combination with an OpenMetrics [`Exemplar`].
+#[derive(Debug)]
+pub struct GaugeWithExemplarInner<S, N, A> {
+    pub(crate) exemplar: Option<Exemplar<S, N>>,
+    pub(crate) gauge: Gauge<N, A>,
+}
+
+impl<S, N, A: Default> Default for GaugeWithExemplar<S, N, A> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(GaugeWithExemplarInner {
+                exemplar: None,
+                gauge: Default::default(),
+            })),
+        }
+    }
+}
+
+impl<S, N: Clone, A: gauge::Atomic<N>> GaugeWithExemplar<S, N, A> {
+    /// Sets the [`GaugeWithExemplar`] to `v`, updating the [`Exemplar`] if a
+    /// label set is provided, returning the previous value.
+    pub fn set(&self, v: N, label_set: Option<S>) -> N {
+        let mut inner = self.inner.write();
+
+        inner.exemplar = label_set.map(|label_set| Exemplar {
+            label_set,
+            value: v.clone(),
+            timestamp: None,
+        });
+
+        inner.gauge.set(v)
+    }
+
+    /// Get the current value of the [`GaugeWithExemplar`] as well as its
+    /// [`Exemplar`] if any.
+    pub fn get(&self) -> (N, MappedRwLockReadGuard<'_, Option<Exemplar<S, N>>>) {
+        let inner = self.inner.read();
+        let value = inner.gauge.get();
+        let exemplar = RwLockReadGuard::map(inner, |inner| &inner.exemplar);
+        (value, exemplar)
+    }
+
+    /// Exposes the inner atomic type of the [`GaugeWithExemplar`].
+    ///
+    /// This should only be used for advanced use-cases which are not directly
+    /// supported by the library.
+    pub fn inner(&self) -> MappedRwLockReadGuard<'_, A> {
+        RwLockReadGuard::map(self.inner.read(), |inner| inner.gauge.inner())
+    }
+}
+
+impl<S, N, A> EncodeMetric for GaugeWithExemplar<S, N, A>
+where
+    S: EncodeLabelSet,
+    N: EncodeGaugeValue + EncodeExemplarValue + Clone,
+    A: gauge::Atomic<N>,
+{
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        let (value, exemplar) = self.get();
+        encoder.encode_gauge_with_exemplar(&value, exemplar.as_ref())
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
+
 /////////////////////////////////////////////////////////////////////////////////
 // Histogram
 
 /// Open Metrics [`Histogram`] to both measure distributions of discrete events.
 /// and track references to data outside of the metric set.
 ///
+/// By default the value of an [`Exemplar`] is the observed value. A distinct
+/// exemplar value type `V` can be provided via
+/// [`HistogramWithExemplars::observe_with_exemplar`].
+///
 /// ```
 /// # use prometheus_client::metrics::exemplar::HistogramWithExemplars;
 /// # use prometheus_client::metrics::histogram::exponential_buckets;
@@ -210,16 +403,16 @@ where
 ///     );
 /// ```
 #[derive(Debug)]
-pub struct HistogramWithExemplars<S> {
+pub struct HistogramWithExemplars<S, V = f64> {
     // TODO: Not ideal, as Histogram has a Mutex as well.
-    pub(crate) inner: Arc<RwLock<HistogramWithExemplarsInner<S>>>,
+    pub(crate) inner: Arc<RwLock<HistogramWithExemplarsInner<S, V>>>,
 }
 
-impl<S> TypedMetric for HistogramWithExemplars<S> {
+impl<S, V> TypedMetric for HistogramWithExemplars<S, V> {
     const TYPE: MetricType = MetricType::Histogram;
 }
 
-impl<S> Clone for HistogramWithExemplars<S> {
+impl<S, V> Clone for HistogramWithExemplars<S, V> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
@@ -229,12 +422,12 @@ impl<S> Clone for HistogramWithExemplars<S> {
 
 /// An OpenMetrics [`Histogram`] in combination with an OpenMetrics [`Exemplar`].
 #[derive(Debug)]
-pub struct HistogramWithExemplarsInner<S> {
-    pub(crate) exemplars: HashMap<usize, Exemplar<S, f64>>,
+pub struct HistogramWithExemplarsInner<S, V = f64> {
+    pub(crate) exemplars: HashMap<usize, Exemplar<S, V>>,
     pub(crate) histogram: Histogram,
 }
 
-impl<S> HistogramWithExemplars<S> {
+impl<S, V> HistogramWithExemplars<S, V> {
     /// Create a new [`HistogramWithExemplars`].
     pub fn new(buckets: impl Iterator<Item = f64>) -> Self {
         Self {
@@ -245,35 +438,157 @@ impl<S> HistogramWithExemplars<S> {
         }
     }
 
-    /// Observe the given value, optionally providing a label set and thus
-    /// setting the [`Exemplar`] value.
-    pub fn observe(&self, v: f64, label_set: Option<S>) {
+    /// Observe the given value, optionally providing a label set and a value
+    /// for the [`Exemplar`] of the bucket the observation falls into.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::exemplar::HistogramWithExemplars;
+    /// # use prometheus_client::metrics::histogram::exponential_buckets;
+    /// let histogram =
+    ///     HistogramWithExemplars::<Vec<(String, String)>, u64>::new(exponential_buckets(1.0, 2.0, 10));
+    /// // Reference the sample with id 7 in the bucket of the observed value.
+    /// histogram.observe_with_exemplar(4.2, Some((vec![("sample".to_string(), "a".to_string())], 7)));
+    /// ```
+    pub fn observe_with_exemplar(&self, v: f64, exemplar: Option<(S, V)>) {
+        self.observe_and_record(
+            v,
+            exemplar.map(|(label_set, value)| Exemplar {
+                label_set,
+                value,
+                timestamp: None,
+            }),
+        );
+    }
+
+    fn observe_and_record(&self, v: f64, exemplar: Option<Exemplar<S, V>>) {
         let mut inner = self.inner.write();
         let bucket = inner.histogram.observe_and_bucket(v);
-        if let (Some(bucket), Some(label_set)) = (bucket, label_set) {
-            inner.exemplars.insert(
-                bucket,
-                Exemplar {
-                    label_set,
-                    value: v,
-                },
-            );
+        if let (Some(bucket), Some(exemplar)) = (bucket, exemplar) {
+            inner.exemplars.insert(bucket, exemplar);
         }
     }
 
-    pub(crate) fn inner(&self) -> RwLockReadGuard<HistogramWithExemplarsInner<S>> {
+    pub(crate) fn inner(&self) -> RwLockReadGuard<'_, HistogramWithExemplarsInner<S, V>> {
         self.inner.read()
     }
 }
 
-impl<S: EncodeLabelSet> EncodeMetric for HistogramWithExemplars<S> {
+impl<S> HistogramWithExemplars<S> {
+    /// Observe the given value, optionally providing a label set and thus
+    /// setting the [`Exemplar`] value.
+    pub fn observe(&self, v: f64, label_set: Option<S>) {
+        self.observe_with_timestamp(v, label_set, None);
+    }
+
+    /// Like [`HistogramWithExemplars::observe`], additionally recording the
+    /// time of the [`Exemplar`], e.g. to correlate it with a trace.
+    pub fn observe_with_timestamp(
+        &self,
+        v: f64,
+        label_set: Option<S>,
+        timestamp: Option<SystemTime>,
+    ) {
+        self.observe_and_record(
+            v,
+            label_set.map(|label_set| Exemplar {
+                label_set,
+                value: v,
+                timestamp,
+            }),
+        );
+    }
+
+    /// Observe the given value, setting the [`Exemplar`] only if the value is
+    /// greater than or equal to `threshold`.
+    ///
+    /// The label set closure is only invoked when the [`Exemplar`] is set,
+    /// thus avoiding the cost of constructing e.g. a trace ID label for values
+    /// below the threshold.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::exemplar::HistogramWithExemplars;
+    /// # use prometheus_client::metrics::histogram::exponential_buckets;
+    /// let histogram = HistogramWithExemplars::new(exponential_buckets(0.1, 2.0, 10));
+    /// // Only slow requests, i.e. those taking one second or more, are
+    /// // recorded with an exemplar.
+    /// histogram.observe_with_exemplar_above(4.2, 1.0, || {
+    ///     vec![("trace_id".to_string(), "3a2f90c9f80b894f".to_string())]
+    /// });
+    /// ```
+    pub fn observe_with_exemplar_above(
+        &self,
+        v: f64,
+        threshold: f64,
+        label_set: impl FnOnce() -> S,
+    ) {
+        let mut inner = self.inner.write();
+        let bucket = inner.histogram.observe_and_bucket(v);
+        if let Some(bucket) = bucket {
+            if v >= threshold {
+                inner.exemplars.insert(
+                    bucket,
+                    Exemplar {
+                        label_set: label_set(),
+                        value: v,
+                        timestamp: None,
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl<S: EncodeLabelSet, V: EncodeExemplarValue> EncodeMetric for HistogramWithExemplars<S, V> {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
         let inner = self.inner();
-        let (sum, count, buckets) = inner.histogram.get();
-        encoder.encode_histogram(sum, count, &buckets, Some(&inner.exemplars))
+        let created = inner.histogram.created();
+        let (sum, count, histogram) = inner.histogram.get();
+        encoder.encode_histogram_buckets(
+            sum,
+            count,
+            histogram.buckets(),
+            Some(&inner.exemplars),
+            created,
+        )
     }
 
     fn metric_type(&self) -> MetricType {
-        Histogram::TYPE
+        <Histogram>::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::histogram::exponential_buckets;
+
+    #[test]
+    fn histogram_exemplar_above_threshold() {
+        let histogram = HistogramWithExemplars::new(exponential_buckets(1.0, 2.0, 10));
+
+        // Below the threshold, the label set closure is never invoked.
+        histogram.observe_with_exemplar_above(1.0, 2.0, || -> Vec<(String, String)> {
+            panic!("label set not to be constructed below threshold")
+        });
+        assert!(histogram.inner().exemplars.is_empty());
+
+        // At the threshold, the exemplar is recorded.
+        histogram.observe_with_exemplar_above(2.0, 2.0, || {
+            vec![("trace_id".to_string(), "1".to_string())]
+        });
+        // Above the threshold, the exemplar is recorded.
+        histogram.observe_with_exemplar_above(5.0, 2.0, || {
+            vec![("trace_id".to_string(), "2".to_string())]
+        });
+
+        let inner = histogram.inner();
+        assert_eq!(2, inner.exemplars.len());
+        assert_eq!(2.0, inner.exemplars[&1].value);
+        assert_eq!(5.0, inner.exemplars[&3].value);
+
+        // All values are observed, independent of the threshold.
+        let (sum, count, _) = inner.histogram.get();
+        assert_eq!(8.0, sum);
+        assert_eq!(3, count);
     }
 }
@@ -6,8 +6,13 @@ use crate::encoding::{EncodeLabelSet, EncodeMetric, MetricEncoder};
 
 use super::{MetricType, TypedMetric};
 use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// Representation of the OpenMetrics *MetricFamily* data type.
 ///
@@ -27,7 +32,7 @@ use std::sync::Arc;
 ///
 /// ```
 /// # use prometheus_client::encoding::text::encode;
-/// # use prometheus_client::metrics::counter::{Atomic, Counter};
+/// # use prometheus_client::metrics::counter::Counter;
 /// # use prometheus_client::metrics::family::Family;
 /// # use prometheus_client::registry::Registry;
 /// #
@@ -62,7 +67,7 @@ use std::sync::Arc;
 /// ```
 /// # use prometheus_client::encoding::{EncodeLabelSet, EncodeLabelValue};
 /// # use prometheus_client::encoding::text::encode;
-/// # use prometheus_client::metrics::counter::{Atomic, Counter};
+/// # use prometheus_client::metrics::counter::Counter;
 /// # use prometheus_client::metrics::family::Family;
 /// # use prometheus_client::registry::Registry;
 /// # use std::io::Write;
@@ -99,9 +104,25 @@ use std::sync::Arc;
 /// #                "# EOF\n";
 /// # assert_eq!(expected, buffer);
 /// ```
-// TODO: Consider exposing hash algorithm.
-pub struct Family<S, M, C = fn() -> M> {
-    metrics: Arc<RwLock<HashMap<S, M>>>,
+///
+/// # Generic over the hash algorithm
+///
+/// By default a [`Family`] uses the [`HashMap`] default hash algorithm, i.e.
+/// [`RandomState`]. For label sets that are expensive to hash, a faster hash
+/// algorithm can be provided via [`Family::with_hasher`] or
+/// [`Family::new_with_constructor_and_hasher`].
+///
+/// ```
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::metrics::family::Family;
+/// # use std::collections::hash_map::RandomState;
+/// #
+/// // Replace `RandomState` with e.g. `ahash::RandomState`.
+/// let family = Family::<Vec<(String, String)>, Counter, _, _>::with_hasher(RandomState::new());
+/// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+/// ```
+pub struct Family<S, M, C = fn() -> M, H = RandomState> {
+    metrics: Arc<RwLock<Shared<S, M, H>>>,
     /// Function that when called constructs a new metric.
     ///
     /// For most metric types this would simply be its [`Default`]
@@ -113,7 +134,105 @@ pub struct Family<S, M, C = fn() -> M> {
     constructor: C,
 }
 
-impl<S: std::fmt::Debug, M: std::fmt::Debug, C> std::fmt::Debug for Family<S, M, C> {
+/// The label sets and metrics of a [`Family`] along with its configuration,
+/// shared between all clones of the [`Family`].
+struct Shared<S, M, H> {
+    series: HashMap<S, Series<M>, H>,
+    /// Set via [`Family::with_expiry`].
+    expiry: Option<Expiry>,
+    /// Set via [`Family::with_max_series`].
+    max_series: Option<Arc<MaxSeries<S>>>,
+}
+
+impl<S, M, H> Shared<S, M, H> {
+    fn new(series: HashMap<S, Series<M>, H>) -> Self {
+        Self {
+            series,
+            expiry: None,
+            max_series: None,
+        }
+    }
+
+    /// Mark the given series as used for [`Family::with_expiry`].
+    fn touch<'a>(&self, series: &'a Series<M>) -> &'a M {
+        series.touch(self.expiry.as_ref())
+    }
+}
+
+impl<S: std::fmt::Debug, M: std::fmt::Debug, H> std::fmt::Debug for Shared<S, M, H> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shared")
+            .field("series", &self.series)
+            .field("expiry", &self.expiry)
+            .field("max_series", &self.max_series)
+            .finish()
+    }
+}
+
+/// A metric of a [`Family`] along with the time of its last access.
+#[derive(Debug)]
+struct Series<M> {
+    metric: M,
+    /// Milliseconds since [`Expiry::epoch`] at the last access. Only updated
+    /// when expiry is enabled.
+    touched: AtomicUsize,
+}
+
+impl<M> Series<M> {
+    fn new(metric: M) -> Self {
+        Self {
+            metric,
+            touched: AtomicUsize::new(0),
+        }
+    }
+
+    fn touch(&self, expiry: Option<&Expiry>) -> &M {
+        if let Some(expiry) = expiry {
+            self.touched.store(expiry.now(), Ordering::Relaxed);
+        }
+        &self.metric
+    }
+}
+
+#[derive(Debug)]
+struct MaxSeries<S> {
+    limit: usize,
+    overflow: S,
+}
+
+/// Outcome of [`Family::admit`].
+enum Admission<'a, Q: ?Sized> {
+    /// The label set may be created.
+    Admitted(&'a Q),
+    /// The limit set via [`Family::with_max_series`] is reached, thus the
+    /// overflow label set is to be used instead.
+    Overflow(&'a Q),
+}
+
+impl<'a, Q: ?Sized> Admission<'a, Q> {
+    /// The label set to create a metric for.
+    fn label_set(&self) -> &'a Q {
+        match self {
+            Admission::Admitted(label_set) | Admission::Overflow(label_set) => label_set,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Expiry {
+    ttl: Duration,
+    epoch: Instant,
+}
+
+impl Expiry {
+    fn now(&self) -> usize {
+        // Wraps after ~49 days on 32-bit platforms, in which case idle times
+        // are computed modulo that period.
+        self.epoch.elapsed().as_millis() as usize
+    }
+}
+
+impl<S: std::fmt::Debug, M: std::fmt::Debug, C, H> std::fmt::Debug for Family<S, M, C, H> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Family")
             .field("metrics", &self.metrics)
@@ -168,10 +287,70 @@ impl<M, F: Fn() -> M> MetricConstructor<M> for F {
     }
 }
 
-impl<S: Clone + std::hash::Hash + Eq, M: Default> Default for Family<S, M> {
+/// A fallible constructor for creating new metrics in a [`Family`] when calling
+/// [`Family::try_get_or_create`], e.g. for constructors validating their
+/// configuration. Such constructor is provided via
+/// [`Family::new_with_constructor`].
+///
+/// Encoding a [`Family`] only reads the existing metrics, i.e. neither
+/// [`MetricConstructor::new_metric`] nor
+/// [`TryMetricConstructor::try_new_metric`] is called when encoding.
+///
+/// ```
+/// # use prometheus_client::metrics::family::{Family, TryMetricConstructor};
+/// # use prometheus_client::metrics::histogram::Histogram;
+/// struct ConfiguredBuckets {
+///     buckets: Vec<f64>,
+/// }
+///
+/// impl TryMetricConstructor<Histogram> for ConfiguredBuckets {
+///     type Error = String;
+///
+///     fn try_new_metric(&self) -> Result<Histogram, String> {
+///         if !self.buckets.windows(2).all(|w| w[0] < w[1]) {
+///             return Err(format!("buckets {:?} are not increasing", self.buckets));
+///         }
+///         Ok(Histogram::new(self.buckets.iter().cloned()))
+///     }
+/// }
+///
+/// let family = Family::<(), Histogram, _>::new_with_constructor(ConfiguredBuckets {
+///     buckets: vec![10.0, 1.0],
+/// });
+/// assert!(family.try_get_or_create(&()).is_err());
+/// ```
+pub trait TryMetricConstructor<M> {
+    /// The error returned if the metric can not be constructed.
+    type Error;
+
+    /// Try to create a new instance of the metric type.
+    fn try_new_metric(&self) -> Result<M, Self::Error>;
+}
+
+/// As with [`MetricConstructor`], a closure can be provided directly.
+///
+/// ```
+/// # use prometheus_client::metrics::family::Family;
+/// # use prometheus_client::metrics::counter::Counter;
+/// let family = Family::<(), Counter, _>::new_with_constructor(|| {
+///     Ok::<_, std::io::Error>(Counter::default())
+/// });
+/// family.try_get_or_create(&()).unwrap().inc();
+/// ```
+impl<M, E, F: Fn() -> Result<M, E>> TryMetricConstructor<M> for F {
+    type Error = E;
+
+    fn try_new_metric(&self) -> Result<M, E> {
+        self()
+    }
+}
+
+impl<S: Clone + std::hash::Hash + Eq, M: Default, H: BuildHasher + Default> Default
+    for Family<S, M, fn() -> M, H>
+{
     fn default() -> Self {
         Self {
-            metrics: Arc::new(RwLock::new(Default::default())),
+            metrics: Arc::new(RwLock::new(Shared::new(Default::default()))),
             constructor: M::default,
         }
     }
@@ -201,55 +380,212 @@ impl<S: Clone + std::hash::Hash + Eq, M, C> Family<S, M, C> {
     /// ```
     pub fn new_with_constructor(constructor: C) -> Self {
         Self {
-            metrics: Arc::new(RwLock::new(Default::default())),
+            metrics: Arc::new(RwLock::new(Shared::new(Default::default()))),
             constructor,
         }
     }
 }
 
-impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>> Family<S, M, C> {
-    /// Access a metric with the given label set, creating it if one does not
-    /// yet exist.
+impl<S: Clone + std::hash::Hash + Eq, M: Default, H: BuildHasher> Family<S, M, fn() -> M, H> {
+    /// Create a metric family using the given hasher to hash label sets.
+    ///
+    /// See [`Family`] for an example.
+    pub fn with_hasher(hasher: H) -> Self {
+        Self::new_with_constructor_and_hasher(M::default, hasher)
+    }
+}
+
+impl<S: Clone + std::hash::Hash + Eq, M, C, H: BuildHasher> Family<S, M, C, H> {
+    /// Create a metric family using a custom constructor to construct new
+    /// metrics and the given hasher to hash label sets.
+    ///
+    /// See [`Family::new_with_constructor`] and [`Family::with_hasher`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::family::Family;
+    /// # use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+    /// # use std::collections::hash_map::RandomState;
+    /// Family::<Vec<(String, String)>, Histogram, _, _>::new_with_constructor_and_hasher(
+    ///     || Histogram::new(exponential_buckets(1.0, 2.0, 10)),
+    ///     RandomState::new(),
+    /// );
+    /// ```
+    pub fn new_with_constructor_and_hasher(constructor: C, hasher: H) -> Self {
+        Self {
+            metrics: Arc::new(RwLock::new(Shared::new(HashMap::with_hasher(hasher)))),
+            constructor,
+        }
+    }
+
+    /// Enable expiry of label sets that have not been accessed for at least
+    /// `ttl`, e.g. to bound the memory of a [`Family`] keyed by high
+    /// cardinality values such as client IP addresses.
+    ///
+    /// Accessing a label set via [`Family::get_or_create`] (and its variants)
+    /// or [`Family::get`] marks it as used. Note that updating a metric
+    /// through a handle obtained earlier, e.g. via
+    /// [`Family::get_or_create_owned`], does not. Idle label sets are removed
+    /// by calling [`Family::expire_idle`], e.g. periodically or before
+    /// encoding.
+    ///
+    /// Applies to all clones of the [`Family`], including those taken
+    /// earlier.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::metrics::family::Family;
+    /// # use std::time::Duration;
     /// #
-    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    /// let family = Family::<Vec<(String, String)>, Counter>::default()
+    ///     .with_expiry(Duration::from_secs(15 * 60));
+    /// family.get_or_create(&vec![("client".to_owned(), "10.0.0.1".to_owned())]).inc();
     ///
-    /// // Will create the metric with label `method="GET"` on first call and
-    /// // return a reference.
-    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    /// // Nothing to expire just yet.
+    /// assert_eq!(0, family.expire_idle());
+    /// ```
+    pub fn with_expiry(self, ttl: Duration) -> Self {
+        self.metrics.write().expiry = Some(Expiry {
+            ttl,
+            epoch: Instant::now(),
+        });
+        self
+    }
+
+    /// Limit the number of label sets of the [`Family`] to `limit`, e.g. to
+    /// protect against runaway cardinality caused by unbounded label values.
+    ///
+    /// Once the limit is reached, [`Family::get_or_create`] (and its variants)
+    /// no longer create new label sets. Instead, they return the metric of the
+    /// `overflow` label set, creating it if need be. Existing label sets keep
+    /// being returned as usual. The `overflow` label set does not count
+    /// towards the limit, thus the [`Family`] holds at most `limit + 1` label
+    /// sets. Label sets removed, e.g. via [`Family::remove`] or
+    /// [`Family::expire_idle`], make room for new ones.
+    ///
+    /// As with [`Family::with_expiry`], the limit is shared with all clones of
+    /// the [`Family`].
     ///
-    /// // Will return a reference to the existing metric on all subsequent
-    /// // calls.
-    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
     /// ```
-    pub fn get_or_create(&self, label_set: &S) -> MappedRwLockReadGuard<M> {
-        if let Some(metric) = self.get(label_set) {
-            return metric;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default()
+    ///     .with_max_series(2, vec![("path".to_owned(), "overflow".to_owned())]);
+    ///
+    /// for path in ["/", "/login", "/a", "/b"] {
+    ///     family.get_or_create(&vec![("path".to_owned(), path.to_owned())]).inc();
+    /// }
+    ///
+    /// let overflow = family.get(&vec![("path".to_owned(), "overflow".to_owned())]);
+    /// assert_eq!(2, overflow.unwrap().get());
+    /// assert_eq!(3, family.len());
+    /// ```
+    pub fn with_max_series(self, limit: usize, overflow: S) -> Self {
+        self.metrics.write().max_series = Some(Arc::new(MaxSeries { limit, overflow }));
+        self
+    }
+
+    /// Whether a metric may be created for `label_set`, or the overflow label
+    /// set is to be used instead, given the `max_series` of `metrics`.
+    ///
+    /// Takes `max_series` separately, cloned out of `metrics`, so the
+    /// returned label set does not borrow the lock guard of `metrics`.
+    fn admit<'a, Q>(
+        max_series: Option<&'a MaxSeries<S>>,
+        metrics: &Shared<S, M, H>,
+        label_set: &'a Q,
+    ) -> Admission<'a, Q>
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match max_series {
+            Some(max_series) if !metrics.series.contains_key(label_set) => {
+                let len = metrics.series.len()
+                    - usize::from(metrics.series.contains_key(max_series.overflow.borrow()));
+                if len >= max_series.limit {
+                    Admission::Overflow(max_series.overflow.borrow())
+                } else {
+                    Admission::Admitted(label_set)
+                }
+            }
+            _ => Admission::Admitted(label_set),
         }
+    }
 
-        let mut write_guard = self.metrics.write();
+    /// Remove all label sets that have not been accessed within the expiry
+    /// duration set via [`Family::with_expiry`], returning the number of
+    /// removed label sets.
+    ///
+    /// Does nothing if expiry is not enabled. Waits for outstanding guards
+    /// returned by e.g. [`Family::get_or_create`] to be dropped.
+    pub fn expire_idle(&self) -> usize {
+        let mut metrics = self.metrics.write();
+        let expiry = match metrics.expiry {
+            Some(expiry) => expiry,
+            None => return 0,
+        };
 
-        write_guard
-            .entry(label_set.clone())
-            .or_insert_with(|| self.constructor.new_metric());
+        let now = expiry.now();
+        let len = metrics.series.len();
+        metrics.series.retain(|_, series| {
+            let idle = now.wrapping_sub(series.touched.load(Ordering::Relaxed));
+            (idle as u128) < expiry.ttl.as_millis()
+        });
+        len - metrics.series.len()
+    }
 
-        let read_guard = RwLockWriteGuard::downgrade(write_guard);
+    /// Lock the metric family for reading, allowing to iterate all label sets
+    /// and their metrics.
+    ///
+    /// The returned [`FamilyGuard`] holds the read lock until dropped. Creating
+    /// new label sets via [`Family::get_or_create`] while holding it may
+    /// deadlock.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    ///
+    /// for (labels, counter) in &family.iter() {
+    ///     println!("{labels:?}: {}", counter.get());
+    /// }
+    /// ```
+    pub fn iter(&self) -> FamilyGuard<'_, S, M, H> {
+        FamilyGuard {
+            guard: self.metrics.read(),
+        }
+    }
 
-        RwLockReadGuard::map(read_guard, |metrics| {
-            metrics
-                .get(label_set)
-                .expect("Metric to exist after creating it.")
-        })
+    /// Call `f` with each label set and its metric.
+    ///
+    /// Holds the read lock for the duration of the iteration. See
+    /// [`Family::iter`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    ///
+    /// let mut total = 0;
+    /// family.for_each(|_labels, counter| total += counter.get());
+    /// assert_eq!(1, total);
+    /// ```
+    pub fn for_each(&self, mut f: impl FnMut(&S, &M)) {
+        for (label_set, series) in self.metrics.read().series.iter() {
+            f(label_set, &series.metric)
+        }
     }
 
     /// Access a metric with the given label set, returning None if one
     /// does not yet exist.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::metrics::family::Family;
     /// #
     /// let family = Family::<Vec<(String, String)>, Counter>::default();
@@ -258,8 +594,34 @@ impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>> Family<S, M, C
     ///     metric.inc();
     /// };
     /// ```
-    pub fn get(&self, label_set: &S) -> Option<MappedRwLockReadGuard<M>> {
-        RwLockReadGuard::try_map(self.metrics.read(), |metrics| metrics.get(label_set)).ok()
+    pub fn get(&self, label_set: &S) -> Option<MappedRwLockReadGuard<'_, M>> {
+        RwLockReadGuard::try_map(self.metrics.read(), |metrics| {
+            metrics
+                .series
+                .get(label_set)
+                .map(|series| metrics.touch(series))
+        })
+        .ok()
+    }
+
+    /// Whether a metric with the given label set exists, without creating it.
+    ///
+    /// In contrast to [`Family::get`], the read lock is released right away
+    /// and the series is not marked as used for [`Family::with_expiry`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    /// let labels = vec![("method".to_owned(), "GET".to_owned())];
+    ///
+    /// assert!(!family.contains(&labels));
+    /// family.get_or_create(&labels).inc();
+    /// assert!(family.contains(&labels));
+    /// ```
+    pub fn contains(&self, label_set: &S) -> bool {
+        self.metrics.read().series.contains_key(label_set)
     }
 
     /// Remove a label set from the metric family.
@@ -267,7 +629,7 @@ impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>> Family<S, M, C
     /// Returns a bool indicating if a label set was removed or not.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::metrics::family::Family;
     /// #
     /// let family = Family::<Vec<(String, String)>, Counter>::default();
@@ -281,13 +643,13 @@ impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>> Family<S, M, C
     /// assert!(family.remove(&vec![("method".to_owned(), "GET".to_owned())]));
     /// ```
     pub fn remove(&self, label_set: &S) -> bool {
-        self.metrics.write().remove(label_set).is_some()
+        self.metrics.write().series.remove(label_set).is_some()
     }
 
     /// Clear all label sets from the metric family.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::metrics::family::Family;
     /// #
     /// let family = Family::<Vec<(String, String)>, Counter>::default();
@@ -300,15 +662,296 @@ impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>> Family<S, M, C
     /// family.clear();
     /// ```
     pub fn clear(&self) {
-        self.metrics.write().clear()
+        self.metrics.write().series.clear()
+    }
+
+    /// Number of label sets in the [`Family`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    /// assert!(family.is_empty());
+    ///
+    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    /// assert_eq!(1, family.len());
+    /// ```
+    pub fn len(&self) -> usize {
+        self.metrics.read().series.len()
+    }
+
+    /// Whether the [`Family`] has no label sets.
+    pub fn is_empty(&self) -> bool {
+        self.metrics.read().series.is_empty()
+    }
+}
+
+impl<S: Clone + std::hash::Hash + Eq, M, C: MetricConstructor<M>, H: BuildHasher>
+    Family<S, M, C, H>
+{
+    /// Access a metric with the given label set, creating it if one does not
+    /// yet exist.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    ///
+    /// // Will create the metric with label `method="GET"` on first call and
+    /// // return a reference.
+    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    ///
+    /// // Will return a reference to the existing metric on all subsequent
+    /// // calls.
+    /// family.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]).inc();
+    /// ```
+    pub fn get_or_create(&self, label_set: &S) -> MappedRwLockReadGuard<'_, M> {
+        if let Some(metric) = self.get(label_set) {
+            return metric;
+        }
+
+        let mut write_guard = self.metrics.write();
+        let max_series = write_guard.max_series.clone();
+        let label_set = Self::admit(max_series.as_deref(), &write_guard, label_set).label_set();
+
+        write_guard
+            .series
+            .entry(label_set.clone())
+            .or_insert_with(|| Series::new(self.constructor.new_metric()));
+
+        let read_guard = RwLockWriteGuard::downgrade(write_guard);
+
+        RwLockReadGuard::map(read_guard, |metrics| {
+            metrics.touch(
+                metrics
+                    .series
+                    .get(label_set)
+                    .expect("Metric to exist after creating it."),
+            )
+        })
+    }
+
+    /// Access a metric with the given borrowed label set, creating it if one
+    /// does not yet exist.
+    ///
+    /// As [`Family::get_or_create`], but accepting any borrowed form of the
+    /// label set, akin to [`HashMap::get`]. The owned label set is only
+    /// constructed when inserting a new metric, avoiding allocations on the
+    /// common path of accessing an existing metric.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    ///
+    /// let labels = [("method".to_owned(), "GET".to_owned())];
+    /// family.get_or_create_borrowed(&labels[..]).inc();
+    /// ```
+    pub fn get_or_create_borrowed<Q>(&self, label_set: &Q) -> MappedRwLockReadGuard<'_, M>
+    where
+        S: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = S> + ?Sized,
+    {
+        if let Ok(metric) = RwLockReadGuard::try_map(self.metrics.read(), |metrics| {
+            metrics
+                .series
+                .get(label_set)
+                .map(|series| metrics.touch(series))
+        }) {
+            return metric;
+        }
+
+        let mut write_guard = self.metrics.write();
+        let max_series = write_guard.max_series.clone();
+        let label_set = Self::admit(max_series.as_deref(), &write_guard, label_set).label_set();
+
+        if !write_guard.series.contains_key(label_set) {
+            write_guard.series.insert(
+                label_set.to_owned(),
+                Series::new(self.constructor.new_metric()),
+            );
+        }
+
+        let read_guard = RwLockWriteGuard::downgrade(write_guard);
+
+        RwLockReadGuard::map(read_guard, |metrics| {
+            metrics.touch(
+                metrics
+                    .series
+                    .get(label_set)
+                    .expect("Metric to exist after creating it."),
+            )
+        })
+    }
+
+    /// Access a metric with the given label set, creating it if one does not
+    /// yet exist, returning a clone of the metric.
+    ///
+    /// In contrast to [`Family::get_or_create`], the read lock of the
+    /// [`Family`] is released before returning. For metric types sharing their
+    /// state between clones, e.g. [`Counter`](crate::metrics::counter::Counter)
+    /// or [`Gauge`](crate::metrics::gauge::Gauge), the returned handle can thus
+    /// be kept around without blocking encoding or the creation of new label
+    /// sets.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    ///
+    /// let counter = family.get_or_create_owned(&vec![("method".to_owned(), "GET".to_owned())]);
+    /// counter.inc();
+    ///
+    /// // Does not deadlock, as `counter` does not hold a lock.
+    /// family.get_or_create(&vec![("method".to_owned(), "POST".to_owned())]).inc();
+    /// ```
+    pub fn get_or_create_owned(&self, label_set: &S) -> M
+    where
+        M: Clone,
+    {
+        self.get_or_create(label_set).clone()
+    }
+
+    /// Create a metric for each of the given label sets that does not yet
+    /// exist, e.g. to expose all known label sets at zero right from startup
+    /// instead of only after their first observation.
+    ///
+    /// Equivalent to calling [`Family::get_or_create`] for each label set,
+    /// but taking the write lock only once. Label sets exceeding the limit set
+    /// via [`Family::with_max_series`] are skipped.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// #
+    /// let family = Family::<Vec<(String, String)>, Counter>::default();
+    ///
+    /// family.init(["GET", "POST"].map(|method| vec![("method".to_owned(), method.to_owned())]));
+    /// assert_eq!(2, family.len());
+    /// ```
+    pub fn init(&self, label_sets: impl IntoIterator<Item = S>) {
+        let mut metrics = self.metrics.write();
+        let max_series = metrics.max_series.clone();
+        for label_set in label_sets {
+            if let Admission::Overflow(_) = Self::admit(max_series.as_deref(), &metrics, &label_set)
+            {
+                continue;
+            }
+            let metrics = &mut *metrics;
+            let series = metrics
+                .series
+                .entry(label_set)
+                .or_insert_with(|| Series::new(self.constructor.new_metric()));
+            series.touch(metrics.expiry.as_ref());
+        }
+    }
+}
+
+impl<S: Clone + std::hash::Hash + Eq, M, C: TryMetricConstructor<M>, H: BuildHasher>
+    Family<S, M, C, H>
+{
+    /// Access a metric with the given label set, creating it via the
+    /// [`TryMetricConstructor`] if one does not yet exist, returning the
+    /// constructor's error if that fails.
+    ///
+    /// Nothing is inserted on failure, thus the next call retries constructing
+    /// the metric. See [`TryMetricConstructor`] for an example.
+    pub fn try_get_or_create(
+        &self,
+        label_set: &S,
+    ) -> Result<MappedRwLockReadGuard<'_, M>, C::Error> {
+        if let Some(metric) = self.get(label_set) {
+            return Ok(metric);
+        }
+
+        let mut write_guard = self.metrics.write();
+        let max_series = write_guard.max_series.clone();
+        let label_set = Self::admit(max_series.as_deref(), &write_guard, label_set).label_set();
+
+        if !write_guard.series.contains_key(label_set) {
+            let metric = self.constructor.try_new_metric()?;
+            write_guard
+                .series
+                .insert(label_set.clone(), Series::new(metric));
+        }
+
+        let read_guard = RwLockWriteGuard::downgrade(write_guard);
+
+        Ok(RwLockReadGuard::map(read_guard, |metrics| {
+            metrics.touch(
+                metrics
+                    .series
+                    .get(label_set)
+                    .expect("Metric to exist after creating it."),
+            )
+        }))
+    }
+}
+
+/// Read access to all label sets and metrics of a [`Family`].
+///
+/// Returned by [`Family::iter`]. Holds the read lock of the [`Family`] until
+/// dropped.
+#[derive(Debug)]
+pub struct FamilyGuard<'a, S, M, H = RandomState> {
+    guard: RwLockReadGuard<'a, Shared<S, M, H>>,
+}
+
+impl<S, M, H> FamilyGuard<'_, S, M, H> {
+    /// Iterate all label sets and their metrics, in arbitrary order.
+    pub fn iter(&self) -> FamilyIter<'_, S, M> {
+        FamilyIter {
+            inner: self.guard.series.iter(),
+        }
+    }
+
+    /// Number of label sets in the [`Family`].
+    pub fn len(&self) -> usize {
+        self.guard.series.len()
+    }
+
+    /// Whether the [`Family`] has no label sets.
+    pub fn is_empty(&self) -> bool {
+        self.guard.series.is_empty()
     }
+}
+
+impl<'b, S, M, H> IntoIterator for &'b FamilyGuard<'_, S, M, H> {
+    type Item = (&'b S, &'b M);
+    type IntoIter = FamilyIter<'b, S, M>;
 
-    pub(crate) fn read(&self) -> RwLockReadGuard<HashMap<S, M>> {
-        self.metrics.read()
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
-impl<S, M, C: Clone> Clone for Family<S, M, C> {
+/// Iterator over the label sets and metrics of a [`Family`].
+///
+/// Returned by [`FamilyGuard::iter`].
+#[derive(Debug)]
+pub struct FamilyIter<'a, S, M> {
+    inner: std::collections::hash_map::Iter<'a, S, Series<M>>,
+}
+
+impl<'a, S, M> Iterator for FamilyIter<'a, S, M> {
+    type Item = (&'a S, &'a M);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(label_set, series)| (label_set, &series.metric))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<S, M, C: Clone, H> Clone for Family<S, M, C, H> {
     fn clone(&self) -> Self {
         Family {
             metrics: self.metrics.clone(),
@@ -317,23 +960,33 @@ impl<S, M, C: Clone> Clone for Family<S, M, C> {
     }
 }
 
-impl<S, M: TypedMetric, C> TypedMetric for Family<S, M, C> {
+impl<S, M: TypedMetric, C, H> TypedMetric for Family<S, M, C, H> {
     const TYPE: MetricType = <M as TypedMetric>::TYPE;
 }
 
-impl<S, M, C> EncodeMetric for Family<S, M, C>
+impl<S, M, C, H> EncodeMetric for Family<S, M, C, H>
 where
     S: Clone + std::hash::Hash + Eq + EncodeLabelSet,
     M: EncodeMetric + TypedMetric,
-    C: MetricConstructor<M>,
+    H: BuildHasher,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        let guard = self.read();
-        for (label_set, m) in guard.iter() {
-            let encoder = encoder.encode_family(label_set)?;
-            m.encode(encoder)?;
-        }
-        Ok(())
+        let guard = self.metrics.read();
+        encoder.encode_series(
+            guard
+                .series
+                .iter()
+                .map(|(label_set, series)| (label_set, &series.metric)),
+        )
+    }
+
+    fn series(&self) -> usize {
+        self.metrics
+            .read()
+            .series
+            .values()
+            .map(|series| series.metric.series())
+            .sum()
     }
 
     fn metric_type(&self) -> MetricType {
@@ -468,6 +1121,206 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_or_create_owned() {
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+
+        let counter = family.get_or_create_owned(&vec![("method".to_string(), "GET".to_string())]);
+        counter.inc();
+
+        // Creating a new label set while holding `counter` must not deadlock.
+        family
+            .get_or_create(&vec![("method".to_string(), "POST".to_string())])
+            .inc();
+
+        assert_eq!(
+            1,
+            family
+                .get(&vec![("method".to_string(), "GET".to_string())])
+                .unwrap()
+                .get()
+        );
+    }
+
+    #[test]
+    fn init() {
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+        family
+            .get_or_create(&vec![("method".to_string(), "GET".to_string())])
+            .inc();
+
+        family.init(["GET", "POST"].map(|method| vec![("method".to_string(), method.to_string())]));
+
+        assert_eq!(2, family.len());
+        assert_eq!(
+            1,
+            family
+                .get(&vec![("method".to_string(), "GET".to_string())])
+                .unwrap()
+                .get()
+        );
+        assert_eq!(
+            0,
+            family
+                .get(&vec![("method".to_string(), "POST".to_string())])
+                .unwrap()
+                .get()
+        );
+    }
+
+    #[test]
+    fn max_series() {
+        let label_set = |path: &str| vec![("path".to_string(), path.to_string())];
+        let family =
+            Family::<Vec<(String, String)>, Counter>::default().with_max_series(2, label_set("*"));
+
+        family.get_or_create(&label_set("/a")).inc();
+        family.get_or_create(&label_set("/b")).inc();
+        family.get_or_create(&label_set("/c")).inc();
+        family.get_or_create_borrowed(&label_set("/d")[..]).inc();
+        family.init([label_set("/e")]);
+        // Existing label sets are still accessible once the limit is reached.
+        family.get_or_create(&label_set("/a")).inc();
+
+        assert_eq!(3, family.len());
+        assert_eq!(2, family.get(&label_set("/a")).unwrap().get());
+        assert_eq!(2, family.get(&label_set("*")).unwrap().get());
+        assert!(family.get(&label_set("/e")).is_none());
+
+        // Removing a label set makes room for a new one.
+        family.remove(&label_set("/b"));
+        family.get_or_create(&label_set("/f")).inc();
+        assert_eq!(1, family.get(&label_set("/f")).unwrap().get());
+
+        // The limit applies to clones taken earlier.
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+        let clone = family.clone();
+        let family = family.with_max_series(1, label_set("*"));
+        clone.get_or_create(&label_set("/a")).inc();
+        clone.get_or_create(&label_set("/b")).inc();
+        assert_eq!(2, family.len());
+        assert!(family.get(&label_set("*")).is_some());
+    }
+
+    #[test]
+    fn iter() {
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+        assert!(family.iter().is_empty());
+
+        family
+            .get_or_create(&vec![("method".to_string(), "GET".to_string())])
+            .inc();
+        family
+            .get_or_create(&vec![("method".to_string(), "POST".to_string())])
+            .inc_by(2);
+
+        let guard = family.iter();
+        assert_eq!(2, guard.len());
+        let mut values = guard
+            .iter()
+            .map(|(labels, counter)| (labels[0].1.as_str(), counter.get()))
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(vec![("GET", 1), ("POST", 2)], values);
+        drop(guard);
+
+        let mut total = 0;
+        family.for_each(|_, counter| total += counter.get());
+        assert_eq!(3, total);
+    }
+
+    #[test]
+    fn get_or_create_borrowed() {
+        let family = Family::<Vec<(String, String)>, Counter>::default();
+        let labels = [("method".to_string(), "GET".to_string())];
+
+        family.get_or_create_borrowed(&labels[..]).inc();
+        family.get_or_create_borrowed(&labels[..]).inc();
+
+        assert_eq!(2, family.get(&labels.to_vec()).unwrap().get());
+        assert_eq!(1, family.iter().len());
+    }
+
+    #[test]
+    fn expire_idle() {
+        let family = Family::<String, Counter>::default();
+        family.get_or_create(&"a".to_string()).inc();
+        // No-op without expiry.
+        assert_eq!(0, family.expire_idle());
+
+        let family = Family::<String, Counter>::default().with_expiry(Duration::ZERO);
+        family.get_or_create(&"a".to_string()).inc();
+        assert_eq!(1, family.expire_idle());
+        assert!(family.iter().is_empty());
+
+        let family = Family::<String, Counter>::default().with_expiry(Duration::from_millis(100));
+        family.get_or_create(&"a".to_string()).inc();
+        family.get_or_create(&"b".to_string()).inc();
+        std::thread::sleep(Duration::from_millis(200));
+        family.get(&"a".to_string()).unwrap().inc();
+
+        assert_eq!(1, family.expire_idle());
+        assert_eq!(2, family.get(&"a".to_string()).unwrap().get());
+        assert!(family.get(&"b".to_string()).is_none());
+
+        // Expiry applies to clones taken earlier.
+        let family = Family::<String, Counter>::default();
+        let clone = family.clone();
+        let family = family.with_expiry(Duration::ZERO);
+        family.get_or_create(&"a".to_string()).inc();
+        assert_eq!(1, clone.expire_idle());
+    }
+
+    #[test]
+    fn with_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let family = Family::<String, Counter, _, BuildHasherDefault<DefaultHasher>>::with_hasher(
+            Default::default(),
+        );
+        family.get_or_create(&"GET".to_string()).inc();
+        family.get_or_create(&"GET".to_string()).inc();
+
+        assert_eq!(2, family.get(&"GET".to_string()).unwrap().get());
+        assert_eq!(1, family.iter().len());
+    }
+
+    #[test]
+    fn contains() {
+        let family = Family::<String, Counter>::default();
+        assert!(!family.contains(&"GET".to_string()));
+
+        family.get_or_create(&"GET".to_string()).inc();
+        assert!(family.contains(&"GET".to_string()));
+        assert!(!family.contains(&"POST".to_string()));
+
+        family.remove(&"GET".to_string());
+        assert!(!family.contains(&"GET".to_string()));
+    }
+
+    #[test]
+    fn try_get_or_create() {
+        let fail = std::sync::atomic::AtomicBool::new(true);
+        let family = Family::<String, Counter, _>::new_with_constructor(|| {
+            if fail.load(Ordering::Relaxed) {
+                Err("not configured")
+            } else {
+                Ok(Counter::default())
+            }
+        });
+
+        assert_eq!(
+            "not configured",
+            family.try_get_or_create(&"GET".to_string()).unwrap_err()
+        );
+        assert!(family.is_empty());
+
+        fail.store(false, Ordering::Relaxed);
+        family.try_get_or_create(&"GET".to_string()).unwrap().inc();
+        assert_eq!(1, family.get(&"GET".to_string()).unwrap().get());
+    }
+
     #[test]
     fn test_get() {
         let family = Family::<Vec<(String, String)>, Counter>::default();
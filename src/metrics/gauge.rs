@@ -2,7 +2,7 @@
 //!
 //! See [`Gauge`] for details.
 
-use crate::encoding::{EncodeGaugeValue, EncodeMetric, MetricEncoder};
+use crate::encoding::{EncodeGaugeValue, EncodeLabelSet, EncodeMetric, MetricEncoder, NoLabelSet};
 
 use super::{MetricType, TypedMetric};
 use std::marker::PhantomData;
@@ -40,6 +40,18 @@ use std::sync::Arc;
 /// gauge.set(42.0);
 /// let _value: f64 = gauge.get();
 /// ```
+///
+/// ## Using [`AtomicU32`] as storage and [`f32`] on the interface
+///
+/// See the [module documentation](super#32-bit-storage) for the trade-offs.
+///
+/// ```
+/// # use prometheus_client::metrics::gauge::Gauge;
+/// # use std::sync::atomic::AtomicU32;
+/// let gauge = Gauge::<f32, AtomicU32>::default();
+/// gauge.set(-0.5);
+/// let _value: f32 = gauge.get();
+/// ```
 #[cfg(target_has_atomic = "64")]
 #[derive(Debug)]
 pub struct Gauge<N = i64, A = AtomicI64> {
@@ -104,15 +116,91 @@ impl<N, A: Atomic<N>> Gauge<N, A> {
         self.value.get()
     }
 
+    /// Set the [`Gauge`] to `v` if `v` is greater than its current value,
+    /// returning the resulting value, e.g. to track a high water mark.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::gauge::Gauge;
+    /// let peak_connections: Gauge = Gauge::default();
+    /// assert_eq!(5, peak_connections.set_max(5));
+    /// assert_eq!(5, peak_connections.set_max(3));
+    /// ```
+    pub fn set_max(&self, v: N) -> N
+    where
+        N: PartialOrd + Copy,
+    {
+        self.value.set_max(v)
+    }
+
+    /// Set the [`Gauge`] to `v` if `v` is less than its current value,
+    /// returning the resulting value, e.g. to track a low water mark.
+    pub fn set_min(&self, v: N) -> N
+    where
+        N: PartialOrd + Copy,
+    {
+        self.value.set_min(v)
+    }
+
+    /// Create a new [`Gauge`] set to `value`, e.g. to restore a gauge
+    /// persisted via [`Gauge::get`] across a graceful restart.
+    ///
+    /// With the `serde` feature enabled, [`Gauge`] implements `Serialize` and
+    /// `Deserialize` based on its current value.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::gauge::Gauge;
+    /// let gauge: Gauge = Gauge::from_snapshot(-42);
+    /// assert_eq!(-42, gauge.get());
+    /// ```
+    pub fn from_snapshot(value: N) -> Self
+    where
+        A: Default,
+    {
+        let gauge = Self::default();
+        gauge.set(value);
+        gauge
+    }
+
     /// Exposes the inner atomic type of the [`Gauge`].
     ///
     /// This should only be used for advanced use-cases which are not directly
-    /// supported by the library.
+    /// supported by the library, e.g. external code updating the same atomic.
+    ///
+    /// In contrast to [`Counter::inner`](crate::metrics::counter::Counter::inner)
+    /// there is no monotonicity to uphold, as a gauge may move freely. Note that
+    /// floating point gauges store the bit pattern of the value, e.g.
+    /// [`f64::to_bits`] for `Gauge<f64, AtomicU64>`.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::gauge::Gauge;
+    /// # use std::sync::atomic::Ordering;
+    /// let gauge: Gauge = Gauge::default();
+    /// gauge.inner().fetch_sub(3, Ordering::Relaxed);
+    /// assert_eq!(-3, gauge.get());
+    /// ```
     pub fn inner(&self) -> &A {
         &self.value
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<N: serde::Serialize, A: Atomic<N>> serde::Serialize for Gauge<N, A> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.get().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de, N: serde::Deserialize<'de>, A: Atomic<N> + Default> serde::Deserialize<'de>
+    for Gauge<N, A>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        N::deserialize(deserializer).map(Self::from_snapshot)
+    }
+}
+
 /// Atomic operations for a [`Gauge`] value store.
 pub trait Atomic<N> {
     /// Increase the value by `1`.
@@ -132,253 +220,377 @@ pub trait Atomic<N> {
 
     /// Get the value.
     fn get(&self) -> N;
-}
-
-impl Atomic<i32> for AtomicI32 {
-    fn inc(&self) -> i32 {
-        self.inc_by(1)
-    }
-
-    fn inc_by(&self, v: i32) -> i32 {
-        self.fetch_add(v, Ordering::Relaxed)
-    }
-
-    fn dec(&self) -> i32 {
-        self.dec_by(1)
-    }
 
-    fn dec_by(&self, v: i32) -> i32 {
-        self.fetch_sub(v, Ordering::Relaxed)
-    }
-
-    fn set(&self, v: i32) -> i32 {
-        self.swap(v, Ordering::Relaxed)
+    /// Set the value to the maximum of the current value and `v`, returning
+    /// the resulting value.
+    ///
+    /// The default implementation is built on [`Atomic::get`] and
+    /// [`Atomic::set`], restoring a larger value set concurrently. Readers
+    /// may thus briefly observe a smaller value. Implementations with a
+    /// compare-and-swap operation should override it.
+    fn set_max(&self, v: N) -> N
+    where
+        N: PartialOrd + Copy,
+    {
+        let mut v = v;
+        loop {
+            let current = self.get();
+            if v.partial_cmp(&current) != Some(std::cmp::Ordering::Greater) {
+                return current;
+            }
+            let previous = self.set(v);
+            if previous.partial_cmp(&v) != Some(std::cmp::Ordering::Greater) {
+                return v;
+            }
+            // A larger value was set concurrently, restore it.
+            v = previous;
+        }
     }
 
-    fn get(&self) -> i32 {
-        self.load(Ordering::Relaxed)
+    /// Set the value to the minimum of the current value and `v`, returning
+    /// the resulting value.
+    ///
+    /// See [`Atomic::set_max`] for the default implementation.
+    fn set_min(&self, v: N) -> N
+    where
+        N: PartialOrd + Copy,
+    {
+        let mut v = v;
+        loop {
+            let current = self.get();
+            if v.partial_cmp(&current) != Some(std::cmp::Ordering::Less) {
+                return current;
+            }
+            let previous = self.set(v);
+            if previous.partial_cmp(&v) != Some(std::cmp::Ordering::Less) {
+                return v;
+            }
+            // A smaller value was set concurrently, restore it.
+            v = previous;
+        }
     }
 }
 
-impl Atomic<u32> for AtomicU32 {
-    fn inc(&self) -> u32 {
-        self.inc_by(1)
-    }
-
-    fn inc_by(&self, v: u32) -> u32 {
-        self.fetch_add(v, Ordering::Relaxed)
-    }
-
-    fn dec(&self) -> u32 {
-        self.dec_by(1)
-    }
+macro_rules! impl_atomic_integer {
+    ($($num:ident => $atomic:ident),*) => {$(
+        impl Atomic<$num> for $atomic {
+            fn inc(&self) -> $num {
+                self.inc_by(1)
+            }
 
-    fn dec_by(&self, v: u32) -> u32 {
-        self.fetch_sub(v, Ordering::Relaxed)
-    }
+            fn inc_by(&self, v: $num) -> $num {
+                self.fetch_add(v, Ordering::Relaxed)
+            }
 
-    fn set(&self, v: u32) -> u32 {
-        self.swap(v, Ordering::Relaxed)
-    }
+            fn dec(&self) -> $num {
+                self.dec_by(1)
+            }
 
-    fn get(&self) -> u32 {
-        self.load(Ordering::Relaxed)
-    }
-}
+            fn dec_by(&self, v: $num) -> $num {
+                self.fetch_sub(v, Ordering::Relaxed)
+            }
 
-#[cfg(target_has_atomic = "64")]
-impl Atomic<i64> for AtomicI64 {
-    fn inc(&self) -> i64 {
-        self.inc_by(1)
-    }
+            fn set(&self, v: $num) -> $num {
+                self.swap(v, Ordering::Relaxed)
+            }
 
-    fn inc_by(&self, v: i64) -> i64 {
-        self.fetch_add(v, Ordering::Relaxed)
-    }
+            fn get(&self) -> $num {
+                self.load(Ordering::Relaxed)
+            }
 
-    fn dec(&self) -> i64 {
-        self.dec_by(1)
-    }
+            fn set_max(&self, v: $num) -> $num
+            where
+                $num: PartialOrd + Copy,
+            {
+                self.fetch_max(v, Ordering::Relaxed).max(v)
+            }
 
-    fn dec_by(&self, v: i64) -> i64 {
-        self.fetch_sub(v, Ordering::Relaxed)
-    }
+            fn set_min(&self, v: $num) -> $num
+            where
+                $num: PartialOrd + Copy,
+            {
+                self.fetch_min(v, Ordering::Relaxed).min(v)
+            }
+        }
+    )*};
+}
 
-    fn set(&self, v: i64) -> i64 {
-        self.swap(v, Ordering::Relaxed)
-    }
+/// Implements [`Atomic`] for a float type, stored as its bit pattern in the
+/// unsigned atomic integer of the same width.
+macro_rules! impl_atomic_float {
+    ($($float:ident => $atomic:ident),*) => {$(
+        const _: () = {
+            /// Atomically replace the stored value with `f` of it, returning
+            /// the previous value.
+            fn update(atomic: &$atomic, f: impl Fn($float) -> $float) -> $float {
+                let previous = atomic
+                    .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                        Some(f($float::from_bits(bits)).to_bits())
+                    })
+                    .unwrap_or_else(|bits| bits);
+                $float::from_bits(previous)
+            }
 
-    fn get(&self) -> i64 {
-        self.load(Ordering::Relaxed)
-    }
+            impl Atomic<$float> for $atomic {
+                fn inc(&self) -> $float {
+                    self.inc_by(1.0)
+                }
+
+                fn inc_by(&self, v: $float) -> $float {
+                    update(self, |old| old + v)
+                }
+
+                fn dec(&self) -> $float {
+                    self.dec_by(1.0)
+                }
+
+                fn dec_by(&self, v: $float) -> $float {
+                    update(self, |old| old - v)
+                }
+
+                fn set(&self, v: $float) -> $float {
+                    $float::from_bits(self.swap(v.to_bits(), Ordering::Relaxed))
+                }
+
+                fn get(&self) -> $float {
+                    $float::from_bits(self.load(Ordering::Relaxed))
+                }
+
+                fn set_max(&self, v: $float) -> $float
+                where
+                    $float: PartialOrd + Copy,
+                {
+                    update(self, |old| old.max(v)).max(v)
+                }
+
+                fn set_min(&self, v: $float) -> $float
+                where
+                    $float: PartialOrd + Copy,
+                {
+                    update(self, |old| old.min(v)).min(v)
+                }
+            }
+        };
+    )*};
 }
 
-#[cfg(target_has_atomic = "64")]
-impl Atomic<u64> for AtomicU64 {
-    fn inc(&self) -> u64 {
-        self.inc_by(1)
-    }
+impl_atomic_integer!(i32 => AtomicI32, u32 => AtomicU32);
+impl_atomic_float!(f32 => AtomicU32);
 
-    fn inc_by(&self, v: u64) -> u64 {
-        self.fetch_add(v, Ordering::Relaxed)
-    }
+#[cfg(target_has_atomic = "64")]
+impl_atomic_integer!(i64 => AtomicI64, u64 => AtomicU64);
+#[cfg(target_has_atomic = "64")]
+impl_atomic_float!(f64 => AtomicU64);
 
-    fn dec(&self) -> u64 {
-        self.dec_by(1)
-    }
+impl<N, A> TypedMetric for Gauge<N, A> {
+    const TYPE: MetricType = MetricType::Gauge;
+}
 
-    fn dec_by(&self, v: u64) -> u64 {
-        self.fetch_sub(v, Ordering::Relaxed)
+impl<N, A> EncodeMetric for Gauge<N, A>
+where
+    N: EncodeGaugeValue,
+    A: Atomic<N>,
+{
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode_gauge(&self.get())
     }
-
-    fn set(&self, v: u64) -> u64 {
-        self.swap(v, Ordering::Relaxed)
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
     }
 
-    fn get(&self) -> u64 {
-        self.load(Ordering::Relaxed)
+    fn as_read_gauge(&self) -> Option<&dyn ReadGauge> {
+        Some(self)
     }
 }
 
-#[cfg(target_has_atomic = "64")]
-impl Atomic<f64> for AtomicU64 {
-    fn inc(&self) -> f64 {
-        self.inc_by(1.0)
-    }
-
-    fn inc_by(&self, v: f64) -> f64 {
-        let mut old_u64 = self.load(Ordering::Relaxed);
-        let mut old_f64;
-        loop {
-            old_f64 = f64::from_bits(old_u64);
-            let new = f64::to_bits(old_f64 + v);
-            match self.compare_exchange_weak(old_u64, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u64 = x,
-            }
-        }
+/// Read the current value of a [`Gauge`] as [`f64`], independent of its value
+/// and storage type.
+///
+/// Useful to uniformly inspect gauges of different types, e.g. in tests. A
+/// [`Gauge`] registered with a [`Registry`](crate::registry::Registry) is
+/// reachable via [`EncodeMetric::as_read_gauge`], e.g. from
+/// [`Registry::iter_metrics`](crate::registry::Registry::iter_metrics).
+///
+/// ```
+/// # use prometheus_client::metrics::gauge::{Gauge, ReadGauge};
+/// # use std::sync::atomic::{AtomicU32, AtomicU64};
+/// let connections: Gauge = Gauge::default();
+/// connections.set(3);
+/// let temperature = Gauge::<f64, AtomicU64>::default();
+/// temperature.set(21.5);
+/// let queue_size = Gauge::<u32, AtomicU32>::default();
+/// queue_size.set(7);
+///
+/// let gauges: [&dyn ReadGauge; 3] = [&connections, &temperature, &queue_size];
+/// let total: f64 = gauges.iter().map(|g| g.get_f64()).sum();
+/// assert_eq!(31.5, total);
+/// ```
+pub trait ReadGauge {
+    /// Get the current value of the gauge as [`f64`].
+    ///
+    /// Integer values beyond 2^53 may lose precision.
+    fn get_f64(&self) -> f64;
+}
 
-        old_f64
+impl<N, A> ReadGauge for Gauge<N, A>
+where
+    N: EncodeGaugeValue,
+    A: Atomic<N>,
+{
+    /// `NaN` if the [`EncodeGaugeValue`] implementation of `N` fails.
+    fn get_f64(&self) -> f64 {
+        crate::encoding::visitor::gauge_value_f64(&self.get()).unwrap_or(f64::NAN)
     }
+}
 
-    fn dec(&self) -> f64 {
-        self.dec_by(1.0)
-    }
+/// As a [`Gauge`], but constant, meaning it cannot change once created.
+///
+/// Needed for advanced use-cases, e.g. in combination with [`Collector`](crate::collector::Collector).
+#[derive(Debug)]
+pub struct ConstGauge<N = i64, S = NoLabelSet> {
+    value: N,
+    label_set: Option<S>,
+}
 
-    fn dec_by(&self, v: f64) -> f64 {
-        let mut old_u64 = self.load(Ordering::Relaxed);
-        let mut old_f64;
-        loop {
-            old_f64 = f64::from_bits(old_u64);
-            let new = f64::to_bits(old_f64 - v);
-            match self.compare_exchange_weak(old_u64, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u64 = x,
-            }
+impl<N: Default, S> Default for ConstGauge<N, S> {
+    fn default() -> Self {
+        Self {
+            value: Default::default(),
+            label_set: None,
         }
-
-        old_f64
-    }
-
-    fn set(&self, v: f64) -> f64 {
-        f64::from_bits(self.swap(f64::to_bits(v), Ordering::Relaxed))
-    }
-
-    fn get(&self) -> f64 {
-        f64::from_bits(self.load(Ordering::Relaxed))
     }
 }
 
-impl Atomic<f32> for AtomicU32 {
-    fn inc(&self) -> f32 {
-        self.inc_by(1.0)
-    }
-
-    fn inc_by(&self, v: f32) -> f32 {
-        let mut old_u32 = self.load(Ordering::Relaxed);
-        let mut old_f32;
-        loop {
-            old_f32 = f32::from_bits(old_u32);
-            let new = f32::to_bits(old_f32 + v);
-            match self.compare_exchange_weak(old_u32, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u32 = x,
-            }
+impl<N> ConstGauge<N> {
+    /// Creates a new [`ConstGauge`].
+    pub fn new(value: N) -> Self {
+        Self {
+            value,
+            label_set: None,
         }
-
-        old_f32
-    }
-
-    fn dec(&self) -> f32 {
-        self.dec_by(1.0)
     }
+}
 
-    fn dec_by(&self, v: f32) -> f32 {
-        let mut old_u32 = self.load(Ordering::Relaxed);
-        let mut old_f32;
-        loop {
-            old_f32 = f32::from_bits(old_u32);
-            let new = f32::to_bits(old_f32 - v);
-            match self.compare_exchange_weak(old_u32, new, Ordering::Relaxed, Ordering::Relaxed) {
-                Ok(_) => break,
-                Err(x) => old_u32 = x,
-            }
+impl<N, S> ConstGauge<N, S> {
+    /// Creates a new [`ConstGauge`] with the given label set, e.g. to expose one
+    /// sample per device from a [`Collector`](crate::collector::Collector).
+    ///
+    /// Encode multiple [`ConstGauge`]s with different label sets into the
+    /// same [`MetricEncoder`], see [`MetricEncoder::by_ref`], to expose them as
+    /// a single metric family.
+    ///
+    /// ```
+    /// # use prometheus_client::collector::Collector;
+    /// # use prometheus_client::encoding::{DescriptorEncoder, EncodeMetric};
+    /// # use prometheus_client::metrics::gauge::ConstGauge;
+    /// # use prometheus_client::metrics::TypedMetric;
+    /// #
+    /// #[derive(Debug)]
+    /// struct DiskCollector {}
+    ///
+    /// impl Collector for DiskCollector {
+    ///     fn encode(&self, mut encoder: DescriptorEncoder) -> Result<(), std::fmt::Error> {
+    ///         let mut metric_encoder = encoder.encode_descriptor(
+    ///             "disk_free_bytes",
+    ///             "Free disk space",
+    ///             None,
+    ///             ConstGauge::<i64>::TYPE,
+    ///         )?;
+    ///         for (device, free) in [("sda", 42i64), ("sdb", 7)] {
+    ///             ConstGauge::with_labels(free, [("device", device)])
+    ///                 .encode(metric_encoder.by_ref())?;
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    /// ```
+    pub fn with_labels(value: N, label_set: S) -> Self {
+        Self {
+            value,
+            label_set: Some(label_set),
         }
-
-        old_f32
-    }
-
-    fn set(&self, v: f32) -> f32 {
-        f32::from_bits(self.swap(f32::to_bits(v), Ordering::Relaxed))
-    }
-
-    fn get(&self) -> f32 {
-        f32::from_bits(self.load(Ordering::Relaxed))
     }
 }
 
-impl<N, A> TypedMetric for Gauge<N, A> {
+impl<N, S> TypedMetric for ConstGauge<N, S> {
     const TYPE: MetricType = MetricType::Gauge;
 }
 
-impl<N, A> EncodeMetric for Gauge<N, A>
+impl<N, S> EncodeMetric for ConstGauge<N, S>
 where
     N: EncodeGaugeValue,
-    A: Atomic<N>,
+    S: EncodeLabelSet,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        encoder.encode_gauge(&self.get())
+        match &self.label_set {
+            Some(label_set) => encoder.encode_family(label_set)?.encode_gauge(&self.value),
+            None => encoder.encode_gauge(&self.value),
+        }
     }
+
     fn metric_type(&self) -> MetricType {
         Self::TYPE
     }
 }
 
-/// As a [`Gauge`], but constant, meaning it cannot change once created.
+/// [`Gauge`] whose value is computed by calling a function at encoding time,
+/// i.e. on each scrape.
 ///
-/// Needed for advanced use-cases, e.g. in combination with [`Collector`](crate::collector::Collector).
-#[derive(Debug, Default)]
-pub struct ConstGauge<N = i64> {
-    value: N,
+/// Useful for values that are expensive to compute or only meaningful when
+/// read, e.g. the current heap usage, avoiding a background task regularly
+/// calling [`Gauge::set`]. The function may return any type a [`Gauge`]
+/// value can be encoded as, e.g. [`i64`] or [`f64`].
+///
+/// ```
+/// # use prometheus_client::metrics::gauge::GaugeFn;
+/// # use prometheus_client::registry::Registry;
+/// # use std::sync::Arc;
+/// #
+/// let queue = Arc::new(vec![1, 2, 3]);
+///
+/// let mut registry = Registry::default();
+/// let queue_len = {
+///     let queue = queue.clone();
+///     GaugeFn::new(move || queue.len() as i64)
+/// };
+/// registry.register("queue_length", "Number of queued items", queue_len);
+/// ```
+#[derive(Clone)]
+pub struct GaugeFn<F> {
+    f: F,
 }
 
-impl<N> ConstGauge<N> {
-    /// Creates a new [`ConstGauge`].
-    pub fn new(value: N) -> Self {
-        Self { value }
+impl<F> GaugeFn<F> {
+    /// Creates a new [`GaugeFn`] calling `f` to get its current value.
+    pub fn new(f: F) -> Self {
+        Self { f }
+    }
+}
+
+impl<N, F: Fn() -> N> GaugeFn<F> {
+    /// Get the current value by calling the function.
+    pub fn get(&self) -> N {
+        (self.f)()
     }
 }
 
-impl<N> TypedMetric for ConstGauge<N> {
+impl<F> std::fmt::Debug for GaugeFn<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GaugeFn").finish_non_exhaustive()
+    }
+}
+
+impl<F> TypedMetric for GaugeFn<F> {
     const TYPE: MetricType = MetricType::Gauge;
 }
 
-impl<N> EncodeMetric for ConstGauge<N>
+impl<N, F> EncodeMetric for GaugeFn<F>
 where
     N: EncodeGaugeValue,
+    F: Fn() -> N,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        encoder.encode_gauge(&self.value)
+        encoder.encode_gauge(&self.get())
     }
 
     fn metric_type(&self) -> MetricType {
@@ -390,6 +602,123 @@ where
 mod tests {
     use super::*;
 
+    #[cfg(target_has_atomic = "64")]
+    #[test]
+    fn read_gauge() {
+        let gauge = Gauge::<u64, AtomicU64>::default();
+        gauge.set(u64::from(u32::MAX) + 1);
+        assert_eq!(4294967296.0, gauge.get_f64());
+
+        let gauge = Gauge::<f32, AtomicU32>::default();
+        gauge.set(-0.5);
+        let boxed: Box<dyn ReadGauge> = Box::new(gauge);
+        assert_eq!(-0.5, boxed.get_f64());
+    }
+
+    #[test]
+    fn as_read_gauge() {
+        use crate::registry::Registry;
+
+        let gauge: Gauge = Gauge::default();
+        gauge.set(-3);
+        let mut registry = Registry::default();
+        registry.register("gauge", "Gauge", gauge);
+        registry.register(
+            "counter",
+            "Counter",
+            crate::metrics::counter::Counter::<u64>::default(),
+        );
+
+        let values = registry
+            .iter_metrics()
+            .map(|(_, metric)| metric.as_read_gauge().map(|g| g.get_f64()))
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Some(-3.0), None], values);
+    }
+
+    #[test]
+    fn gauge_fn() {
+        use crate::encoding::text::encode;
+        use crate::registry::Registry;
+
+        let value = Arc::new(AtomicI32::new(1));
+        let mut registry = Registry::default();
+        registry.register("my_gauge", "My gauge", {
+            let value = value.clone();
+            GaugeFn::new(move || value.load(Ordering::Relaxed))
+        });
+
+        value.store(42, Ordering::Relaxed);
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        assert!(encoded.contains("my_gauge 42\n"));
+
+        let temperature = GaugeFn::new(|| 21.5);
+        assert_eq!(21.5, temperature.get());
+    }
+
+    #[test]
+    fn set_max_and_min() {
+        let gauge: Gauge = Gauge::default();
+        assert_eq!(7, gauge.set_max(7));
+        assert_eq!(7, gauge.set_max(-1));
+        assert_eq!(-1, gauge.set_min(-1));
+        assert_eq!(-1, gauge.set_min(3));
+
+        let gauge = Gauge::<f32, AtomicU32>::default();
+        assert_eq!(1.5, gauge.set_max(1.5));
+        assert_eq!(1.5, gauge.set_max(0.5));
+        assert_eq!(0.5, gauge.set_min(0.5));
+        assert_eq!(0.5, gauge.get());
+    }
+
+    #[test]
+    fn default_set_max_and_min() {
+        #[derive(Debug, Default)]
+        struct Store(AtomicI32);
+
+        impl Atomic<i32> for Store {
+            fn inc(&self) -> i32 {
+                self.0.inc()
+            }
+
+            fn inc_by(&self, v: i32) -> i32 {
+                self.0.inc_by(v)
+            }
+
+            fn dec(&self) -> i32 {
+                self.0.dec()
+            }
+
+            fn dec_by(&self, v: i32) -> i32 {
+                self.0.dec_by(v)
+            }
+
+            fn set(&self, v: i32) -> i32 {
+                self.0.set(v)
+            }
+
+            fn get(&self) -> i32 {
+                self.0.get()
+            }
+        }
+
+        let gauge = Gauge::<i32, Store>::default();
+        assert_eq!(7, gauge.set_max(7));
+        assert_eq!(7, gauge.set_max(-1));
+        assert_eq!(-1, gauge.set_min(-1));
+        assert_eq!(-1, gauge.set_min(3));
+    }
+
+    #[test]
+    fn f32_gauge() {
+        let gauge = Gauge::<f32, AtomicU32>::default();
+        assert_eq!(0.0, gauge.inc_by(1.5));
+        assert_eq!(1.5, gauge.dec());
+        assert_eq!(0.5, gauge.set(-2.0));
+        assert_eq!(-2.0, gauge.get());
+    }
+
     #[test]
     fn inc_dec_and_get() {
         let gauge: Gauge = Gauge::default();
@@ -402,4 +731,17 @@ mod tests {
         assert_eq!(0, gauge.set(10));
         assert_eq!(10, gauge.get());
     }
+
+    #[cfg(all(feature = "serde", target_has_atomic = "64"))]
+    #[test]
+    fn serde() {
+        let gauge: Gauge = Gauge::default();
+        gauge.set(-42);
+
+        let serialized = serde_json::to_string(&gauge).unwrap();
+        assert_eq!("-42", serialized);
+
+        let restored: Gauge = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(-42, restored.get());
+    }
 }
@@ -0,0 +1,132 @@
+//! Module implementing an Open Metrics gauge histogram.
+//!
+//! See [`GaugeHistogram`] for details.
+
+use crate::encoding::{EncodeMetric, MetricEncoder};
+
+use super::histogram::{bucket_index, upper_bounds};
+use super::{MetricType, TypedMetric};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Open Metrics [`GaugeHistogram`] to measure current distributions, e.g. of
+/// the items in a queue.
+///
+/// In contrast to a [`Histogram`](crate::metrics::histogram::Histogram), the
+/// observations of a [`GaugeHistogram`] can be removed again via
+/// [`GaugeHistogram::dec`]. It is encoded with `_gcount` and `_gsum` instead of
+/// `_count` and `_sum` samples.
+///
+/// ```
+/// # use prometheus_client::metrics::gauge_histogram::GaugeHistogram;
+/// # use prometheus_client::metrics::histogram::exponential_buckets;
+/// let queue_item_size = GaugeHistogram::new(exponential_buckets(64.0, 4.0, 5));
+///
+/// // Item enqueued.
+/// queue_item_size.observe(512.0);
+///
+/// // Item dequeued.
+/// queue_item_size.dec(512.0);
+/// ```
+#[derive(Debug)]
+pub struct GaugeHistogram {
+    inner: Arc<RwLock<Inner>>,
+}
+
+impl Clone for GaugeHistogram {
+    fn clone(&self) -> Self {
+        GaugeHistogram {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    sum: f64,
+    count: u64,
+    upper_bounds: Vec<f64>,
+    buckets: Vec<u64>,
+}
+
+impl GaugeHistogram {
+    /// Create a new [`GaugeHistogram`].
+    ///
+    /// Panics if the buckets are not finite and strictly increasing.
+    pub fn new(buckets: impl IntoIterator<Item = f64>) -> Self {
+        let upper_bounds = upper_bounds(buckets);
+        Self {
+            inner: Arc::new(RwLock::new(Inner {
+                sum: Default::default(),
+                count: Default::default(),
+                buckets: vec![0; upper_bounds.len()],
+                upper_bounds,
+            })),
+        }
+    }
+
+    /// Add an observation of the given value.
+    pub fn observe(&self, v: f64) {
+        let mut inner = self.inner.write();
+        inner.sum += v;
+        inner.count += 1;
+
+        if let Some(i) = bucket_index(&inner.upper_bounds, v) {
+            inner.buckets[i] += 1;
+        }
+    }
+
+    /// Remove a previous observation of the given value.
+    ///
+    /// Counts saturate at zero.
+    pub fn dec(&self, v: f64) {
+        let mut inner = self.inner.write();
+        inner.sum -= v;
+        inner.count = inner.count.saturating_sub(1);
+
+        if let Some(i) = bucket_index(&inner.upper_bounds, v) {
+            inner.buckets[i] = inner.buckets[i].saturating_sub(1);
+        }
+    }
+}
+
+impl TypedMetric for GaugeHistogram {
+    const TYPE: MetricType = MetricType::GaugeHistogram;
+}
+
+impl EncodeMetric for GaugeHistogram {
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        let inner = self.inner.read();
+        encoder.encode_gauge_histogram_buckets(
+            inner.sum,
+            inner.count,
+            inner
+                .upper_bounds
+                .iter()
+                .copied()
+                .zip(inner.buckets.iter().copied()),
+        )
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observe_and_dec() {
+        let histogram = GaugeHistogram::new([1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.dec(0.5);
+
+        let inner = histogram.inner.read();
+        assert_eq!(5.0, inner.sum);
+        assert_eq!(1, inner.count);
+        assert_eq!(vec![0, 1, 0], inner.buckets);
+    }
+}
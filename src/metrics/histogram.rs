@@ -5,9 +5,10 @@
 use crate::encoding::{EncodeMetric, MetricEncoder, NoLabelSet};
 
 use super::{MetricType, TypedMetric};
-use parking_lot::{MappedRwLockReadGuard, RwLock, RwLockReadGuard};
+use parking_lot::{RwLock, RwLockReadGuard};
 use std::iter::{self, once};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Open Metrics [`Histogram`] to measure distributions of discrete events.
 ///
@@ -31,14 +32,43 @@ use std::sync::Arc;
 /// let histogram = Histogram::new(custom_buckets);
 /// histogram.observe(4.2);
 /// ```
+///
+/// # Bucket count width
+///
+/// [`Histogram`] is generic over the integer type counting the observations
+/// per bucket, see [`BucketCount`]. Out of convenience it defaults to [`u64`].
+/// Memory constrained deployments with many histograms, e.g. a
+/// [`Family`](crate::metrics::family::Family) with thousands of label sets,
+/// that only ever observe a low number of values per bucket may choose a
+/// smaller type via [`Histogram::new_with_bucket_count`].
+///
+/// Each bucket takes 8 bytes for its upper bound plus the size of the bucket
+/// count type, i.e. for a histogram with 30 buckets:
+///
+/// | Bucket count type | Bytes per bucket | Bytes for 30 buckets |
+/// |-------------------|------------------|----------------------|
+/// | [`u64`]           | 16               | 480                  |
+/// | [`u32`]           | 12               | 360                  |
+/// | [`u16`]           | 10               | 300                  |
+///
+/// A bucket count saturates at the maximum value of its type instead of
+/// wrapping around. The total count and sum of the histogram are unaffected,
+/// thus a saturated bucket is detectable by the `+Inf` bucket being lower than
+/// the histogram's count.
+///
+/// ```
+/// # use prometheus_client::metrics::histogram::Histogram;
+/// let histogram = Histogram::<u32>::new_with_bucket_count([10.0, 100.0, 1_000.0]);
+/// histogram.observe(4.2);
+/// ```
 // TODO: Consider using atomics. See
 // https://github.com/tikv/rust-prometheus/pull/314.
 #[derive(Debug)]
-pub struct Histogram {
-    inner: Arc<RwLock<Inner>>,
+pub struct Histogram<B = u64> {
+    inner: Arc<RwLock<Inner<B>>>,
 }
 
-impl Clone for Histogram {
+impl<B> Clone for Histogram<B> {
     fn clone(&self) -> Self {
         Histogram {
             inner: self.inner.clone(),
@@ -47,14 +77,158 @@ impl Clone for Histogram {
 }
 
 #[derive(Debug)]
-pub(crate) struct Inner {
+pub(crate) struct Inner<B> {
     // TODO: Consider allowing integer observe values.
-    sum: f64,
+    sum: CompensatedSum,
     count: u64,
-    // TODO: Consider being generic over the bucket length.
-    buckets: Vec<(f64, u64)>,
+    // Upper bounds and bucket counts are stored separately, as `(f64, u32)`
+    // would be padded to the size of `(f64, u64)`.
+    upper_bounds: Vec<f64>,
+    buckets: Vec<B>,
+    created: Option<SystemTime>,
+}
+
+/// Integer type counting the observations of a single [`Histogram`] bucket.
+///
+/// See [`Histogram`] for details.
+pub trait BucketCount: Copy + Default + Into<u64> + std::fmt::Debug {
+    /// Increase the count by one, saturating at the maximum value of the type.
+    fn saturating_inc(&mut self);
+
+    /// Increase the count by `n`, saturating at the maximum value of the type.
+    ///
+    /// The implementations of this crate add `n` in constant time. The default
+    /// implementation is only a fallback for other types: it calls
+    /// [`BucketCount::saturating_inc`] up to `n` times, i.e. its cost grows
+    /// linearly with `n`. Implementations should override it where possible.
+    fn saturating_inc_by(&mut self, n: u64) {
+        for _ in 0..n {
+            let previous: u64 = (*self).into();
+            self.saturating_inc();
+            if previous == (*self).into() {
+                break;
+            }
+        }
+    }
+}
+
+/// Sum of `f64` values using Kahan-Babuska (Neumaier) compensated summation,
+/// keeping the rounding error of long running sums of many small values
+/// bounded instead of growing with the number of values.
+#[derive(Debug, Default, Clone, Copy)]
+struct CompensatedSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    fn new(sum: f64) -> Self {
+        Self {
+            sum,
+            compensation: 0.0,
+        }
+    }
+
+    fn add(&mut self, v: f64) {
+        let t = self.sum + v;
+        // Once the sum is no longer finite, the compensation would turn NaN.
+        if t.is_finite() {
+            if self.sum.abs() >= v.abs() {
+                self.compensation += (self.sum - t) + v;
+            } else {
+                self.compensation += (v - t) + self.sum;
+            }
+        }
+        self.sum = t;
+    }
+
+    fn get(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+macro_rules! impl_bucket_count {
+    ($($t:ident),*) => {$(
+        impl BucketCount for $t {
+            fn saturating_inc(&mut self) {
+                *self = self.saturating_add(1);
+            }
+
+            fn saturating_inc_by(&mut self, n: u64) {
+                *self = self.saturating_add(n.try_into().unwrap_or($t::MAX));
+            }
+        }
+    )*};
 }
 
+impl_bucket_count!(u8, u16, u32, u64);
+
+/// A point-in-time copy of the state of a [`Histogram`].
+///
+/// Returned by [`Histogram::snapshot`], e.g. to persist a [`Histogram`] across
+/// a graceful restart and restore it via [`Histogram::from_snapshot`]. With the
+/// `serde` feature enabled, [`HistogramSnapshot`] and [`Histogram`] implement
+/// `Serialize` and `Deserialize`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistogramSnapshot {
+    /// Sum of all observed values.
+    pub sum: f64,
+    /// Number of observed values.
+    pub count: u64,
+    /// Upper bound and cumulative count of each bucket, see
+    /// [`Histogram::buckets`].
+    pub buckets: Vec<(f64, u64)>,
+}
+
+/// Error returned by [`Histogram::try_from_snapshot`] for a
+/// [`HistogramSnapshot`] not describing a valid [`Histogram`], e.g. corrupt
+/// persisted data.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum InvalidSnapshot {
+    /// The last bucket is not the `+Inf` bucket, i.e. its upper bound is not
+    /// [`f64::MAX`].
+    MissingInfBucket,
+    /// The upper bound of the bucket at the given index is not finite.
+    NonFiniteUpperBound(usize, f64),
+    /// The given upper bounds of adjacent buckets are not strictly increasing.
+    NonIncreasingUpperBounds(f64, f64),
+    /// The cumulative count of the bucket with the given upper bound is lower
+    /// than the one of the previous bucket.
+    DecreasingBucketCount(f64),
+    /// The cumulative count of the `+Inf` bucket exceeds the total count.
+    BucketCountExceedsCount(u64, u64),
+}
+
+impl std::fmt::Display for InvalidSnapshot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidSnapshot::MissingInfBucket => {
+                write!(f, "histogram snapshot is missing the +Inf bucket")
+            }
+            InvalidSnapshot::NonFiniteUpperBound(i, upper_bound) => write!(
+                f,
+                "histogram bucket {i} with upper bound {upper_bound} is not finite"
+            ),
+            InvalidSnapshot::NonIncreasingUpperBounds(lower, upper) => write!(
+                f,
+                "histogram buckets must be strictly increasing, found {lower} followed by {upper}"
+            ),
+            InvalidSnapshot::DecreasingBucketCount(upper_bound) => write!(
+                f,
+                "histogram snapshot bucket count decreases at upper bound {upper_bound}"
+            ),
+            InvalidSnapshot::BucketCountExceedsCount(bucket_count, count) => write!(
+                f,
+                "histogram snapshot +Inf bucket count {bucket_count} exceeds count {count}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidSnapshot {}
+
 impl Histogram {
     /// Create a new [`Histogram`].
     ///
@@ -62,16 +236,121 @@ impl Histogram {
     /// # use prometheus_client::metrics::histogram::Histogram;
     /// let histogram = Histogram::new([10.0, 100.0, 1_000.0]);
     /// ```
+    ///
+    /// Panics if the buckets are not finite and strictly increasing.
     pub fn new(buckets: impl IntoIterator<Item = f64>) -> Self {
+        Self::new_with_bucket_count(buckets)
+    }
+
+    /// Create a new [`Histogram`] from a slice of bucket upper bounds, e.g. a
+    /// constant.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// const LATENCY_BUCKETS: &[f64] = &[0.005, 0.05, 0.5, 5.0];
+    ///
+    /// let histogram = Histogram::from_buckets(LATENCY_BUCKETS);
+    /// ```
+    ///
+    /// Panics if the buckets are not finite and strictly increasing.
+    pub fn from_buckets(buckets: &[f64]) -> Self {
+        Self::new(buckets.iter().copied())
+    }
+
+    /// Create a new [`Histogram`] from a [`HistogramSnapshot`], restoring its
+    /// buckets, sum and count.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// let histogram = Histogram::new([1.0, 10.0]);
+    /// histogram.observe(5.0);
+    ///
+    /// let restored = Histogram::from_snapshot(&histogram.snapshot());
+    /// assert_eq!(histogram.buckets(), restored.buckets());
+    /// ```
+    ///
+    /// Panics if the snapshot is invalid, see [`Histogram::try_from_snapshot`].
+    pub fn from_snapshot(snapshot: &HistogramSnapshot) -> Self {
+        Self::try_from_snapshot(snapshot).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like [`Histogram::from_snapshot`], but returning an error if the
+    /// snapshot does not describe a valid [`Histogram`]: the buckets have to
+    /// end with the `+Inf` bucket, their upper bounds have to be finite and
+    /// strictly increasing and their cumulative counts must not decrease nor
+    /// exceed the count. The count may exceed the one of the `+Inf` bucket,
+    /// as observations of `NaN` are counted without a bucket.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::{Histogram, HistogramSnapshot};
+    /// let snapshot = HistogramSnapshot {
+    ///     sum: 1.0,
+    ///     count: 1,
+    ///     buckets: vec![(10.0, 1), (1.0, 1), (f64::MAX, 1)],
+    /// };
+    /// assert!(Histogram::try_from_snapshot(&snapshot).is_err());
+    /// ```
+    pub fn try_from_snapshot(snapshot: &HistogramSnapshot) -> Result<Self, InvalidSnapshot> {
+        let Some((f64::MAX, inf_count)) = snapshot.buckets.last() else {
+            return Err(InvalidSnapshot::MissingInfBucket);
+        };
+        if *inf_count > snapshot.count {
+            return Err(InvalidSnapshot::BucketCountExceedsCount(
+                *inf_count,
+                snapshot.count,
+            ));
+        }
+
+        let upper_bounds = try_upper_bounds(
+            snapshot.buckets[..snapshot.buckets.len() - 1]
+                .iter()
+                .map(|(upper_bound, _)| *upper_bound),
+        )?;
+
+        let mut buckets = Vec::with_capacity(upper_bounds.len());
+        let mut previous = 0;
+        for (upper_bound, cumulative) in snapshot.buckets.iter() {
+            let count = cumulative
+                .checked_sub(previous)
+                .ok_or(InvalidSnapshot::DecreasingBucketCount(*upper_bound))?;
+            buckets.push(count);
+            previous = *cumulative;
+        }
+
+        Ok(Self {
+            inner: Arc::new(RwLock::new(Inner {
+                sum: CompensatedSum::new(snapshot.sum),
+                count: snapshot.count,
+                upper_bounds,
+                buckets,
+                created: super::now(),
+            })),
+        })
+    }
+}
+
+impl<B: BucketCount> Histogram<B> {
+    /// Create a new [`Histogram`] counting the observations per bucket with
+    /// the integer type `B`.
+    ///
+    /// See [`Histogram`] for the trade-offs of the different bucket count
+    /// types.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// let histogram = Histogram::<u16>::new_with_bucket_count([10.0, 100.0, 1_000.0]);
+    /// ```
+    ///
+    /// Panics if the buckets are not finite and strictly increasing.
+    pub fn new_with_bucket_count(buckets: impl IntoIterator<Item = f64>) -> Self {
+        let upper_bounds = upper_bounds(buckets);
         Self {
             inner: Arc::new(RwLock::new(Inner {
                 sum: Default::default(),
                 count: Default::default(),
-                buckets: buckets
-                    .into_iter()
-                    .chain(once(f64::MAX))
-                    .map(|upper_bound| (upper_bound, 0))
-                    .collect(),
+                buckets: vec![B::default(); upper_bounds.len()],
+                upper_bounds,
+                created: super::now(),
             })),
         }
     }
@@ -81,6 +360,159 @@ impl Histogram {
         self.observe_and_bucket(v);
     }
 
+    /// Observe the given value `count` times under a single lock acquisition,
+    /// e.g. to import the aggregated observations of another system.
+    ///
+    /// Equivalent to calling [`Histogram::observe`] `count` times, adding
+    /// `value * count` to the sum.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// let histogram = Histogram::new([1.0, 10.0]);
+    /// histogram.observe_many(5.0, 3);
+    /// assert_eq!(3, histogram.count());
+    /// assert_eq!(15.0, histogram.sum());
+    /// ```
+    pub fn observe_many(&self, value: f64, count: u64) {
+        let mut inner = self.inner.write();
+        inner.sum.add(value * count as f64);
+        inner.count += count;
+
+        if let Some(i) = bucket_index(&inner.upper_bounds, value) {
+            inner.buckets[i].saturating_inc_by(count);
+        }
+    }
+
+    /// Observe the given [`Duration`] in seconds.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::{default_buckets, Histogram};
+    /// # use std::time::Instant;
+    /// let request_duration_seconds = Histogram::new(default_buckets());
+    ///
+    /// let start = Instant::now();
+    /// // Handle request.
+    /// request_duration_seconds.observe_duration(start.elapsed());
+    /// ```
+    pub fn observe_duration(&self, d: Duration) {
+        self.observe(d.as_secs_f64());
+    }
+
+    /// Call `f`, observing its wall-clock duration in seconds and returning
+    /// its result.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::{default_buckets, Histogram};
+    /// let request_duration_seconds = Histogram::new(default_buckets());
+    ///
+    /// let response = request_duration_seconds.observe_closure_duration(|| "Hello, world!");
+    /// ```
+    pub fn observe_closure_duration<T>(&self, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.observe_duration(start.elapsed());
+        result
+    }
+
+    /// Start a [`HistogramTimer`] observing the elapsed time in seconds once
+    /// dropped, e.g. to time a scope with early returns.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::{default_buckets, Histogram};
+    /// let request_duration_seconds = Histogram::new(default_buckets());
+    ///
+    /// fn handle(request_duration_seconds: &Histogram) -> Result<(), std::fmt::Error> {
+    ///     let _timer = request_duration_seconds.start_timer();
+    ///     // Handle request, possibly returning early.
+    ///     Ok(())
+    /// }
+    ///
+    /// handle(&request_duration_seconds).unwrap();
+    /// assert_eq!(1, request_duration_seconds.count());
+    /// ```
+    pub fn start_timer(&self) -> HistogramTimer<B> {
+        HistogramTimer {
+            histogram: Some(self.clone()),
+            start: Instant::now(),
+        }
+    }
+
+    /// The time the [`Histogram`] was created or last reset at.
+    ///
+    /// Exposed as `_created` sample if enabled via
+    /// [`Registry::set_emit_created`](crate::registry::Registry::set_emit_created).
+    /// `None` on platforms without a system clock.
+    pub fn created(&self) -> Option<SystemTime> {
+        self.inner.read().created
+    }
+
+    /// Get the sum of all observed values.
+    ///
+    /// The sum is accumulated using compensated summation, thus stays accurate
+    /// even after many observations of small values.
+    pub fn sum(&self) -> f64 {
+        self.inner.read().sum.get()
+    }
+
+    /// Get the number of observed values.
+    pub fn count(&self) -> u64 {
+        self.inner.read().count
+    }
+
+    /// Get the upper bound and cumulative count of each bucket, including the
+    /// final `+Inf` bucket, represented by [`f64::MAX`].
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// let histogram = Histogram::new([1.0, 10.0]);
+    /// histogram.observe(0.5);
+    /// histogram.observe(5.0);
+    ///
+    /// assert_eq!(
+    ///     histogram.buckets(),
+    ///     vec![(1.0, 1), (10.0, 2), (f64::MAX, 2)],
+    /// );
+    /// ```
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        self.snapshot().buckets
+    }
+
+    /// Get a consistent copy of the sum, count and buckets of the
+    /// [`Histogram`], see [`HistogramSnapshot`].
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let inner = self.inner.read();
+        let mut cumulative = 0;
+        HistogramSnapshot {
+            sum: inner.sum.get(),
+            count: inner.count,
+            buckets: inner
+                .buckets()
+                .map(|(upper_bound, count)| {
+                    cumulative += count;
+                    (upper_bound, cumulative)
+                })
+                .collect(),
+        }
+    }
+
+    /// Reset the sum, count and all bucket counts to zero, keeping the
+    /// configured buckets. Sets [`Histogram::created`] to the current time,
+    /// signaling the reset to scrapers.
+    ///
+    /// ```rust
+    /// # use prometheus_client::metrics::histogram::Histogram;
+    /// let histogram = Histogram::new([10.0, 100.0]);
+    /// histogram.observe(42.0);
+    /// histogram.reset();
+    /// ```
+    pub fn reset(&self) {
+        let mut inner = self.inner.write();
+        inner.sum = Default::default();
+        inner.count = Default::default();
+        inner.buckets.fill(B::default());
+        inner.created = super::now();
+    }
+
     /// Observes the given value, returning the index of the first bucket the
     /// value is added to.
     ///
@@ -88,39 +520,155 @@ impl Histogram {
     /// [`HistogramWithExemplars`](crate::metrics::exemplar::HistogramWithExemplars).
     pub(crate) fn observe_and_bucket(&self, v: f64) -> Option<usize> {
         let mut inner = self.inner.write();
-        inner.sum += v;
+        inner.sum.add(v);
         inner.count += 1;
 
-        let first_bucket = inner
-            .buckets
-            .iter_mut()
-            .enumerate()
-            .find(|(_i, (upper_bound, _value))| upper_bound >= &v);
-
-        match first_bucket {
-            Some((i, (_upper_bound, value))) => {
-                *value += 1;
+        match bucket_index(&inner.upper_bounds, v) {
+            Some(i) => {
+                inner.buckets[i].saturating_inc();
                 Some(i)
             }
             None => None,
         }
     }
 
-    pub(crate) fn get(&self) -> (f64, u64, MappedRwLockReadGuard<Vec<(f64, u64)>>) {
+    pub(crate) fn get(&self) -> (f64, u64, RwLockReadGuard<'_, Inner<B>>) {
         let inner = self.inner.read();
-        let sum = inner.sum;
+        let sum = inner.sum.get();
         let count = inner.count;
-        let buckets = RwLockReadGuard::map(inner, |inner| &inner.buckets);
-        (sum, count, buckets)
+        (sum, count, inner)
+    }
+}
+
+impl<B: BucketCount> Inner<B> {
+    /// Iterate the upper bound and count of each bucket.
+    pub(crate) fn buckets(&self) -> impl Iterator<Item = (f64, u64)> + '_ {
+        self.upper_bounds
+            .iter()
+            .copied()
+            .zip(self.buckets.iter().map(|count| (*count).into()))
     }
 }
 
-impl TypedMetric for Histogram {
+/// Collect the given bucket upper bounds, appending the `+Inf` bucket,
+/// represented by [`f64::MAX`].
+///
+/// Panics if the buckets are not finite and strictly increasing, as the
+/// cumulative bucket counts would be wrong otherwise.
+pub(crate) fn upper_bounds(buckets: impl IntoIterator<Item = f64>) -> Vec<f64> {
+    try_upper_bounds(buckets).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// Like [`upper_bounds`], but returning an error instead of panicking.
+fn try_upper_bounds(buckets: impl IntoIterator<Item = f64>) -> Result<Vec<f64>, InvalidSnapshot> {
+    let upper_bounds: Vec<f64> = buckets.into_iter().chain(once(f64::MAX)).collect();
+    if let Some((i, upper_bound)) = upper_bounds
+        .iter()
+        .enumerate()
+        .find(|(_, upper_bound)| !upper_bound.is_finite())
+    {
+        return Err(InvalidSnapshot::NonFiniteUpperBound(i, *upper_bound));
+    }
+    if let Some(pair) = upper_bounds.windows(2).find(|pair| pair[0] >= pair[1]) {
+        return Err(InvalidSnapshot::NonIncreasingUpperBounds(pair[0], pair[1]));
+    }
+    Ok(upper_bounds)
+}
+
+/// Index of the first bucket whose upper bound is greater than or equal to
+/// `v`, if any.
+pub(crate) fn bucket_index(upper_bounds: &[f64], v: f64) -> Option<usize> {
+    upper_bounds
+        .iter()
+        .position(|upper_bound| upper_bound >= &v)
+}
+
+/// Timer observing the elapsed time in seconds into a [`Histogram`] when
+/// dropped.
+///
+/// Created via [`Histogram::start_timer`]. Use [`HistogramTimer::stop`] to
+/// observe before the end of the scope, or [`HistogramTimer::cancel`] to not
+/// observe at all.
+#[derive(Debug)]
+#[must_use = "the elapsed time is observed when the timer is dropped"]
+pub struct HistogramTimer<B: BucketCount = u64> {
+    // `None` once observed or cancelled.
+    histogram: Option<Histogram<B>>,
+    start: Instant,
+}
+
+impl<B: BucketCount> HistogramTimer<B> {
+    /// Observe the elapsed time now, returning it in seconds.
+    pub fn stop(mut self) -> f64 {
+        self.observe()
+    }
+
+    /// Stop the timer without observing the elapsed time, returning it in
+    /// seconds.
+    pub fn cancel(mut self) -> f64 {
+        self.histogram = None;
+        self.start.elapsed().as_secs_f64()
+    }
+
+    fn observe(&mut self) -> f64 {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        if let Some(histogram) = self.histogram.take() {
+            histogram.observe(elapsed);
+        }
+        elapsed
+    }
+}
+
+impl<B: BucketCount> Drop for HistogramTimer<B> {
+    fn drop(&mut self) {
+        self.observe();
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<B: BucketCount> serde::Serialize for Histogram<B> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for Histogram {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let snapshot = HistogramSnapshot::deserialize(deserializer)?;
+        Self::try_from_snapshot(&snapshot).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<B> TypedMetric for Histogram<B> {
     const TYPE: MetricType = MetricType::Histogram;
 }
 
 /// Exponential bucket distribution.
+///
+/// Creates `length` buckets, yielding `start`, `start * factor`,
+/// `start * factor^2`, and so on.
+///
+/// ```
+/// # use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+/// let histogram = Histogram::new(exponential_buckets(0.001, 2.0, 10));
+/// ```
+///
+/// In debug builds, panics if `start` is not positive or `factor` is not
+/// greater than 1, as the buckets would not be increasing. In release builds
+/// such buckets are rejected by [`Histogram::new`].
 pub fn exponential_buckets(start: f64, factor: f64, length: u16) -> impl Iterator<Item = f64> {
+    debug_assert!(
+        start > 0.0 && start.is_finite(),
+        "exponential_buckets requires a positive finite start, got {start}"
+    );
+    debug_assert!(
+        factor > 1.0 && factor.is_finite(),
+        "exponential_buckets requires a finite factor greater than 1, got {factor}"
+    );
+
     iter::repeat(())
         .enumerate()
         .map(move |(i, _)| start * factor.powf(i as f64))
@@ -152,17 +700,65 @@ pub fn exponential_buckets_range(min: f64, max: f64, length: u16) -> impl Iterat
 }
 
 /// Linear bucket distribution.
+///
+/// Creates `length` buckets, yielding `start`, `start + width`,
+/// `start + 2 * width`, and so on.
+///
+/// ```
+/// # use prometheus_client::metrics::histogram::{linear_buckets, Histogram};
+/// let histogram = Histogram::new(linear_buckets(0.1, 0.1, 5));
+/// ```
+///
+/// In debug builds, panics if `length` is 0, `start` is not finite or `width`
+/// is not finite and positive. In release builds the latter buckets are
+/// rejected by [`Histogram::new`], as they would not be increasing.
 pub fn linear_buckets(start: f64, width: f64, length: u16) -> impl Iterator<Item = f64> {
+    debug_assert!(
+        length > 0,
+        "linear_buckets requires at least one bucket, got length {length}"
+    );
+    debug_assert!(
+        start.is_finite() && width.is_finite(),
+        "linear_buckets requires finite start and width, got start {start} and width {width}"
+    );
+    debug_assert!(
+        width > 0.0,
+        "linear_buckets requires a positive width, got width {width}"
+    );
+
     iter::repeat(())
         .enumerate()
         .map(move |(i, _)| start + (width * (i as f64)))
         .take(length.into())
 }
 
-impl EncodeMetric for Histogram {
+/// The default buckets of the Go client library, i.e. `0.005`, `0.01`,
+/// `0.025`, `0.05`, `0.1`, `0.25`, `0.5`, `1`, `2.5`, `5` and `10`.
+///
+/// Tuned for request latencies in seconds, ranging from milliseconds to
+/// seconds.
+///
+/// ```
+/// # use prometheus_client::metrics::histogram::{default_buckets, Histogram};
+/// let request_duration_seconds = Histogram::new(default_buckets());
+/// ```
+pub fn default_buckets() -> impl Iterator<Item = f64> {
+    [
+        0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+    ]
+    .into_iter()
+}
+
+impl<B: BucketCount> EncodeMetric for Histogram<B> {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        let (sum, count, buckets) = self.get();
-        encoder.encode_histogram::<NoLabelSet>(sum, count, &buckets, None)
+        let (sum, count, inner) = self.get();
+        encoder.encode_histogram_buckets::<NoLabelSet, f64>(
+            sum,
+            count,
+            inner.buckets(),
+            None,
+            inner.created,
+        )
     }
 
     fn metric_type(&self) -> MetricType {
@@ -180,6 +776,133 @@ mod tests {
         histogram.observe(1.0);
     }
 
+    #[test]
+    fn bucket_count_saturates() {
+        let histogram = Histogram::<u8>::new_with_bucket_count([1.0]);
+        for _ in 0..300 {
+            histogram.observe(0.5);
+        }
+
+        let (sum, count, inner) = histogram.get();
+        assert_eq!(150.0, sum);
+        assert_eq!(300, count);
+        assert_eq!(
+            vec![(1.0, u8::MAX as u64), (f64::MAX, 0)],
+            inner.buckets().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn bucket_count_saturating_inc_by() {
+        let mut count = 250u8;
+        count.saturating_inc_by(u64::MAX);
+        assert_eq!(u8::MAX, count);
+
+        let mut count = 1u32;
+        count.saturating_inc_by(41);
+        assert_eq!(42, count);
+
+        let mut count = u64::MAX - 1;
+        count.saturating_inc_by(u64::MAX);
+        assert_eq!(u64::MAX, count);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+
+        let serialized = serde_json::to_string(&histogram).unwrap();
+        let restored: Histogram = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(histogram.snapshot(), restored.snapshot());
+
+        restored.observe(0.5);
+        assert_eq!(vec![(1.0, 2), (10.0, 3), (f64::MAX, 4)], restored.buckets());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_invalid() {
+        for invalid in [
+            r#"{"sum":1.0,"count":1,"buckets":[]}"#,
+            r#"{"sum":1.0,"count":1,"buckets":[[1.0,1]]}"#,
+            r#"{"sum":1.0,"count":1,"buckets":[[10.0,1],[1.0,1],[1.7976931348623157e308,1]]}"#,
+            r#"{"sum":1.0,"count":1,"buckets":[[1.0,1],[1.0,1],[1.7976931348623157e308,1]]}"#,
+            r#"{"sum":1.0,"count":2,"buckets":[[1.0,2],[1.7976931348623157e308,1]]}"#,
+            r#"{"sum":1.0,"count":1,"buckets":[[1.0,1],[1.7976931348623157e308,2]]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Histogram>(invalid).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn try_from_snapshot() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(f64::NAN);
+        let restored = Histogram::try_from_snapshot(&histogram.snapshot()).unwrap();
+        assert_eq!(histogram.snapshot().buckets, restored.snapshot().buckets);
+        assert_eq!(2, restored.count());
+
+        let snapshot = HistogramSnapshot {
+            sum: 1.0,
+            count: 2,
+            buckets: vec![(1.0, 2), (f64::MAX, 1)],
+        };
+        assert_eq!(
+            Err(InvalidSnapshot::DecreasingBucketCount(f64::MAX)),
+            Histogram::try_from_snapshot(&snapshot).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.observe(50.0);
+
+        assert_eq!(55.5, histogram.sum());
+        assert_eq!(3, histogram.count());
+        assert_eq!(
+            vec![(1.0, 1), (10.0, 2), (f64::MAX, 3)],
+            histogram.buckets()
+        );
+    }
+
+    #[test]
+    fn reset() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        histogram.reset();
+
+        let (sum, count, inner) = histogram.get();
+        assert_eq!(0.0, sum);
+        assert_eq!(0, count);
+        assert_eq!(
+            vec![(1.0, 0), (10.0, 0), (f64::MAX, 0)],
+            inner.buckets().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reset_updates_created() {
+        let histogram = Histogram::new([1.0]);
+        let created = histogram.created();
+
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        histogram.clone().reset();
+
+        assert!(histogram.created() > created);
+    }
+
     #[test]
     fn exponential() {
         assert_eq!(
@@ -196,6 +919,161 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "at least one bucket, got length 0")]
+    fn linear_zero_length() {
+        let _ = linear_buckets(0.0, 1.0, 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "finite start and width, got start 0 and width NaN")]
+    fn linear_non_finite() {
+        let _ = linear_buckets(0.0, f64::NAN, 10);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "positive width, got width 0")]
+    fn linear_zero_width() {
+        let _ = linear_buckets(0.0, 0.0, 10);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "positive width, got width -1")]
+    fn linear_negative_width() {
+        let _ = linear_buckets(0.0, -1.0, 10);
+    }
+
+    #[test]
+    fn from_buckets() {
+        const BUCKETS: &[f64] = &[1.0, 10.0];
+        let histogram = Histogram::from_buckets(BUCKETS);
+        histogram.observe(5.0);
+        assert_eq!(
+            vec![(1.0, 0), (10.0, 1), (f64::MAX, 1)],
+            histogram.buckets()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn buckets_out_of_order() {
+        let _ = Histogram::new([1.0, 10.0, 5.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly increasing")]
+    fn buckets_duplicate() {
+        let _ = Histogram::from_buckets(&[1.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not finite")]
+    fn buckets_non_finite() {
+        let _ = Histogram::new([1.0, f64::INFINITY]);
+    }
+
+    #[test]
+    fn observe_many() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe_many(0.5, 2);
+        histogram.observe_many(5.0, 3);
+        histogram.observe_many(50.0, 0);
+        assert_eq!(5, histogram.count());
+        assert_eq!(16.0, histogram.sum());
+        assert_eq!(
+            vec![(1.0, 2), (10.0, 5), (f64::MAX, 5)],
+            histogram.buckets()
+        );
+
+        let saturating = Histogram::<u8>::new_with_bucket_count([1.0]);
+        saturating.observe_many(0.5, 1_000);
+        assert_eq!(1_000, saturating.count());
+        assert_eq!((1.0, 255), saturating.buckets()[0]);
+    }
+
+    #[test]
+    fn compensated_sum() {
+        let histogram = Histogram::new([1.0]);
+        for _ in 0..1_000_000 {
+            histogram.observe(0.1);
+        }
+
+        // Naive summation is off by more than 1e-6.
+        assert_eq!(100_000.0, histogram.sum());
+        assert_eq!(100_000.0, histogram.snapshot().sum);
+
+        histogram.observe(f64::INFINITY);
+        assert_eq!(f64::INFINITY, histogram.sum());
+    }
+
+    #[test]
+    fn observe_duration() {
+        let histogram = Histogram::new([1.0, 10.0]);
+        histogram.observe_duration(Duration::from_millis(1_500));
+        assert_eq!(1.5, histogram.sum());
+
+        assert_eq!(42, histogram.observe_closure_duration(|| 42));
+        assert_eq!(2, histogram.count());
+        assert_eq!((1.0, 1), histogram.buckets()[0]);
+    }
+
+    #[test]
+    fn timer() {
+        let histogram = Histogram::new([1.0]);
+
+        drop(histogram.start_timer());
+        assert_eq!(1, histogram.count());
+
+        let elapsed = histogram.start_timer().stop();
+        assert_eq!(2, histogram.count());
+        assert!(elapsed < 1.0);
+
+        histogram.start_timer().cancel();
+        assert_eq!(2, histogram.count());
+    }
+
+    #[test]
+    fn go_default_buckets() {
+        let histogram = Histogram::new(default_buckets());
+        histogram.observe(0.3);
+        let buckets = histogram.buckets();
+        assert_eq!(12, buckets.len());
+        assert_eq!((0.25, 0), buckets[5]);
+        assert_eq!((0.5, 1), buckets[6]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "positive finite start")]
+    fn exponential_zero_start() {
+        let _ = exponential_buckets(0.0, 2.0, 10);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "positive finite start")]
+    fn exponential_negative_start() {
+        let _ = exponential_buckets(-1.0, 2.0, 10);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "factor greater than 1")]
+    fn exponential_factor_one() {
+        let _ = exponential_buckets(1.0, 1.0, 10);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "factor greater than 1")]
+    fn exponential_flipped_arguments() {
+        let _ = exponential_buckets(2.0, 0.5, 10);
+    }
+
     #[test]
     fn exponential_range() {
         assert_eq!(
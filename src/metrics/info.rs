@@ -3,9 +3,11 @@
 //! See [`Info`] for details.
 
 use crate::{
-    encoding::{EncodeLabelSet, EncodeMetric, MetricEncoder},
+    encoding::{collect_labels, EncodeLabelSet, EncodeMetric, MetricEncoder},
     metrics::{MetricType, TypedMetric},
 };
+use parking_lot::RwLock;
+use std::sync::Arc;
 
 /// Open Metrics [`Info`] metric "to expose textual information which SHOULD NOT
 /// change during process lifetime".
@@ -15,16 +17,154 @@ use crate::{
 ///
 /// let _info = Info::new(vec![("os", "GNU/linux")]);
 /// ```
+///
+/// The label set can be replaced via [`Info::set`], e.g. to register an
+/// [`Info`] metric early and populate it once the details are known. Clones
+/// share the same label set.
+///
+/// ```
+/// # use prometheus_client::metrics::info::Info;
+/// # use prometheus_client::registry::Registry;
+/// let mut registry = Registry::default();
+/// let build = Info::new(vec![("version", String::from("unknown"))]);
+/// registry.register("build", "Build information", build.clone());
+///
+/// // Later, e.g. after reading a version file.
+/// build.set(vec![("version", String::from("1.2.3"))]);
+/// ```
+///
+/// The label set must contain at least one label and all label names must be
+/// legal, i.e. match `[a-zA-Z_][a-zA-Z0-9_]*`. See [`Info::try_new`] to handle
+/// an invalid label set without panicking.
 #[derive(Debug)]
-pub struct Info<S>(pub(crate) S);
+pub struct Info<S> {
+    label_set: Arc<RwLock<S>>,
+}
 
-impl<S> Info<S> {
+impl<S> Clone for Info<S> {
+    fn clone(&self) -> Self {
+        Info {
+            label_set: self.label_set.clone(),
+        }
+    }
+}
+
+impl<S: EncodeLabelSet> Info<S> {
     /// Create [`Info`] metric with the provided label set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label set is empty or contains an illegal label name. See
+    /// [`Info::try_new`] for a non-panicking alternative.
     pub fn new(label_set: S) -> Self {
-        Self(label_set)
+        Self::try_new(label_set).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Create [`Info`] metric with the provided label set, returning an error
+    /// if the label set is empty or contains an illegal label name.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::info::{Info, InvalidInfo};
+    /// assert!(Info::try_new(vec![("version", "1.0")]).is_ok());
+    /// assert_eq!(
+    ///     InvalidInfo::EmptyLabelSet,
+    ///     Info::try_new(Vec::<(&str, &str)>::new()).unwrap_err(),
+    /// );
+    /// ```
+    pub fn try_new(label_set: S) -> Result<Self, InvalidInfo> {
+        validate(&label_set)?;
+        Ok(Self {
+            label_set: Arc::new(RwLock::new(label_set)),
+        })
+    }
+
+    /// Replace the label set of the [`Info`] metric, returning the previous
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the label set is empty or contains an illegal label name. See
+    /// [`Info::try_set`] for a non-panicking alternative.
+    pub fn set(&self, label_set: S) -> S {
+        self.try_set(label_set).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Replace the label set of the [`Info`] metric, returning the previous
+    /// one, or an error if the label set is empty or contains an illegal label
+    /// name. The label set is left unchanged on error.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::info::Info;
+    /// let info = Info::new(vec![("version", "1.0")]);
+    /// assert!(info.try_set(vec![("1version", "1.1")]).is_err());
+    /// assert_eq!(vec![("version", "1.0")], info.get());
+    /// ```
+    pub fn try_set(&self, label_set: S) -> Result<S, InvalidInfo> {
+        validate(&label_set)?;
+        Ok(std::mem::replace(&mut *self.label_set.write(), label_set))
+    }
+}
+
+impl<S> Info<S> {
+    /// Get a copy of the current label set of the [`Info`] metric.
+    pub fn get(&self) -> S
+    where
+        S: Clone,
+    {
+        self.label_set.read().clone()
     }
 }
 
+/// Error returned by [`Info::try_new`] and [`Info::try_set`] for an invalid
+/// label set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvalidInfo {
+    /// The label set contains no labels.
+    EmptyLabelSet,
+    /// The label set contains the given illegal label name.
+    InvalidLabelName(String),
+}
+
+impl std::fmt::Display for InvalidInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidInfo::EmptyLabelSet => write!(f, "info metric requires at least one label"),
+            InvalidInfo::InvalidLabelName(name) => {
+                write!(
+                    f,
+                    "info metric label name {name:?} is not a legal label name"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidInfo {}
+
+fn validate(label_set: &impl EncodeLabelSet) -> Result<(), InvalidInfo> {
+    // Label sets failing to encode fail the encoding later on, thus are not
+    // rejected here.
+    let Ok(labels) = collect_labels(label_set) else {
+        return Ok(());
+    };
+    if labels.is_empty() {
+        return Err(InvalidInfo::EmptyLabelSet);
+    }
+    if let Some((name, _)) = labels.iter().find(|(name, _)| !is_legal_label_name(name)) {
+        return Err(InvalidInfo::InvalidLabelName(name.clone()));
+    }
+    Ok(())
+}
+
+fn is_legal_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl<S> TypedMetric for Info<S> {
     const TYPE: MetricType = MetricType::Info;
 }
@@ -34,10 +174,58 @@ where
     S: Clone + std::hash::Hash + Eq + EncodeLabelSet,
 {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        encoder.encode_info(&self.0)
+        encoder.encode_info(&*self.label_set.read())
     }
 
     fn metric_type(&self) -> MetricType {
         Self::TYPE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoding::text::encode;
+    use crate::registry::Registry;
+
+    #[test]
+    fn set() {
+        let mut registry = Registry::default();
+        let info = Info::new(vec![("version", "unknown")]);
+        registry.register("build", "Build", info.clone());
+
+        assert_eq!(
+            vec![("version", "unknown")],
+            info.set(vec![("version", "1.0")])
+        );
+        assert_eq!(vec![("version", "1.0")], info.get());
+
+        let mut encoded = String::new();
+        encode(&mut encoded, &registry).unwrap();
+        assert!(encoded.contains("build_info{version=\"1.0\"} 1\n"));
+    }
+
+    #[test]
+    fn invalid_label_set() {
+        assert_eq!(
+            InvalidInfo::EmptyLabelSet,
+            Info::try_new(Vec::<(&str, &str)>::new()).unwrap_err()
+        );
+        assert_eq!(
+            InvalidInfo::InvalidLabelName("1version".to_string()),
+            Info::try_new(vec![("os", "linux"), ("1version", "1.0")]).unwrap_err()
+        );
+        assert_eq!(
+            InvalidInfo::InvalidLabelName("build-id".to_string()),
+            Info::try_new(vec![("build-id", "1")]).unwrap_err()
+        );
+        assert!(Info::try_new(vec![("_os", "linux"), ("version_2", "1.0")]).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one label")]
+    fn set_empty_label_set() {
+        let info = Info::new(vec![("version", "1.0")]);
+        info.set(vec![]);
+    }
+}
@@ -0,0 +1,163 @@
+//! Module implementing an Open Metrics state set metric.
+//!
+//! See [`StateSet`] for details.
+
+use crate::encoding::{EncodeMetric, EncodeStateSet, MetricEncoder};
+
+use super::{MetricType, TypedMetric};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Open Metrics [`StateSet`] to expose which one out of a set of mutually
+/// exclusive states is currently active.
+///
+/// Each state is encoded as a separate sample with value `1` for the active
+/// state and `0` otherwise.
+///
+/// ```
+/// # use prometheus_client::encoding::EncodeStateSet;
+/// # use prometheus_client::encoding::text::encode;
+/// # use prometheus_client::metrics::stateset::StateSet;
+/// # use prometheus_client::registry::Registry;
+/// #
+/// #[derive(Clone, Copy, Debug)]
+/// enum Mode {
+///     Primary,
+///     Standby,
+/// }
+///
+/// impl EncodeStateSet for Mode {
+///     const STATES: &'static [&'static str] = &["primary", "standby"];
+///
+///     fn index(&self) -> usize {
+///         *self as usize
+///     }
+/// }
+///
+/// let mode = StateSet::new(Mode::Standby);
+///
+/// let mut registry = Registry::default();
+/// registry.register("mode", "Current replication mode", mode.clone());
+///
+/// mode.set(Mode::Primary);
+///
+/// let mut buffer = String::new();
+/// encode(&mut buffer, &registry).unwrap();
+///
+/// let expected = "# HELP mode Current replication mode.\n".to_owned()
+///     + "# TYPE mode stateset\n"
+///     + "mode{mode=\"primary\"} 1\n"
+///     + "mode{mode=\"standby\"} 0\n"
+///     + "# EOF\n";
+/// assert_eq!(expected, buffer);
+/// ```
+#[derive(Debug)]
+pub struct StateSet<S> {
+    state: Arc<AtomicUsize>,
+    phantom: PhantomData<S>,
+}
+
+impl<S> Clone for StateSet<S> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: EncodeStateSet + Default> Default for StateSet<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: EncodeStateSet> StateSet<S> {
+    /// Create a new [`StateSet`] with `state` being the active state.
+    pub fn new(state: S) -> Self {
+        Self {
+            state: Arc::new(AtomicUsize::new(state.index())),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Set the active state.
+    pub fn set(&self, state: S) {
+        self.state.store(state.index(), Ordering::Relaxed);
+    }
+
+    /// Get the name of the active state.
+    ///
+    /// Returns `None` if [`EncodeStateSet::index`] of the active state is out
+    /// of bounds of [`EncodeStateSet::STATES`].
+    pub fn get(&self) -> Option<&'static str> {
+        S::STATES.get(self.state.load(Ordering::Relaxed)).copied()
+    }
+}
+
+impl<S> TypedMetric for StateSet<S> {
+    const TYPE: MetricType = MetricType::StateSet;
+}
+
+impl<S: EncodeStateSet> EncodeMetric for StateSet<S> {
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        let active = self.state.load(Ordering::Relaxed);
+        encoder.encode_stateset(
+            S::STATES
+                .iter()
+                .enumerate()
+                .map(|(i, state)| (*state, i == active)),
+        )
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Default)]
+    enum Mode {
+        #[default]
+        Primary,
+        Standby,
+    }
+
+    impl EncodeStateSet for Mode {
+        const STATES: &'static [&'static str] = &["primary", "standby"];
+
+        fn index(&self) -> usize {
+            *self as usize
+        }
+    }
+
+    #[test]
+    fn set_and_get() {
+        let mode = StateSet::<Mode>::default();
+        assert_eq!(Some("primary"), mode.get());
+
+        mode.clone().set(Mode::Standby);
+        assert_eq!(Some("standby"), mode.get());
+    }
+
+    #[derive(Clone, Copy, Debug)]
+    struct OutOfRange;
+
+    impl EncodeStateSet for OutOfRange {
+        const STATES: &'static [&'static str] = &["only"];
+
+        fn index(&self) -> usize {
+            1
+        }
+    }
+
+    #[test]
+    fn get_out_of_range() {
+        let state = StateSet::new(OutOfRange);
+        assert_eq!(None, state.get());
+    }
+}
@@ -0,0 +1,102 @@
+//! Module implementing an Open Metrics unknown metric.
+//!
+//! See [`Unknown`] for details.
+
+use crate::encoding::{EncodeGaugeValue, EncodeMetric, MetricEncoder};
+
+use super::gauge::{Atomic, Gauge};
+use super::{MetricType, TypedMetric};
+#[cfg(not(target_has_atomic = "64"))]
+use std::sync::atomic::AtomicU32;
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::AtomicU64;
+
+/// Open Metrics [`Unknown`] metric, exposing a value of indeterminate
+/// semantics, e.g. an untyped value bridged from another monitoring system.
+///
+/// Like a [`Gauge`] it holds a single settable value, but it is exposed with
+/// the `unknown` metric type, and as `untyped` in the Prometheus text format,
+/// rather than claiming gauge semantics.
+///
+/// ```
+/// # use prometheus_client::metrics::unknown::Unknown;
+/// # use prometheus_client::registry::Registry;
+/// let mut registry = Registry::default();
+/// let value: Unknown = Unknown::default();
+/// registry.register("bridged_value", "Value of the bridged system", value.clone());
+///
+/// value.set(42.5);
+/// assert_eq!(42.5, value.get());
+/// ```
+#[cfg(target_has_atomic = "64")]
+#[derive(Debug)]
+pub struct Unknown<N = f64, A = AtomicU64> {
+    value: Gauge<N, A>,
+}
+
+/// Open Metrics [`Unknown`] metric, exposing a value of indeterminate
+/// semantics.
+#[cfg(not(target_has_atomic = "64"))]
+#[derive(Debug)]
+pub struct Unknown<N = f32, A = AtomicU32> {
+    value: Gauge<N, A>,
+}
+
+impl<N, A> Clone for Unknown<N, A> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
+impl<N, A: Default> Default for Unknown<N, A> {
+    fn default() -> Self {
+        Self {
+            value: Gauge::default(),
+        }
+    }
+}
+
+impl<N, A: Atomic<N>> Unknown<N, A> {
+    /// Sets the [`Unknown`] to `v`, returning the previous value.
+    pub fn set(&self, v: N) -> N {
+        self.value.set(v)
+    }
+
+    /// Get the current value of the [`Unknown`].
+    pub fn get(&self) -> N {
+        self.value.get()
+    }
+}
+
+impl<N, A> TypedMetric for Unknown<N, A> {
+    const TYPE: MetricType = MetricType::Unknown;
+}
+
+impl<N, A> EncodeMetric for Unknown<N, A>
+where
+    N: EncodeGaugeValue,
+    A: Atomic<N>,
+{
+    fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        encoder.encode_unknown(&self.get())
+    }
+
+    fn metric_type(&self) -> MetricType {
+        Self::TYPE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[test]
+    fn set_and_get() {
+        let unknown = Unknown::<f32, AtomicU32>::default();
+        assert_eq!(0.0, unknown.set(1.5));
+        assert_eq!(1.5, unknown.clone().get());
+    }
+}
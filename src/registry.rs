@@ -3,9 +3,21 @@
 //! See [`Registry`] for details.
 
 use std::borrow::Cow;
+use std::collections::HashSet;
+#[cfg(feature = "async")]
+use std::future::Future;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::sync::Arc;
 
+#[cfg(feature = "async")]
+use crate::collector::AsyncCollector;
 use crate::collector::Collector;
-use crate::encoding::{DescriptorEncoder, EncodeMetric};
+use crate::encoding::visitor::{self, MetricVisitor};
+use crate::encoding::{text, DescriptorEncoder, EncodeMetric};
+use crate::metrics::exemplar::ExemplarLabelOverflow;
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 /// A metric registry.
 ///
@@ -23,8 +35,8 @@ use crate::encoding::{DescriptorEncoder, EncodeMetric};
 ///
 /// ```
 /// # use prometheus_client::encoding::text::encode;
-/// # use prometheus_client::metrics::counter::{Atomic as _, Counter};
-/// # use prometheus_client::metrics::gauge::{Atomic as _, Gauge};
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::metrics::gauge::Gauge;
 /// # use prometheus_client::registry::Registry;
 /// #
 /// // Create a metric registry.
@@ -61,16 +73,40 @@ use crate::encoding::{DescriptorEncoder, EncodeMetric};
 pub struct Registry {
     prefix: Option<Prefix>,
     labels: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    label_providers: Vec<LabelProvider>,
     metrics: Vec<(Descriptor, Box<dyn Metric>)>,
     collectors: Vec<Box<dyn Collector>>,
+    #[cfg(feature = "async")]
+    async_collectors: Vec<Box<dyn AsyncCollector>>,
     sub_registries: Vec<Registry>,
+    emit_created: bool,
+    exemplar_overflow: ExemplarLabelOverflow,
+    separator: Separator,
 }
 
 impl Registry {
+    /// Returns a [`RegistryBuilder`] to configure a new [`Registry`].
+    ///
+    /// ```
+    /// # use prometheus_client::registry::Registry;
+    /// # use std::borrow::Cow;
+    /// #
+    /// let registry = Registry::builder()
+    ///     .prefix("app")
+    ///     .label((Cow::Borrowed("region"), Cow::Borrowed("eu")))
+    ///     .separator(':')
+    ///     .emit_created(true)
+    ///     .build();
+    /// assert_eq!(Some("app"), registry.prefix());
+    /// ```
+    pub fn builder() -> RegistryBuilder {
+        RegistryBuilder::default()
+    }
+
     /// Creates a new default [`Registry`] with the given prefix.
     pub fn with_prefix(prefix: impl Into<String>) -> Self {
         Self {
-            prefix: Some(Prefix(prefix.into())),
+            prefix: Some(Prefix::from(prefix.into())),
             ..Default::default()
         }
     }
@@ -91,12 +127,212 @@ impl Registry {
         labels: impl Iterator<Item = (Cow<'static, str>, Cow<'static, str>)>,
     ) -> Self {
         Self {
-            prefix: Some(Prefix(prefix.into())),
+            prefix: Some(Prefix::from(prefix.into())),
             labels: labels.into_iter().collect(),
             ..Default::default()
         }
     }
 
+    /// Returns the prefix prepended to the names of all metrics registered with
+    /// this [`Registry`], if any.
+    ///
+    /// For sub-registries this is the effective prefix, i.e. including the
+    /// prefixes of all parent registries.
+    ///
+    /// ```
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::with_prefix("my_prefix");
+    /// assert_eq!(Some("my_prefix"), registry.prefix());
+    ///
+    /// let sub_registry = registry.sub_registry_with_prefix("subsystem");
+    /// assert_eq!(Some("my_prefix_subsystem"), sub_registry.prefix());
+    /// ```
+    pub fn prefix(&self) -> Option<&str> {
+        self.prefix.as_ref().map(Prefix::as_str)
+    }
+
+    /// Returns the constant labels added to all metrics registered with this
+    /// [`Registry`].
+    ///
+    /// For sub-registries this includes the labels of all parent registries.
+    ///
+    /// ```
+    /// # use prometheus_client::registry::Registry;
+    /// # use std::borrow::Cow;
+    /// #
+    /// let mut registry = Registry::default();
+    /// assert!(registry.labels().is_empty());
+    ///
+    /// let sub_registry =
+    ///     registry.sub_registry_with_label((Cow::Borrowed("my_key"), Cow::Borrowed("my_value")));
+    /// assert_eq!(
+    ///     &[(Cow::Borrowed("my_key"), Cow::Borrowed("my_value"))],
+    ///     sub_registry.labels(),
+    /// );
+    /// ```
+    pub fn labels(&self) -> &[(Cow<'static, str>, Cow<'static, str>)] {
+        &self.labels
+    }
+
+    /// Add a function computing additional labels on each encoding, e.g. for a
+    /// label reflecting state that changes at runtime.
+    ///
+    /// The provided labels follow the constant labels of this [`Registry`]
+    /// and precede those given to [`Registry::register_with_labels`]. They
+    /// apply to all metrics and collectors of this [`Registry`], including
+    /// those of its sub-registries, whether created before or after the
+    /// provider was added. Each provider is called once per encoding.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// # use std::borrow::Cow;
+    /// # use std::sync::atomic::{AtomicBool, Ordering};
+    /// # use std::sync::Arc;
+    /// #
+    /// let leader = Arc::new(AtomicBool::new(false));
+    ///
+    /// let mut registry = Registry::default();
+    /// let is_leader = leader.clone();
+    /// registry.add_label_provider(move || {
+    ///     let value = if is_leader.load(Ordering::Relaxed) { "true" } else { "false" };
+    ///     vec![(Cow::Borrowed("leader"), Cow::Borrowed(value))]
+    /// });
+    /// registry.register("elections", "Elections", Counter::<u64>::default());
+    ///
+    /// leader.store(true, Ordering::Relaxed);
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert!(buffer.contains("elections_total{leader=\"true\"} 0"));
+    /// ```
+    ///
+    /// Sub-registries created before the provider was added are covered too.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// # use std::borrow::Cow;
+    /// #
+    /// let mut registry = Registry::default();
+    /// registry
+    ///     .sub_registry_with_prefix("db")
+    ///     .register("queries", "Queries", Counter::<u64>::default());
+    /// registry.add_label_provider(|| vec![(Cow::Borrowed("leader"), Cow::Borrowed("true"))]);
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert!(buffer.contains("db_queries_total{leader=\"true\"} 0"));
+    /// ```
+    pub fn add_label_provider(
+        &mut self,
+        provider: impl Fn() -> Vec<Label> + Send + Sync + 'static,
+    ) {
+        self.label_providers.push(LabelProvider(Box::new(provider)));
+    }
+
+    /// Enable or disable encoding the creation time of counters and
+    /// histograms, i.e. `_created` samples in the text format and the
+    /// `created` field in the protobuf format. Disabled by default.
+    ///
+    /// Applies to all metrics encoded via this [`Registry`], including those of
+    /// its sub-registries. The setting of sub-registries is ignored.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::default();
+    /// registry.set_emit_created(true);
+    /// registry.register("my_counter", "This is my counter", Counter::<u64>::default());
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert!(buffer.contains("my_counter_created "));
+    /// ```
+    pub fn set_emit_created(&mut self, emit_created: bool) {
+        self.emit_created = emit_created;
+    }
+
+    pub(crate) fn emit_created(&self) -> bool {
+        self.emit_created
+    }
+
+    /// Set how to handle exemplars whose label set exceeds the limit of
+    /// [`MAX_EXEMPLAR_LABEL_SET_LENGTH`] UTF-8 characters imposed by
+    /// OpenMetrics. Defaults to [`ExemplarLabelOverflow::Omit`].
+    ///
+    /// Applies to all metrics encoded via this [`Registry`], including those of
+    /// its sub-registries. The setting of sub-registries is ignored.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::exemplar::{CounterWithExemplar, ExemplarLabelOverflow};
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::default();
+    /// registry.set_exemplar_overflow(ExemplarLabelOverflow::Error);
+    ///
+    /// let counter = CounterWithExemplar::<Vec<(String, String)>>::default();
+    /// registry.register("my_counter", "This is my counter", counter.clone());
+    /// counter.inc_by(1, Some(vec![("trace_id".to_string(), "f".repeat(200))]));
+    ///
+    /// let mut buffer = String::new();
+    /// assert!(encode(&mut buffer, &registry).is_err());
+    /// ```
+    ///
+    /// [`MAX_EXEMPLAR_LABEL_SET_LENGTH`]: crate::metrics::exemplar::MAX_EXEMPLAR_LABEL_SET_LENGTH
+    pub fn set_exemplar_overflow(&mut self, exemplar_overflow: ExemplarLabelOverflow) {
+        self.exemplar_overflow = exemplar_overflow;
+    }
+
+    pub(crate) fn exemplar_overflow(&self) -> ExemplarLabelOverflow {
+        self.exemplar_overflow
+    }
+
+    /// Set the separator joining the prefixes of this [`Registry`] and its
+    /// sub-registries with each other and with metric names. Defaults to `_`.
+    ///
+    /// Applies to the prefix of this [`Registry`] and to sub-registries created
+    /// afterwards, which inherit the separator. Set it before creating
+    /// sub-registries. The unit suffix of a metric name is always joined with
+    /// `_`, as required by OpenMetrics.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the separator is not a legal metric name character, i.e. not
+    /// one of `[a-zA-Z0-9_:]`.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::with_prefix("app");
+    /// registry.set_separator(':');
+    /// registry
+    ///     .sub_registry_with_prefix("subsystem")
+    ///     .register("requests", "Requests", Counter::<u64>::default());
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert!(buffer.contains("app:subsystem:requests_total 0"));
+    /// ```
+    pub fn set_separator(&mut self, separator: char) {
+        assert!(
+            separator.is_ascii_alphanumeric() || separator == '_' || separator == ':',
+            "separator {separator:?} is not a legal metric name character"
+        );
+        self.separator = Separator(separator);
+        if let Some(prefix) = self.prefix.as_mut() {
+            prefix.separator = separator;
+        }
+    }
+
     /// Register a metric with the [`Registry`].
     ///
     /// Note: In the Open Metrics text exposition format some metric types have
@@ -111,8 +347,13 @@ impl Registry {
     /// Use [`Registry::register_with_unit`] whenever a unit for the given
     /// metric is known.
     ///
+    /// # Panics
+    ///
+    /// Panics if a metric with the same name is already registered. See
+    /// [`Registry::try_register`] for a non-panicking alternative.
+    ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic as _, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::registry::{Registry, Unit};
     /// #
     /// let mut registry = Registry::default();
@@ -126,6 +367,55 @@ impl Registry {
         help: H,
         metric: impl Metric,
     ) {
+        if let Err(e) = self.priv_register(name, help, metric, None) {
+            panic!("{e}");
+        }
+    }
+
+    /// Register a metric with the [`Registry`], returning an error if a metric
+    /// with the same name is already registered.
+    ///
+    /// Names are compared as exposed, i.e. including the prefix of this
+    /// [`Registry`] and the unit of the metric, against the metrics of this
+    /// [`Registry`] and all of its sub-registries, see
+    /// [`Registry::metric_names`].
+    ///
+    /// A sub-registry does not know its parent. Thus collisions with metrics
+    /// of parent or sibling registries, e.g. two sub-registries created via
+    /// [`Registry::sub_registry_with_label`] registering the same name, are
+    /// not detected. Neither are collisions with metrics of [`Collector`]s not
+    /// advertised via [`Collector::descriptors`].
+    ///
+    /// See [`Registry::register`] for additional documentation.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::with_prefix("my_subsystem");
+    ///
+    /// let counter: Counter = Counter::default();
+    /// registry.try_register("my_counter", "This is my counter", counter.clone()).unwrap();
+    ///
+    /// let err = registry
+    ///     .try_register("my_counter", "This is my counter", counter.clone())
+    ///     .unwrap_err();
+    /// assert_eq!("my_subsystem_my_counter", err.name());
+    ///
+    /// registry
+    ///     .sub_registry_with_prefix("db")
+    ///     .register("queries", "Queries", counter.clone());
+    /// let err = registry
+    ///     .try_register("db_queries", "Queries", counter)
+    ///     .unwrap_err();
+    /// assert_eq!("my_subsystem_db_queries", err.name());
+    /// ```
+    pub fn try_register<N: Into<String>, H: Into<String>>(
+        &mut self,
+        name: N,
+        help: H,
+        metric: impl Metric,
+    ) -> Result<(), AlreadyRegistered> {
         self.priv_register(name, help, metric, None)
     }
 
@@ -138,7 +428,7 @@ impl Registry {
     /// unit to the name manually.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic as _, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::registry::{Registry, Unit};
     /// #
     /// let mut registry = Registry::default();
@@ -158,7 +448,56 @@ impl Registry {
         unit: Unit,
         metric: impl Metric,
     ) {
-        self.priv_register(name, help, metric, Some(unit))
+        if let Err(e) = self.priv_register(name, help, metric, Some(unit)) {
+            panic!("{e}");
+        }
+    }
+
+    /// Register a metric with the [`Registry`], adding the given constant
+    /// labels to this metric only.
+    ///
+    /// The labels are added to those of this [`Registry`]. Use
+    /// [`Registry::sub_registry_with_labels`] to add labels to multiple
+    /// metrics instead.
+    ///
+    /// See [`Registry::register`] for additional documentation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a metric with the same name is already registered.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// # use std::borrow::Cow;
+    /// #
+    /// let mut registry = Registry::with_labels(
+    ///     [(Cow::Borrowed("service"), Cow::Borrowed("api"))].into_iter(),
+    /// );
+    /// registry.register_with_labels(
+    ///     "hits",
+    ///     "Cache hits",
+    ///     [(Cow::Borrowed("component"), Cow::Borrowed("cache"))].into_iter(),
+    ///     Counter::<u64>::default(),
+    /// );
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert!(buffer.contains("hits_total{service=\"api\",component=\"cache\"} 0"));
+    /// ```
+    pub fn register_with_labels<N: Into<String>, H: Into<String>>(
+        &mut self,
+        name: N,
+        help: H,
+        labels: impl Iterator<Item = (Cow<'static, str>, Cow<'static, str>)>,
+        metric: impl Metric,
+    ) {
+        let mut descriptor = Descriptor::new(name, help, None);
+        descriptor.labels = labels.collect();
+        if let Err(e) = self.register_descriptor(descriptor, metric) {
+            panic!("{e}");
+        }
     }
 
     fn priv_register<N: Into<String>, H: Into<String>>(
@@ -167,9 +506,224 @@ impl Registry {
         help: H,
         metric: impl Metric,
         unit: Option<Unit>,
-    ) {
-        let descriptor = Descriptor::new(name, help, unit);
+    ) -> Result<(), AlreadyRegistered> {
+        self.register_descriptor(Descriptor::new(name, help, unit), metric)
+    }
+
+    fn register_descriptor(
+        &mut self,
+        descriptor: Descriptor,
+        metric: impl Metric,
+    ) -> Result<(), AlreadyRegistered> {
+        self.ensure_unregistered(descriptor.name_with_unit())?;
+
         self.metrics.push((descriptor, Box::new(metric)));
+        Ok(())
+    }
+
+    /// Returns an error if a metric or an advertised metric of a [`Collector`]
+    /// with the given name, including the unit, once prefixed is exposed by
+    /// this [`Registry`] or one of its sub-registries.
+    fn ensure_unregistered(&self, name: String) -> Result<(), AlreadyRegistered> {
+        self.ensure_unregistered_names(std::iter::once(self.prefixed(name)))
+    }
+
+    /// Return an error for the first of the given fully prefixed names that is
+    /// already exposed by this [`Registry`] or one of its sub-registries.
+    fn ensure_unregistered_names(
+        &self,
+        mut names: impl Iterator<Item = String>,
+    ) -> Result<(), AlreadyRegistered> {
+        let existing = self.metric_names().collect::<HashSet<_>>();
+        match names.find(|name| existing.contains(name)) {
+            Some(name) => Err(AlreadyRegistered { name }),
+            None => Ok(()),
+        }
+    }
+
+    /// Absorb the metrics, collectors and sub-registries of another
+    /// [`Registry`], e.g. one built by an isolated module, to expose them
+    /// alongside the metrics of this [`Registry`].
+    ///
+    /// The prefix and labels of this [`Registry`] apply to the absorbed
+    /// metrics, in addition to those of `other`. If `other` has a prefix or
+    /// labels, it becomes a sub-registry. Either way, nothing is absorbed if
+    /// any metric of `other` would be exposed under the same name as an
+    /// existing one, see [`Registry::metric_names`].
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::with_prefix("app");
+    /// registry.register("requests", "Requests", Counter::<u64>::default());
+    ///
+    /// let mut db_registry = Registry::with_prefix("db");
+    /// db_registry.register("queries", "Queries", Counter::<u64>::default());
+    /// registry.merge(db_registry).unwrap();
+    ///
+    /// let mut duplicate = Registry::default();
+    /// duplicate.register("requests", "Requests", Counter::<u64>::default());
+    /// let err = registry.merge(duplicate).unwrap_err();
+    /// assert_eq!("app_requests", err.name());
+    ///
+    /// let mut duplicate = Registry::with_prefix("db");
+    /// duplicate.register("queries", "Queries", Counter::<u64>::default());
+    /// let err = registry.merge(duplicate).unwrap_err();
+    /// assert_eq!("app_db_queries", err.name());
+    ///
+    /// assert_eq!(
+    ///     vec!["app_requests", "app_db_queries"],
+    ///     registry.metric_names().collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn merge(&mut self, mut other: Registry) -> Result<(), AlreadyRegistered> {
+        if other.prefix.is_some() || !other.labels.is_empty() || !other.label_providers.is_empty() {
+            other.rebase(self.prefix.as_ref(), &self.labels);
+            self.ensure_unregistered_names(other.metric_names())?;
+            self.sub_registries.push(other);
+            return Ok(());
+        }
+
+        for sub_registry in other.sub_registries.iter_mut() {
+            sub_registry.rebase(self.prefix.as_ref(), &self.labels);
+        }
+        self.ensure_unregistered_names(
+            other
+                .metrics
+                .iter()
+                .map(|(descriptor, _)| descriptor.name_with_unit())
+                .chain(
+                    other
+                        .collectors
+                        .iter()
+                        .flat_map(|collector| collector.descriptors())
+                        .map(|descriptor| descriptor.name_with_unit()),
+                )
+                .map(|name| self.prefixed(name))
+                .chain(
+                    other
+                        .sub_registries
+                        .iter()
+                        .flat_map(|sub_registry| sub_registry.metric_names()),
+                ),
+        )?;
+
+        self.metrics.extend(other.metrics);
+        self.collectors.extend(other.collectors);
+        #[cfg(feature = "async")]
+        self.async_collectors.extend(other.async_collectors);
+        self.sub_registries.extend(other.sub_registries);
+        Ok(())
+    }
+
+    /// Nest this [`Registry`] and its sub-registries under the given parent
+    /// prefix and labels.
+    ///
+    /// Label providers are not copied, as those of the parent are evaluated
+    /// on encoding, see [`Registry::encode_nested`].
+    fn rebase(
+        &mut self,
+        prefix: Option<&Prefix>,
+        labels: &[(Cow<'static, str>, Cow<'static, str>)],
+    ) {
+        self.prefix = match (prefix, self.prefix.take()) {
+            (Some(parent), Some(own)) => Some(Prefix {
+                prefix: format!("{}{}{}", parent.as_str(), parent.separator, own.as_str()),
+                separator: parent.separator,
+            }),
+            (parent, own) => own.or_else(|| parent.cloned()),
+        };
+
+        let mut new_labels = labels.to_vec();
+        new_labels.append(&mut self.labels);
+        self.labels = new_labels;
+
+        for sub_registry in self.sub_registries.iter_mut() {
+            sub_registry.rebase(prefix, labels);
+        }
+    }
+
+    /// Remove the first metric registered with the given name from this
+    /// [`Registry`], returning whether a metric was removed.
+    ///
+    /// `name` is matched against the name passed to [`Registry::register`] or
+    /// [`Registry::register_with_unit`], i.e. excluding both the prefix of
+    /// this [`Registry`] and the unit. Metrics registered with sub-registries
+    /// have to be removed via the respective sub-registry.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::{Registry, Unit};
+    /// #
+    /// let mut registry = Registry::with_prefix("my_subsystem");
+    /// let counter: Counter = Counter::default();
+    /// registry.register_with_unit("my_counter", "This is my counter", Unit::Seconds, counter);
+    ///
+    /// // Neither the prefix nor the unit are part of the name to match.
+    /// assert!(!registry.unregister("my_subsystem_my_counter_seconds"));
+    /// assert!(registry.unregister("my_counter"));
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert_eq!("# EOF\n", buffer);
+    /// ```
+    pub fn unregister(&mut self, name: &str) -> bool {
+        match self
+            .metrics
+            .iter()
+            .position(|(descriptor, _)| descriptor.name == name)
+        {
+            Some(i) => {
+                self.metrics.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove all metrics, collectors and sub-registries from this
+    /// [`Registry`], e.g. to reuse it in tests or on reload.
+    ///
+    /// The prefix, labels and settings of this [`Registry`] are kept. Note
+    /// that this is distinct from
+    /// [`Family::clear`](crate::metrics::family::Family::clear), which removes
+    /// the series of a single metric.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::text::encode;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::with_prefix("my_subsystem");
+    /// registry.register("my_counter", "This is my counter", Counter::<u64>::default());
+    /// registry
+    ///     .sub_registry_with_prefix("db")
+    ///     .register("queries", "Queries", Counter::<u64>::default());
+    ///
+    /// registry.clear();
+    ///
+    /// let mut buffer = String::new();
+    /// encode(&mut buffer, &registry).unwrap();
+    /// assert_eq!("# EOF\n", buffer);
+    /// assert_eq!(Some("my_subsystem"), registry.prefix());
+    /// ```
+    pub fn clear(&mut self) {
+        self.metrics.clear();
+        self.collectors.clear();
+        #[cfg(feature = "async")]
+        self.async_collectors.clear();
+        self.sub_registries.clear();
+    }
+
+    /// Prepend the prefix of this [`Registry`], if any, to the given name.
+    fn prefixed(&self, name: String) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}{}{name}", prefix.as_str(), prefix.separator()),
+            None => name,
+        }
     }
 
     /// Register a [`Collector`].
@@ -207,6 +761,17 @@ impl Registry {
         self.collectors.push(collector);
     }
 
+    /// Register an [`AsyncCollector`].
+    ///
+    /// Asynchronous collectors are only encoded by
+    /// [`encode_async`](crate::encoding::text::encode_async). The synchronous
+    /// encoding functions skip them.
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn register_async_collector(&mut self, collector: Box<dyn AsyncCollector>) {
+        self.async_collectors.push(collector);
+    }
+
     /// Create a sub-registry to register metrics with a common prefix.
     ///
     /// Say you would like to prefix one set of metrics with `subsystem_a` and
@@ -219,7 +784,7 @@ impl Registry {
     /// subsystem registers.
     ///
     /// ```
-    /// # use prometheus_client::metrics::counter::{Atomic as _, Counter};
+    /// # use prometheus_client::metrics::counter::Counter;
     /// # use prometheus_client::registry::{Registry, Unit};
     /// #
     /// let mut registry = Registry::default();
@@ -228,25 +793,27 @@ impl Registry {
     /// let subsystem_a_counter_2: Counter = Counter::default();
     ///
     /// let subsystem_a_registry = registry.sub_registry_with_prefix("subsystem_a");
-    /// registry.register("counter_1", "", subsystem_a_counter_1.clone());
-    /// registry.register("counter_2", "", subsystem_a_counter_2.clone());
+    /// subsystem_a_registry.register("counter_1", "", subsystem_a_counter_1.clone());
+    /// subsystem_a_registry.register("counter_2", "", subsystem_a_counter_2.clone());
     ///
     /// let subsystem_b_counter_1: Counter = Counter::default();
     /// let subsystem_b_counter_2: Counter = Counter::default();
     ///
-    /// let subsystem_a_registry = registry.sub_registry_with_prefix("subsystem_b");
-    /// registry.register("counter_1", "", subsystem_b_counter_1.clone());
-    /// registry.register("counter_2", "", subsystem_b_counter_2.clone());
+    /// let subsystem_b_registry = registry.sub_registry_with_prefix("subsystem_b");
+    /// subsystem_b_registry.register("counter_1", "", subsystem_b_counter_1.clone());
+    /// subsystem_b_registry.register("counter_2", "", subsystem_b_counter_2.clone());
     /// ```
     ///
     /// See [`Registry::sub_registry_with_label`] for the same functionality,
     /// but namespacing with a label instead of a metric name prefix.
     pub fn sub_registry_with_prefix<P: AsRef<str>>(&mut self, prefix: P) -> &mut Self {
         let sub_registry = Registry {
-            prefix: Some(Prefix(
-                self.prefix.clone().map(|p| p.0 + "_").unwrap_or_default() + prefix.as_ref(),
-            )),
+            prefix: Some(Prefix {
+                prefix: self.prefixed(prefix.as_ref().to_owned()),
+                separator: self.separator.0,
+            }),
             labels: self.labels.clone(),
+            separator: self.separator,
             ..Default::default()
         };
 
@@ -272,6 +839,7 @@ impl Registry {
         let sub_registry = Registry {
             prefix: self.prefix.clone(),
             labels: new_labels,
+            separator: self.separator,
             ..Default::default()
         };
 
@@ -286,10 +854,287 @@ impl Registry {
             .expect("sub_registries not to be empty.")
     }
 
+    /// Iterate all metrics registered with this [`Registry`] and its
+    /// sub-registries along with their [`Descriptor`], e.g. to implement an
+    /// encoder outside of this crate.
+    ///
+    /// The name of each yielded [`Descriptor`] includes the prefix of the
+    /// respective (sub-)registry, its labels are the labels of the respective
+    /// (sub-)registry followed by those of the metric. Metrics provided by
+    /// [`Collector`]s are not included. See [`Registry::visit`] to read the
+    /// current values of all metrics.
+    ///
+    /// ```
+    /// # use prometheus_client::encoding::EncodeMetric;
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::{Registry, Unit};
+    /// # use std::borrow::Cow;
+    /// #
+    /// let mut registry = Registry::with_prefix("app");
+    /// registry.register("requests", "Requests", Counter::<u64>::default());
+    /// registry
+    ///     .sub_registry_with_label((Cow::Borrowed("db"), Cow::Borrowed("main")))
+    ///     .register_with_unit("query_time", "Query time", Unit::Seconds, Counter::<u64>::default());
+    ///
+    /// let metrics = registry.iter_metrics().collect::<Vec<_>>();
+    /// assert_eq!(2, metrics.len());
+    ///
+    /// let (descriptor, metric) = &metrics[1];
+    /// assert_eq!("app_query_time", descriptor.name());
+    /// assert_eq!(Some("seconds"), descriptor.unit().map(Unit::as_str));
+    /// assert_eq!(&[(Cow::Borrowed("db"), Cow::Borrowed("main"))], descriptor.labels());
+    /// assert_eq!("counter", metric.metric_type().as_str());
+    /// ```
+    pub fn iter_metrics(&self) -> impl Iterator<Item = (Descriptor, &dyn Metric)> + '_ {
+        self.iter_metrics_nested(Vec::new())
+    }
+
+    /// Like [`Registry::iter_metrics`], with `provided` being the labels
+    /// computed by the label providers of all parent registries.
+    fn iter_metrics_nested(
+        &self,
+        provided: Vec<(Cow<'static, str>, Cow<'static, str>)>,
+    ) -> Box<dyn Iterator<Item = (Descriptor, &dyn Metric)> + '_> {
+        let provided = self.provided_labels(&provided).into_owned();
+        let registry_labels = self.current_labels(&provided).into_owned();
+
+        let metrics = self.metrics.iter().map(move |(descriptor, metric)| {
+            let descriptor = Descriptor {
+                name: self.prefixed(descriptor.name.clone()),
+                help: descriptor.help.clone(),
+                unit: descriptor.unit.clone(),
+                labels: Self::metric_labels(&registry_labels, descriptor).into_owned(),
+            };
+            (descriptor, metric.as_ref() as &dyn Metric)
+        });
+
+        let sub_registries = self
+            .sub_registries
+            .iter()
+            .flat_map(move |registry| registry.iter_metrics_nested(provided.clone()));
+
+        Box::new(metrics.chain(sub_registries))
+    }
+
+    /// Iterate the names of all metrics registered with this [`Registry`] and
+    /// its sub-registries.
+    ///
+    /// Names are yielded as exposed, i.e. including the prefix of the
+    /// respective (sub-)registry and the unit, but excluding type specific
+    /// suffixes like `_total`. Metrics provided by [`Collector`]s are only
+    /// included if advertised via [`Collector::descriptors`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::{Registry, Unit};
+    /// #
+    /// let mut registry = Registry::default();
+    /// registry.register("requests", "Requests", Counter::<u64>::default());
+    ///
+    /// let db_registry = registry.sub_registry_with_prefix("db");
+    /// db_registry.register_with_unit(
+    ///     "query_time",
+    ///     "Time spent in queries",
+    ///     Unit::Seconds,
+    ///     Counter::<u64>::default(),
+    /// );
+    ///
+    /// assert_eq!(
+    ///     vec!["requests", "db_query_time_seconds"],
+    ///     registry.metric_names().collect::<Vec<_>>(),
+    /// );
+    /// ```
+    pub fn metric_names(&self) -> impl Iterator<Item = String> + '_ {
+        let metrics = self
+            .metrics
+            .iter()
+            .map(move |(descriptor, _)| self.prefixed(descriptor.name_with_unit()));
+
+        let collectors = self
+            .collectors
+            .iter()
+            .flat_map(|collector| collector.descriptors())
+            .map(move |descriptor| self.prefixed(descriptor.name_with_unit()));
+
+        let sub_registries = self.sub_registries.iter().flat_map(
+            |registry| -> Box<dyn Iterator<Item = String> + '_> {
+                Box::new(registry.metric_names())
+            },
+        );
+
+        metrics.chain(collectors).chain(sub_registries)
+    }
+
+    /// Pass the current value of each series of all metrics registered with
+    /// this [`Registry`] and its sub-registries, including those of
+    /// [`Collector`]s, to the given [`MetricVisitor`].
+    ///
+    /// [`AsyncCollector`]s are not visited. See
+    /// [`visitor`] for an example.
+    pub fn visit(&self, visitor: &mut impl MetricVisitor) -> Result<(), std::fmt::Error> {
+        self.encode(&mut visitor::DescriptorEncoder::new(visitor).into())
+    }
+
+    /// The number of series of all metrics registered with this [`Registry`]
+    /// and its sub-registries, e.g. to expose or alert on the cardinality of
+    /// an application's metrics.
+    ///
+    /// Each label set of a [`Family`](crate::metrics::family::Family) counts
+    /// as a series, any other metric as one. Series of collectors are not
+    /// included.
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::metrics::family::Family;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::default();
+    /// registry.register("errors", "Errors", Counter::<u64>::default());
+    ///
+    /// let requests = Family::<Vec<(String, String)>, Counter>::default();
+    /// registry
+    ///     .sub_registry_with_prefix("http")
+    ///     .register("requests", "Requests", requests.clone());
+    /// requests.get_or_create(&vec![("method".to_owned(), "GET".to_owned())]);
+    /// requests.get_or_create(&vec![("method".to_owned(), "PUT".to_owned())]);
+    ///
+    /// assert_eq!(3, registry.total_series());
+    /// ```
+    pub fn total_series(&self) -> usize {
+        self.metrics
+            .iter()
+            .map(|(_, metric)| metric.series())
+            .chain(self.sub_registries.iter().map(Registry::total_series))
+            .sum()
+    }
+
+    /// Encode the subset of metrics whose name passes the given predicate,
+    /// followed by the EOF marker, into the provided [`Write`]r using the
+    /// OpenMetrics text format.
+    ///
+    /// The predicate is called with the full metric family name as exposed,
+    /// i.e. including the prefixes of sub-registries and the unit, but
+    /// excluding type specific suffixes like `_total`. It applies to metrics
+    /// provided by [`Collector`]s as well.
+    ///
+    /// Useful to serve scoped scrapes, e.g. `/metrics?prefix=db_`, from a
+    /// single [`Registry`].
+    ///
+    /// ```
+    /// # use prometheus_client::metrics::counter::Counter;
+    /// # use prometheus_client::registry::Registry;
+    /// #
+    /// let mut registry = Registry::default();
+    /// let requests: Counter = Counter::default();
+    /// registry.register("http_requests", "HTTP requests", requests.clone());
+    ///
+    /// let db_registry = registry.sub_registry_with_prefix("db");
+    /// let queries: Counter = Counter::default();
+    /// db_registry.register("queries", "Database queries", queries.clone());
+    ///
+    /// let mut buffer = String::new();
+    /// registry
+    ///     .encode_filtered(&mut buffer, |name| name.starts_with("db_"))
+    ///     .unwrap();
+    ///
+    /// let expected = "# HELP db_queries Database queries.\n".to_owned() +
+    ///                "# TYPE db_queries counter\n" +
+    ///                "db_queries_total 0\n" +
+    ///                "# EOF\n";
+    /// assert_eq!(expected, buffer);
+    /// ```
+    ///
+    /// [`Write`]: std::fmt::Write
+    pub fn encode_filtered<W: std::fmt::Write>(
+        &self,
+        writer: &mut W,
+        predicate: impl Fn(&str) -> bool,
+    ) -> Result<(), std::fmt::Error> {
+        let mut encoder = text::DescriptorEncoder::new(writer)
+            .with_emit_created(self.emit_created)
+            .with_exemplar_overflow(self.exemplar_overflow);
+        self.encode(&mut encoder.with_filter(&predicate).into())?;
+        text::encode_eof(writer)
+    }
+
+    /// The given labels computed by the label providers of all parent
+    /// registries followed by those computed by the label providers of this
+    /// [`Registry`], see [`Registry::add_label_provider`].
+    fn provided_labels<'a>(
+        &self,
+        provided: &'a [(Cow<'static, str>, Cow<'static, str>)],
+    ) -> Cow<'a, [(Cow<'static, str>, Cow<'static, str>)]> {
+        if self.label_providers.is_empty() {
+            return Cow::Borrowed(provided);
+        }
+        let mut labels = provided.to_vec();
+        for provider in self.label_providers.iter() {
+            labels.extend((provider.0)());
+        }
+        Cow::Owned(labels)
+    }
+
+    /// The labels of this [`Registry`] followed by the given provided labels,
+    /// see [`Registry::provided_labels`].
+    fn current_labels<'a>(
+        &'a self,
+        provided: &[(Cow<'static, str>, Cow<'static, str>)],
+    ) -> Cow<'a, [(Cow<'static, str>, Cow<'static, str>)]> {
+        if provided.is_empty() {
+            return Cow::Borrowed(&self.labels);
+        }
+        Cow::Owned(self.labels.iter().chain(provided).cloned().collect())
+    }
+
+    /// The given labels of this [`Registry`] followed by the labels registered
+    /// with the given metric via [`Registry::register_with_labels`].
+    fn metric_labels<'a>(
+        labels: &'a [(Cow<'static, str>, Cow<'static, str>)],
+        descriptor: &Descriptor,
+    ) -> Cow<'a, [(Cow<'static, str>, Cow<'static, str>)]> {
+        if descriptor.labels.is_empty() {
+            return Cow::Borrowed(labels);
+        }
+        Cow::Owned(labels.iter().chain(&descriptor.labels).cloned().collect())
+    }
+
     pub(crate) fn encode(&self, encoder: &mut DescriptorEncoder) -> Result<(), std::fmt::Error> {
+        self.encode_collected(encoder, &mut std::iter::empty())
+    }
+
+    /// Encode the metrics and collectors of this [`Registry`] and its
+    /// sub-registries, taking the [`Collector`] of each [`AsyncCollector`]
+    /// from `collected`, as returned by [`Registry::collect_async`].
+    pub(crate) fn encode_collected(
+        &self,
+        encoder: &mut DescriptorEncoder,
+        collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+    ) -> Result<(), std::fmt::Error> {
+        self.encode_nested(encoder, collected, &[])
+    }
+
+    /// Like [`Registry::encode_collected`], with `provided` being the labels
+    /// computed by the label providers of all parent registries. Thus each
+    /// label provider is called once per encoding.
+    fn encode_nested(
+        &self,
+        encoder: &mut DescriptorEncoder,
+        collected: &mut dyn Iterator<Item = Box<dyn Collector>>,
+        provided: &[(Cow<'static, str>, Cow<'static, str>)],
+    ) -> Result<(), std::fmt::Error> {
+        let provided = self.provided_labels(provided);
+        let registry_labels = self.current_labels(&provided);
+        let filter = encoder.filter();
         for (descriptor, metric) in self.metrics.iter() {
+            if let Some(filter) = filter {
+                if !filter(&self.prefixed(descriptor.name_with_unit())) {
+                    continue;
+                }
+            }
+
+            let labels = Self::metric_labels(&registry_labels, descriptor);
             let mut descriptor_encoder =
-                encoder.with_prefix_and_labels(self.prefix.as_ref(), &self.labels);
+                encoder.with_prefix_and_labels(self.prefix.as_ref(), &labels);
             let metric_encoder = descriptor_encoder.encode_descriptor(
                 &descriptor.name,
                 &descriptor.help,
@@ -299,55 +1144,437 @@ impl Registry {
             metric.encode(metric_encoder)?;
         }
 
-        for collector in self.collectors.iter() {
+        let own_collected = collected
+            .take(self.async_collector_count())
+            .collect::<Vec<_>>();
+        for collector in self.collectors.iter().chain(own_collected.iter()) {
             let descriptor_encoder =
-                encoder.with_prefix_and_labels(self.prefix.as_ref(), &self.labels);
+                encoder.with_prefix_and_labels(self.prefix.as_ref(), &registry_labels);
             collector.encode(descriptor_encoder)?;
         }
 
         for registry in self.sub_registries.iter() {
-            registry.encode(encoder)?;
+            registry.encode_nested(encoder, collected, &provided)?;
         }
 
         Ok(())
     }
+
+    #[cfg(feature = "async")]
+    fn async_collector_count(&self) -> usize {
+        self.async_collectors.len()
+    }
+
+    #[cfg(not(feature = "async"))]
+    fn async_collector_count(&self) -> usize {
+        0
+    }
+
+    /// Await the [`AsyncCollector`]s of this [`Registry`] and its
+    /// sub-registries, in the order [`Registry::encode_collected`] encodes
+    /// them.
+    ///
+    /// Awaited before encoding starts, as the encoders are not [`Send`].
+    #[cfg(feature = "async")]
+    pub(crate) fn collect_async(&self) -> Pin<Box<dyn Future<Output = Collected> + Send + '_>> {
+        Box::pin(async move {
+            let mut collected = Vec::new();
+            for collector in self.async_collectors.iter() {
+                collected.push(collector.collect().await);
+            }
+            for registry in self.sub_registries.iter() {
+                collected.extend(registry.collect_async().await);
+            }
+            collected
+        })
+    }
+}
+
+/// A [`Registry`] shared between threads, e.g. between the tasks registering
+/// metrics at runtime and the task serving scrapes.
+///
+/// Instead of hand-rolling an `Arc<RwLock<Registry>>`, [`SharedRegistry`]
+/// manages the lock: registration takes the write lock for the duration of
+/// the registration only, encoding takes the read lock. Thus a slow scrape does
+/// not block concurrent scrapes, nor is registration blocked longer than a
+/// single scrape.
+///
+/// # Lock ordering
+///
+/// Encoding a metric takes the metric's own lock while holding the lock of the
+/// [`SharedRegistry`], e.g. the lock of a
+/// [`Family`](crate::metrics::family::Family). To prevent a deadlock, never
+/// access the [`SharedRegistry`] while holding a guard of a metric, e.g. the one
+/// returned by
+/// [`Family::get_or_create`](crate::metrics::family::Family::get_or_create),
+/// and never register while holding the guard returned by
+/// [`SharedRegistry::read`]. Drop the guard first.
+///
+/// ```
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::metrics::family::Family;
+/// # use prometheus_client::registry::SharedRegistry;
+/// #
+/// let registry = SharedRegistry::default();
+///
+/// let requests = Family::<Vec<(String, String)>, Counter>::default();
+/// registry.register("requests", "Requests", requests.clone());
+///
+/// // Drop the guard returned by `get_or_create` before touching the registry.
+/// requests
+///     .get_or_create(&vec![("method".to_owned(), "GET".to_owned())])
+///     .inc();
+///
+/// let scraper = registry.clone();
+/// std::thread::spawn(move || {
+///     let mut buffer = String::new();
+///     scraper.encode(&mut buffer).unwrap();
+///     assert!(buffer.contains("requests_total{method=\"GET\"} 1"));
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SharedRegistry {
+    registry: Arc<RwLock<Registry>>,
+}
+
+impl SharedRegistry {
+    /// Share the given [`Registry`].
+    pub fn new(registry: Registry) -> Self {
+        Self {
+            registry: Arc::new(RwLock::new(registry)),
+        }
+    }
+
+    /// Register a metric, taking the write lock.
+    ///
+    /// See [`Registry::register`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a metric with the same name is already registered. See
+    /// [`SharedRegistry::try_register`] for a non-panicking alternative.
+    pub fn register<N: Into<String>, H: Into<String>>(
+        &self,
+        name: N,
+        help: H,
+        metric: impl Metric,
+    ) {
+        self.registry.write().register(name, help, metric)
+    }
+
+    /// Register a metric, taking the write lock, returning an error if a
+    /// metric with the same name is already registered.
+    ///
+    /// See [`Registry::try_register`] for details.
+    pub fn try_register<N: Into<String>, H: Into<String>>(
+        &self,
+        name: N,
+        help: H,
+        metric: impl Metric,
+    ) -> Result<(), AlreadyRegistered> {
+        self.registry.write().try_register(name, help, metric)
+    }
+
+    /// Register a metric specifying the metric's unit, taking the write lock.
+    ///
+    /// See [`Registry::register_with_unit`] for details.
+    pub fn register_with_unit<N: Into<String>, H: Into<String>>(
+        &self,
+        name: N,
+        help: H,
+        unit: Unit,
+        metric: impl Metric,
+    ) {
+        self.registry
+            .write()
+            .register_with_unit(name, help, unit, metric)
+    }
+
+    /// Register a [`Collector`], taking the write lock.
+    pub fn register_collector(&self, collector: Box<dyn Collector>) {
+        self.registry.write().register_collector(collector)
+    }
+
+    /// Unregister a metric, taking the write lock.
+    ///
+    /// See [`Registry::unregister`] for details.
+    pub fn unregister(&self, name: &str) -> bool {
+        self.registry.write().unregister(name)
+    }
+
+    /// Encode the registered metrics into the provided [`Write`](std::fmt::Write)r
+    /// using the OpenMetrics text format, taking the read lock.
+    ///
+    /// See [`text::encode`] for details.
+    pub fn encode<W: std::fmt::Write>(&self, writer: &mut W) -> Result<(), std::fmt::Error> {
+        text::encode(writer, &self.registry.read())
+    }
+
+    /// Take the read lock, e.g. to encode the registered metrics with any
+    /// other encoder.
+    pub fn read(&self) -> RwLockReadGuard<'_, Registry> {
+        self.registry.read()
+    }
+
+    /// Take the write lock, e.g. to register metrics with a sub-registry.
+    pub fn write(&self) -> RwLockWriteGuard<'_, Registry> {
+        self.registry.write()
+    }
+}
+
+impl From<Registry> for SharedRegistry {
+    fn from(registry: Registry) -> Self {
+        Self::new(registry)
+    }
 }
 
 /// Metric prefix
 #[derive(Clone, Debug)]
-pub(crate) struct Prefix(String);
+pub(crate) struct Prefix {
+    prefix: String,
+    /// Separator between the prefix and the metric name.
+    separator: char,
+}
 
 impl Prefix {
     pub(crate) fn as_str(&self) -> &str {
-        self.0.as_str()
+        self.prefix.as_str()
+    }
+
+    pub(crate) fn separator(&self) -> char {
+        self.separator
     }
 }
 
 impl From<String> for Prefix {
     fn from(s: String) -> Self {
-        Prefix(s)
+        Prefix {
+            prefix: s,
+            separator: Separator::default().0,
+        }
+    }
+}
+
+/// Separator joining prefixes and metric names, see
+/// [`Registry::set_separator`].
+#[derive(Clone, Copy, Debug)]
+struct Separator(char);
+
+impl Default for Separator {
+    fn default() -> Self {
+        Separator('_')
     }
 }
 
 /// OpenMetrics metric descriptor.
+///
+/// Describes a metric by its name, help text, unit and constant labels.
+/// Yielded by [`Registry::iter_metrics`] for registered metrics and advertised
+/// by [`Collector::descriptors`] for collected ones.
+///
+/// E.g. to render a catalog of all registered metrics:
+///
+/// ```
+/// # use prometheus_client::encoding::EncodeMetric;
+/// # use prometheus_client::metrics::counter::Counter;
+/// # use prometheus_client::metrics::gauge::Gauge;
+/// # use prometheus_client::registry::{Registry, Unit};
+/// #
+/// let mut registry = Registry::default();
+/// registry.register("requests", "Requests", Counter::<u64>::default());
+/// registry.register_with_unit("temperature", "Temperature", Unit::Celsius, Gauge::<i64>::default());
+///
+/// let catalog = registry
+///     .iter_metrics()
+///     .map(|(descriptor, metric)| {
+///         format!(
+///             "{} ({}, {}): {}",
+///             descriptor.name(),
+///             metric.metric_type().as_str(),
+///             descriptor.unit().map(Unit::as_str).unwrap_or("-"),
+///             descriptor.help(),
+///         )
+///     })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     vec![
+///         "requests (counter, -): Requests.",
+///         "temperature (gauge, celsius): Temperature.",
+///     ],
+///     catalog,
+/// );
+/// ```
 #[derive(Debug, Clone)]
-struct Descriptor {
+pub struct Descriptor {
     name: String,
     help: String,
     unit: Option<Unit>,
+    labels: Vec<(Cow<'static, str>, Cow<'static, str>)>,
 }
 
 impl Descriptor {
     /// Create new [`Descriptor`].
-    fn new<N: Into<String>, H: Into<String>>(name: N, help: H, unit: Option<Unit>) -> Self {
+    ///
+    /// As with [`Registry::register`], a full stop is appended to `help`.
+    pub fn new<N: Into<String>, H: Into<String>>(name: N, help: H, unit: Option<Unit>) -> Self {
         Self {
             name: name.into(),
             help: help.into() + ".",
             unit,
+            labels: Vec::new(),
         }
     }
+
+    /// The metric name, excluding the unit. Excludes the prefix, unless
+    /// yielded by [`Registry::iter_metrics`].
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The help text.
+    pub fn help(&self) -> &str {
+        &self.help
+    }
+
+    /// The unit, if any.
+    pub fn unit(&self) -> Option<&Unit> {
+        self.unit.as_ref()
+    }
+
+    /// The constant labels of the metric, i.e. those given to
+    /// [`Registry::register_with_labels`], preceded by those of the registry
+    /// if yielded by [`Registry::iter_metrics`].
+    pub fn labels(&self) -> &[(Cow<'static, str>, Cow<'static, str>)] {
+        &self.labels
+    }
+
+    /// The metric name followed by the unit, if any.
+    fn name_with_unit(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{}_{}", self.name, unit.as_str()),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Builder of a [`Registry`], see [`Registry::builder`].
+///
+/// Options not set keep the defaults of [`Registry::default`].
+#[derive(Debug, Default)]
+pub struct RegistryBuilder {
+    registry: Registry,
 }
 
+impl RegistryBuilder {
+    /// Set the prefix prepended to the names of all metrics, see
+    /// [`Registry::with_prefix`].
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        let mut prefix = Prefix::from(prefix.into());
+        prefix.separator = self.registry.separator.0;
+        self.registry.prefix = Some(prefix);
+        self
+    }
+
+    /// Add a constant label to all metrics, see [`Registry::with_labels`].
+    pub fn label(mut self, label: (Cow<'static, str>, Cow<'static, str>)) -> Self {
+        self.registry.labels.push(label);
+        self
+    }
+
+    /// Add constant labels to all metrics, see [`Registry::with_labels`].
+    pub fn labels(
+        mut self,
+        labels: impl Iterator<Item = (Cow<'static, str>, Cow<'static, str>)>,
+    ) -> Self {
+        self.registry.labels.extend(labels);
+        self
+    }
+
+    /// Add a function computing additional labels on each encoding, see
+    /// [`Registry::add_label_provider`].
+    pub fn label_provider(
+        mut self,
+        provider: impl Fn() -> Vec<Label> + Send + Sync + 'static,
+    ) -> Self {
+        self.registry.add_label_provider(provider);
+        self
+    }
+
+    /// Set the separator between the prefix and the metric name, see
+    /// [`Registry::set_separator`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the separator is not a legal metric name character.
+    pub fn separator(mut self, separator: char) -> Self {
+        self.registry.set_separator(separator);
+        self
+    }
+
+    /// Set whether to emit the creation time of metrics, see
+    /// [`Registry::set_emit_created`].
+    pub fn emit_created(mut self, emit_created: bool) -> Self {
+        self.registry.set_emit_created(emit_created);
+        self
+    }
+
+    /// Set how to handle exemplars exceeding the label set length limit, see
+    /// [`Registry::set_exemplar_overflow`].
+    pub fn exemplar_overflow(mut self, exemplar_overflow: ExemplarLabelOverflow) -> Self {
+        self.registry.set_exemplar_overflow(exemplar_overflow);
+        self
+    }
+
+    /// Build the configured [`Registry`].
+    pub fn build(self) -> Registry {
+        self.registry
+    }
+}
+
+/// The collectors returned by the [`AsyncCollector`]s of a [`Registry`], see
+/// [`Registry::collect_async`].
+#[cfg(feature = "async")]
+type Collected = Vec<Box<dyn Collector>>;
+
+/// A label name and value pair, as computed by a label provider, see
+/// [`Registry::add_label_provider`].
+pub type Label = (Cow<'static, str>, Cow<'static, str>);
+
+/// Function computing labels on each encoding, see
+/// [`Registry::add_label_provider`].
+struct LabelProvider(Box<dyn Fn() -> Vec<Label> + Send + Sync>);
+
+impl std::fmt::Debug for LabelProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LabelProvider").finish_non_exhaustive()
+    }
+}
+
+/// Error returned by [`Registry::try_register`] when a metric with the same
+/// name is already registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlreadyRegistered {
+    name: String,
+}
+
+impl AlreadyRegistered {
+    /// The full name of the conflicting metric, including prefix and unit.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for AlreadyRegistered {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "metric with name '{}' is already registered", self.name)
+    }
+}
+
+impl std::error::Error for AlreadyRegistered {}
+
 /// Metric units recommended by Open Metrics.
 ///
 /// See [`Unit::Other`] to specify alternative units.